@@ -0,0 +1,210 @@
+//! 動きの苦手な人やスクリーンリーダーを使う人のための表示
+//!
+//! `describe` はハートの見た目を、色やエスケープシーケンスを使わない文章にする。
+
+use crate::i18n::Locale;
+use crate::{sanitize_input, uses_big_text, HeartColor, HeartConfig};
+
+/// 空でなく `0` / `false` / `no` / `off` でもなければ `--reduced-motion` を有効にする環境変数
+pub const REDUCED_MOTION_ENV: &str = "LOVE_REDUCED_MOTION";
+
+/// ハートの形・色・メッセージを説明する1段落の文章を返す
+///
+/// メッセージはサニタイズし、タブや改行も空白にしてから文章に入れる。
+pub fn describe(config: &HeartConfig, locale: Locale) -> String {
+    let message = config.message.as_deref().map(|message| {
+        sanitize_input(message)
+            .replace(['\t', '\n'], " ")
+            .trim()
+            .to_string()
+    });
+    let message = message.filter(|message| !message.is_empty());
+    match locale {
+        Locale::En => describe_en(config, message.as_deref()),
+        Locale::Ja => describe_ja(config, message.as_deref()),
+    }
+}
+
+fn describe_en(config: &HeartConfig, message: Option<&str>) -> String {
+    let size = if config.petite { "small" } else { "large" };
+    let shape = if config.filled {
+        "filled in solid"
+    } else {
+        "made of the letter \"v\""
+    };
+    let mut text = format!("A {} {} heart {}", size, config.color, shape);
+
+    match message {
+        Some(message) if uses_big_text(config) => text.push_str(&format!(
+            ", with the message \"{}\" in big block letters in the middle.",
+            message
+        )),
+        Some(message) => text.push_str(&format!(
+            ", with the message \"{}\" in the middle.",
+            message
+        )),
+        None => text.push_str(", with no message."),
+    }
+
+    if message.is_some() {
+        match (config.message_color, config.message_bg) {
+            (Some(fg), Some(bg)) => text.push_str(&format!(" The message is {} on {}.", fg, bg)),
+            (Some(fg), None) => text.push_str(&format!(" The message is {}.", fg)),
+            (None, Some(bg)) => text.push_str(&format!(" The message has a {} background.", bg)),
+            (None, None) => {}
+        }
+    }
+    text
+}
+
+fn describe_ja(config: &HeartConfig, message: Option<&str>) -> String {
+    let size = if config.petite {
+        "小さな"
+    } else {
+        "大きな"
+    };
+    let shape = if config.filled {
+        "塗りつぶした"
+    } else {
+        "vの文字で描いた"
+    };
+    let mut text = format!("{}{}の{}ハート。", shape, color_ja(config.color), size);
+
+    match message {
+        Some(message) if uses_big_text(config) => text.push_str(&format!(
+            "真ん中に大きなブロック文字でメッセージ「{}」。",
+            message
+        )),
+        Some(message) => text.push_str(&format!("真ん中にメッセージ「{}」。", message)),
+        None => text.push_str("メッセージはありません。"),
+    }
+
+    if message.is_some() {
+        match (config.message_color, config.message_bg) {
+            (Some(fg), Some(bg)) => text.push_str(&format!(
+                "メッセージは{}の背景に{}の文字。",
+                color_ja(bg),
+                color_ja(fg)
+            )),
+            (Some(fg), None) => text.push_str(&format!("メッセージは{}の文字。", color_ja(fg))),
+            (None, Some(bg)) => text.push_str(&format!("メッセージは{}の背景。", color_ja(bg))),
+            (None, None) => {}
+        }
+    }
+    text
+}
+
+/// 色の日本語の名前
+fn color_ja(color: HeartColor) -> &'static str {
+    match color {
+        HeartColor::Red => "赤",
+        HeartColor::Green => "緑",
+        HeartColor::Blue => "青",
+        HeartColor::Yellow => "黄色",
+        HeartColor::Magenta => "マゼンタ",
+        HeartColor::Cyan => "シアン",
+        HeartColor::White => "白",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigtext::Font;
+    use crate::config::HeartConfigBuilder;
+    use rstest::rstest;
+
+    fn config(message: Option<&str>) -> HeartConfigBuilder {
+        let builder = HeartConfig::builder().color(HeartColor::Red);
+        match message {
+            Some(message) => builder.message(message),
+            None => builder,
+        }
+    }
+
+    // ================================================================
+    // describe: ハートの説明文の仕様
+    // ================================================================
+
+    mod describe_describe {
+        use super::*;
+
+        mod 英語の場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::メッセージあり(
+                config(Some("I love you")),
+                "A large red heart made of the letter \"v\", with the message \"I love you\" in the middle."
+            )]
+            #[case::メッセージなし(
+                config(None),
+                "A large red heart made of the letter \"v\", with no message."
+            )]
+            #[case::小さく塗りつぶしたハート(
+                config(None).petite(true).filled(true),
+                "A small red heart filled in solid, with no message."
+            )]
+            #[case::メッセージの色(
+                config(Some("Hi"))
+                    .message_color(HeartColor::Yellow)
+                    .message_bg(HeartColor::Blue),
+                "A large red heart made of the letter \"v\", with the message \"Hi\" in the middle. The message is yellow on blue."
+            )]
+            #[case::ブロック文字(
+                config(Some("Hi")).font(Font::builtin()),
+                "A large red heart made of the letter \"v\", with the message \"Hi\" in big block letters in the middle."
+            )]
+            fn 形と色とメッセージを説明する(
+                #[case] config: HeartConfigBuilder,
+                #[case] expected: &str,
+            ) {
+                assert_eq!(describe(&config.build().unwrap(), Locale::En), expected);
+            }
+        }
+
+        mod 日本語の場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::メッセージあり(
+                config(Some("I love you")),
+                "vの文字で描いた赤の大きなハート。真ん中にメッセージ「I love you」。"
+            )]
+            #[case::メッセージなし(
+                config(None).petite(true).filled(true),
+                "塗りつぶした赤の小さなハート。メッセージはありません。"
+            )]
+            #[case::メッセージの色(
+                config(Some("Hi")).message_color(HeartColor::White),
+                "vの文字で描いた赤の大きなハート。真ん中にメッセージ「Hi」。メッセージは白の文字。"
+            )]
+            fn 形と色とメッセージを説明する(
+                #[case] config: HeartConfigBuilder,
+                #[case] expected: &str,
+            ) {
+                assert_eq!(describe(&config.build().unwrap(), Locale::Ja), expected);
+            }
+        }
+
+        mod メッセージに制御文字を含む場合 {
+            use super::*;
+
+            #[test]
+            fn エスケープシーケンスを取り除き改行は空白にする() {
+                let text = describe(
+                    &config(Some("\x1b[31mHi\nthere\x07")).build().unwrap(),
+                    Locale::En,
+                );
+                assert!(!text.contains('\x1b'));
+                assert!(text.contains("\"[31mHi there\""));
+            }
+
+            #[test]
+            fn 空白しか残らなければメッセージなしとして説明する() {
+                let text = describe(&config(Some("\x1b\t")).build().unwrap(), Locale::En);
+                assert!(text.ends_with("with no message."));
+            }
+        }
+    }
+}
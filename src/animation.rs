@@ -0,0 +1,643 @@
+use crate::controls::DEFAULT_TICK_MS;
+use crate::effects;
+use crate::{heart_sizes, love_cells, message_row, Cell, HeartConfig, MessageState};
+use crossterm::cursor::MoveTo;
+use crossterm::queue;
+use crossterm::style::{
+    Attribute, Color, Print, ResetColor, SetAttribute, SetAttributes, SetBackgroundColor,
+    SetForegroundColor,
+};
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// マーキー表示の既定の速さ (1秒あたりの文字数)
+pub const DEFAULT_MARQUEE_SPEED: u64 = 8;
+
+/// 動きを控える場合に、ハートが現れきる・消えきるまでのtick数
+pub const FADE_TICKS: i32 = 4;
+
+/// 画面1枚ぶんの色つきの文字と、次のフレームまでの推奨の待ち時間
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frame {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "delay_ms", with = "crate::serialize::millis")
+    )]
+    pub delay: Duration,
+}
+
+impl Frame {
+    /// 空白で埋めたフレーム
+    pub fn new(width: u16, height: u16, delay: Duration) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec![Frame::blank(); width as usize * height as usize],
+            delay,
+        }
+    }
+
+    /// 何も描かれていない1文字 (色はターミナルの既定のまま)
+    pub fn blank() -> Cell {
+        Cell::new(' ', Color::Reset)
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// 左上を原点とした位置の文字 (範囲外はNone)
+    pub fn cell(&self, x: u16, y: u16) -> Option<&Cell> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells
+            .get(y as usize * self.width as usize + x as usize)
+    }
+
+    /// 上から1行ずつ返す
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        // 幅0のフレームでもchunksが panic しないようにする
+        self.cells.chunks(self.width.max(1) as usize)
+    }
+
+    /// 色を除いた文字だけを行ごとに改行でつないで返す
+    pub fn text(&self) -> String {
+        self.rows()
+            .map(|row| row.iter().map(|cell| cell.ch).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// ANSIエスケープシーケンスで、画面の左上からフレーム全体を描く
+    ///
+    /// 背景色のない文字は `background` で描く (Noneならターミナルの背景のまま)。
+    pub fn write_ansi(&self, out: &mut impl Write, background: Option<Color>) -> io::Result<()> {
+        for (y, row) in self.rows().enumerate() {
+            queue!(out, MoveTo(0, y as u16))?;
+            write_cells(out, row, background)?;
+        }
+        queue!(out, SetAttribute(Attribute::Reset), ResetColor)
+    }
+
+    /// カーソルを動かさずに、色つきの行を上から改行で区切って書き出す
+    ///
+    /// パイプやファイルに書き出しても崩れないよう、各行の末尾の空白は書かない。
+    pub fn write_ansi_lines(
+        &self,
+        out: &mut impl Write,
+        background: Option<Color>,
+    ) -> io::Result<()> {
+        for row in self.rows() {
+            let is_drawn = |cell: &Cell| cell.ch != ' ' || cell.bg.or(background).is_some();
+            let end = row.iter().rposition(is_drawn).map_or(0, |end| end + 1);
+            write_cells(out, &row[..end], background)?;
+            queue!(out, SetAttribute(Attribute::Reset), ResetColor, Print('\n'))?;
+        }
+        Ok(())
+    }
+
+    /// ハート1つぶんの大きさのフレームに、ハート全体が見えている静止画を描く
+    ///
+    /// ハートが描かれない上下左右の余白は含めない。
+    pub fn still(config: &HeartConfig) -> Self {
+        let (heart_size, _) = heart_sizes(config);
+        let state = MessageState::default();
+        let is_drawn = |cell: &Cell| cell.ch != ' ' || cell.bg.is_some();
+
+        let (mut start, mut end) = (usize::MAX, 0);
+        let (mut top, mut bottom) = (heart_size, 0);
+        for y in 0..=heart_size {
+            let cells = love_cells(y, config, &state);
+            if let (Some(first), Some(last)) = (
+                cells.iter().position(is_drawn),
+                cells.iter().rposition(is_drawn),
+            ) {
+                start = start.min(first);
+                end = end.max(last);
+                top = top.min(y);
+                bottom = bottom.max(y);
+            }
+        }
+        let (start, top) = (start.min(end), top.min(bottom));
+
+        let width = (end - start + 1) as u16;
+        let height = (bottom - top + 1) as u16;
+        let mut frame = Frame::new(width, height, Duration::ZERO);
+        frame.draw_heart(-(start as i32), -top, config, &state);
+        frame
+    }
+
+    /// 左上を (left, top) としてハートを重ねる
+    ///
+    /// 外側の空白は下に描いたものを消さないよう描かず、フレームからはみ出す部分は切り落とす。
+    pub fn draw_heart(&mut self, left: i32, top: i32, config: &HeartConfig, state: &MessageState) {
+        let (heart_size, _) = heart_sizes(config);
+
+        for y in 0..=heart_size {
+            let line = top + y;
+            if line < 0 || line >= self.height as i32 {
+                continue;
+            }
+
+            let cells = love_cells(y, config, state);
+            let is_drawn = |cell: &Cell| cell.ch != ' ' || cell.bg.is_some();
+            let (Some(start), Some(end)) = (
+                cells.iter().position(is_drawn),
+                cells.iter().rposition(is_drawn),
+            ) else {
+                continue;
+            };
+
+            for (i, cell) in cells.iter().enumerate().take(end + 1).skip(start) {
+                let column = left + i as i32;
+                if column < 0 || column >= self.width as i32 {
+                    continue;
+                }
+                let index = line as usize * self.width as usize + column as usize;
+                // 読み込んだフレームは大きさと文字数が食い違うことがあるため、範囲外は描かない
+                if let Some(slot) = self.cells.get_mut(index) {
+                    *slot = *cell;
+                }
+            }
+        }
+    }
+
+    /// 描かれた文字の色を `brightness` 倍の明るさにする (1.0以上なら元の色のまま)
+    ///
+    /// 何も描かれていない文字は、ターミナルの既定の色のまま残す。
+    pub fn dim(&mut self, brightness: f32) {
+        if brightness >= 1.0 {
+            return;
+        }
+        for cell in self
+            .cells
+            .iter_mut()
+            .filter(|cell| **cell != Frame::blank())
+        {
+            cell.fg = effects::dim(cell.fg, brightness);
+            cell.bg = cell.bg.map(|bg| effects::dim(bg, brightness));
+        }
+    }
+}
+
+/// 1行ぶんの文字を、色や装飾が変わる所でだけ設定し直しながら書き出す
+fn write_cells(out: &mut impl Write, cells: &[Cell], background: Option<Color>) -> io::Result<()> {
+    let mut style = None;
+    for cell in cells {
+        let bg = cell.bg.or(background);
+        if style != Some((cell.fg, bg, cell.attributes)) {
+            // 背景色と装飾は個別に外せないため、一度全て戻してから設定する
+            queue!(out, SetAttribute(Attribute::Reset))?;
+            queue!(out, SetForegroundColor(cell.fg))?;
+            if let Some(bg) = bg {
+                queue!(out, SetBackgroundColor(bg))?;
+            }
+            queue!(out, SetAttributes(cell.attributes))?;
+            style = Some((cell.fg, bg, cell.attributes));
+        }
+        queue!(out, Print(cell.ch))?;
+    }
+    Ok(())
+}
+
+/// 画面の下から上へハートが浮かぶアニメーションを、フレームごとに返す
+///
+/// 各フレームの `delay` だけ待ってから次のフレームを取り出せば、`love` と同じ速さで浮かぶ。
+/// ハートが画面の上端を抜けきると終わる。
+/// 途中の状態を書き出せるが、`tick` などを検証せずに組み立て直せないよう読み込みはしない。
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Animation {
+    config: HeartConfig,
+    width: u16,
+    height: u16,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "tick_ms", with = "crate::serialize::millis")
+    )]
+    tick: Duration,
+    marquee_speed: u64,
+    reduced_motion: bool,
+    y: i32,
+}
+
+impl Animation {
+    pub fn new(config: HeartConfig, width: u16, height: u16) -> Self {
+        Animation {
+            config,
+            width,
+            height,
+            tick: Duration::from_millis(DEFAULT_TICK_MS),
+            marquee_speed: DEFAULT_MARQUEE_SPEED,
+            reduced_motion: false,
+            y: 0,
+        }
+    }
+
+    /// 1行浮かぶ間隔を変える
+    pub fn with_tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    /// マーキー表示の速さ (1秒あたりの文字数) を変える
+    pub fn with_marquee_speed(mut self, marquee_speed: u64) -> Self {
+        self.marquee_speed = marquee_speed;
+        self
+    }
+
+    /// 浮かべる代わりに画面の中央に置いたまま、現れる時と消える時だけフェードする
+    ///
+    /// フレーム数は浮かぶ場合と変わらない。
+    pub fn with_reduced_motion(mut self, reduced_motion: bool) -> Self {
+        self.reduced_motion = reduced_motion;
+        self
+    }
+
+    pub fn config(&self) -> &HeartConfig {
+        &self.config
+    }
+
+    /// ハートの左上の位置 (中央寄せで、最新の行が最下段に来る位置から浮かせる)
+    ///
+    /// 動きを控える場合は画面の上下の中央に置いたままにする。
+    pub fn heart_origin(&self) -> (i32, i32) {
+        let (heart_size, half_size) = heart_sizes(&self.config);
+        let left = ((self.width as i32 / 2) - half_size) - 10;
+        let top = match self.reduced_motion {
+            true => (self.height as i32 - heart_size - 1) / 2,
+            false => self.height as i32 - 1 - self.y,
+        };
+        (left, top)
+    }
+
+    /// 今のフレームでのメッセージの状態
+    pub fn message_state(&self) -> MessageState {
+        // 間隔や速さがどれだけ大きくても溢れないよう、上限で止める
+        let started = self.tick.saturating_mul(self.y.max(0) as u32);
+        // エフェクトはメッセージ行が見えてから進める (動きを控える場合は最初から見えている)
+        let shown = match self.reduced_motion {
+            true => self.y,
+            false => (self.y - message_row(&self.config)).max(0),
+        };
+        let started_ms = u64::try_from(started.as_millis()).unwrap_or(u64::MAX);
+        MessageState {
+            marquee_offset: (started_ms.saturating_mul(self.marquee_speed) / 1000) as usize,
+            elapsed: self.tick.saturating_mul(shown as u32),
+        }
+    }
+
+    /// 今のフレームを描く
+    pub fn frame(&self) -> Frame {
+        let mut frame = Frame::new(self.width, self.height, self.tick);
+        let brightness = self.brightness();
+        if brightness > 0.0 {
+            let (left, top) = self.heart_origin();
+            frame.draw_heart(left, top, &self.config, &self.message_state());
+            frame.dim(brightness);
+        }
+        frame
+    }
+
+    /// 動きを控える場合のハートの明るさ (表示の始めと終わりの `FADE_TICKS` で変える)
+    fn brightness(&self) -> f32 {
+        if !self.reduced_motion {
+            return 1.0;
+        }
+        let remaining = self.remaining() as i32 - 1;
+        (self.y.min(remaining) as f32 / FADE_TICKS as f32).clamp(0.0, 1.0)
+    }
+
+    /// 残りのフレーム数
+    fn remaining(&self) -> usize {
+        let (heart_size, _) = heart_sizes(&self.config);
+        (self.height as i32 + heart_size - self.y).max(0) as usize
+    }
+}
+
+impl Iterator for Animation {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        if self.remaining() == 0 {
+            return None;
+        }
+        let frame = self.frame();
+        self.y += 1;
+        Some(frame)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Animation {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HeartConfigBuilder;
+    use crate::effects::MessageEffect;
+    use crate::{love_row, HeartColor, HEART_SIZE_L};
+
+    fn builder(message: Option<&str>) -> HeartConfigBuilder {
+        let builder = HeartConfig::builder().color(HeartColor::Red);
+        match message {
+            Some(message) => builder.message(message),
+            None => builder,
+        }
+    }
+
+    fn config(message: Option<&str>) -> HeartConfig {
+        builder(message).build().unwrap()
+    }
+
+    // ================================================================
+    // Frame: フレームの仕様
+    // ================================================================
+
+    mod describe_frame {
+        use super::*;
+
+        mod 作った直後の場合 {
+            use super::*;
+
+            #[test]
+            fn 全て空白で埋まっている() {
+                let frame = Frame::new(4, 2, Duration::ZERO);
+                assert_eq!(frame.text(), "    \n    ");
+                assert!(frame.rows().flatten().all(|cell| *cell == Frame::blank()));
+            }
+
+            #[test]
+            fn 範囲外の位置はnoneを返す() {
+                let frame = Frame::new(4, 2, Duration::ZERO);
+                assert!(frame.cell(3, 1).is_some());
+                assert!(frame.cell(4, 0).is_none());
+                assert!(frame.cell(0, 2).is_none());
+            }
+        }
+
+        mod ハートを重ねた場合 {
+            use super::*;
+
+            #[test]
+            fn love_rowと同じ文字を描く() {
+                let config = config(Some("Hi"));
+                let mut frame = Frame::new(60, 30, Duration::ZERO);
+                frame.draw_heart(5, 3, &config, &MessageState::default());
+
+                let lines: Vec<String> = frame.text().lines().map(str::to_string).collect();
+                for y in 0..=HEART_SIZE_L {
+                    let expected = love_row(y, &config);
+                    let line = &lines[3 + y as usize][5..5 + expected.len()];
+                    assert_eq!(line.trim_end(), expected.trim_end());
+                }
+            }
+
+            #[test]
+            fn 外側の空白は下に描いたものを消さない() {
+                let mut frame = Frame::new(60, 30, Duration::ZERO);
+                frame.draw_heart(0, 0, &config(None), &MessageState::default());
+                frame.draw_heart(20, 0, &config(None), &MessageState::default());
+                // 右のハートの左上の外側から、左のハートが透けて見える
+                assert_eq!(frame.cell(24, 4).unwrap().ch, 'v');
+            }
+
+            #[test]
+            fn はみ出す部分は切り落とす() {
+                let mut frame = Frame::new(10, 5, Duration::ZERO);
+                frame.draw_heart(-15, -8, &config(None), &MessageState::default());
+                assert_eq!(frame.rows().count(), 5);
+                assert!(frame.rows().all(|row| row.len() == 10));
+                assert!(frame.text().contains('v'));
+            }
+        }
+
+        mod 静止画を描いた場合 {
+            use super::*;
+
+            #[test]
+            fn ハート全体が左右の余白なしで収まる() {
+                let frame = Frame::still(&config(Some("Hi")));
+                let text = frame.text();
+                let lines: Vec<&str> = text.lines().collect();
+
+                assert!(lines.len() <= HEART_SIZE_L as usize);
+                assert!(lines.first().unwrap().contains('v'));
+                assert!(lines.last().unwrap().contains('v'));
+                assert!(lines.iter().any(|line| line.starts_with('v')));
+                assert!(lines.iter().any(|line| line.ends_with('v')));
+                assert!(text.contains("Hi"));
+            }
+
+            #[test]
+            fn 小さいハートも収まる() {
+                let config = builder(None).petite(true).build().unwrap();
+                let frame = Frame::still(&config);
+                assert!(frame.height() as i32 <= crate::HEART_SIZE_S);
+                assert!(frame.width() < Frame::still(&self::config(None)).width());
+            }
+        }
+
+        mod 暗くした場合 {
+            use super::*;
+
+            #[test]
+            fn 描かれた文字だけを暗くする() {
+                let mut frame = Frame::new(60, 30, Duration::ZERO);
+                frame.draw_heart(0, 0, &config(None), &MessageState::default());
+                frame.dim(0.5);
+
+                let heart = frame.rows().flatten().find(|cell| cell.ch == 'v').unwrap();
+                assert_eq!(heart.fg, Color::Rgb { r: 128, g: 0, b: 0 });
+                assert_eq!(*frame.cell(59, 29).unwrap(), Frame::blank());
+            }
+
+            #[test]
+            fn 明るさが1以上なら色を変えない() {
+                let mut frame = Frame::still(&config(None));
+                let original = frame.clone();
+                frame.dim(1.0);
+                assert_eq!(frame, original);
+            }
+        }
+
+        mod 行ごとに書き出した場合 {
+            use super::*;
+
+            #[test]
+            fn カーソルを動かさずに改行で区切る() {
+                let frame = Frame::still(&config(None));
+                let mut out = Vec::new();
+                frame.write_ansi_lines(&mut out, None).unwrap();
+                let out = String::from_utf8(out).unwrap();
+
+                assert_eq!(out.matches('\n').count(), frame.height() as usize);
+                assert!(!out.contains(";1H"));
+                // 行末の空白は書かない
+                assert!(out
+                    .lines()
+                    .all(|line| line.trim_end_matches("\x1b[0m").ends_with('v')));
+            }
+        }
+    }
+
+    // ================================================================
+    // Animation: アニメーションの仕様
+    // ================================================================
+
+    mod describe_animation {
+        use super::*;
+
+        mod 最後まで取り出した場合 {
+            use super::*;
+
+            #[test]
+            fn 画面の高さとハートの大きさぶんのフレームを返す() {
+                let animation = Animation::new(config(None), 80, 24);
+                assert_eq!(animation.len(), 24 + HEART_SIZE_L as usize);
+                assert_eq!(animation.count(), 24 + HEART_SIZE_L as usize);
+            }
+
+            #[test]
+            fn ハートの上の行から順に最下行に現れる() {
+                let frames: Vec<Frame> = Animation::new(config(None), 80, 24).collect();
+                let lines = |i: usize| {
+                    frames[i]
+                        .text()
+                        .lines()
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                };
+
+                // 最初の3行はハートの上の余白
+                assert!(frames[2].text().trim().is_empty());
+                let lines = lines(3);
+                assert_eq!(lines.len(), 24);
+                assert!(lines[..23].iter().all(|line| line.trim().is_empty()));
+                assert!(lines[23].contains("vvvv"));
+            }
+
+            #[test]
+            fn ハートの先端が最上行を抜けるまで続く() {
+                let frames: Vec<Frame> = Animation::new(config(None), 80, 24).collect();
+                let last = frames
+                    .iter()
+                    .rev()
+                    .find(|frame| frame.text().contains('v'))
+                    .unwrap()
+                    .text();
+                let lines: Vec<&str> = last.lines().collect();
+                assert_eq!(lines[0].trim(), "vv");
+                assert!(lines[1..].iter().all(|line| line.trim().is_empty()));
+            }
+        }
+
+        mod 間隔を指定した場合 {
+            use super::*;
+
+            #[test]
+            fn フレームの待ち時間に使う() {
+                let tick = Duration::from_millis(120);
+                let mut animation = Animation::new(config(None), 80, 24).with_tick(tick);
+                assert_eq!(animation.next().unwrap().delay, tick);
+            }
+
+            #[test]
+            fn とても長い間隔でもメッセージの状態が溢れない() {
+                let mut animation = Animation::new(config(Some("Hi")), 80, 24)
+                    .with_tick(Duration::from_millis(u64::MAX))
+                    .with_marquee_speed(u64::MAX);
+                animation.nth(20);
+                let state = animation.message_state();
+                assert_eq!(state.marquee_offset, (u64::MAX / 1000) as usize);
+                assert!(state.elapsed >= Duration::from_millis(u64::MAX));
+            }
+        }
+
+        mod エフェクトを指定した場合 {
+            use super::*;
+
+            #[test]
+            fn メッセージ行が見えてから進める() {
+                let config = builder(Some("Hi"))
+                    .message_effect(MessageEffect::Typewriter)
+                    .build()
+                    .unwrap();
+                let tick = Duration::from_millis(100);
+                let mut animation = Animation::new(config, 80, 24).with_tick(tick);
+                let row = message_row(animation.config());
+                for _ in 0..row {
+                    assert_eq!(animation.message_state().elapsed, Duration::ZERO);
+                    animation.next();
+                }
+                animation.next();
+                animation.next();
+                assert_eq!(animation.message_state().elapsed, tick * 2);
+            }
+        }
+
+        mod 動きを控えた場合 {
+            use super::*;
+
+            fn still(message: Option<&str>) -> Animation {
+                Animation::new(config(message), 80, 24).with_reduced_motion(true)
+            }
+
+            #[test]
+            fn 浮かぶ場合と同じフレーム数を返す() {
+                assert_eq!(
+                    still(None).count(),
+                    Animation::new(config(None), 80, 24).count()
+                );
+            }
+
+            #[test]
+            fn ハートを同じ位置に置いたままにする() {
+                let frames: Vec<String> = still(Some("Hi"))
+                    .skip(FADE_TICKS as usize)
+                    .take(10)
+                    .map(|frame| frame.text())
+                    .collect();
+                assert!(frames[0].contains('v'));
+                assert!(frames.iter().all(|text| *text == frames[0]));
+            }
+
+            #[test]
+            fn 現れる時と消える時だけ暗くする() {
+                let frames: Vec<Frame> = still(None).collect();
+                let color = |frame: &Frame| {
+                    frame
+                        .rows()
+                        .flatten()
+                        .find(|cell| cell.ch == 'v')
+                        .map(|cell| cell.fg)
+                };
+
+                assert_eq!(color(&frames[0]), None);
+                assert_eq!(color(frames.last().unwrap()), None);
+                assert_ne!(color(&frames[1]), color(&frames[FADE_TICKS as usize]));
+                assert_eq!(color(&frames[FADE_TICKS as usize]), Some(Color::Red));
+            }
+
+            #[test]
+            fn エフェクトは最初から進める() {
+                let tick = Duration::from_millis(100);
+                let mut animation = still(Some("Hi")).with_tick(tick);
+                animation.nth(1);
+                assert_eq!(animation.message_state().elapsed, tick * 2);
+            }
+        }
+    }
+}
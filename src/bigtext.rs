@@ -0,0 +1,514 @@
+use crate::error::{Error, Reason, Result};
+use std::collections::BTreeMap;
+use std::io::Read;
+
+// フォントファイルから読み込む量の上限 (リソース枯渇対策)
+pub const MAX_FONT_BYTES: u64 = 1024 * 1024;
+
+// FIGletフォントが収録する文字 (ASCIIの印字可能文字) の範囲
+const FIGLET_FIRST_CHAR: u8 = b' ';
+const FIGLET_LAST_CHAR: u8 = b'~';
+
+// フォントの高さと文字の間の列数の上限 (ハートに収まらない大きさは描けないため)
+pub const MAX_FONT_HEIGHT: usize = 64;
+pub const MAX_FONT_SPACING: usize = 16;
+pub const MAX_GLYPH_WIDTH: usize = 64;
+
+// フォントにない文字の代わりに使う文字
+const FALLBACK_CHAR: char = '?';
+
+// 組み込みフォント (3x5のブロック文字)
+const BUILTIN_HEIGHT: usize = 5;
+const BUILTIN_GLYPHS: [(char, [&str; BUILTIN_HEIGHT]); 47] = [
+    ('A', [" # ", "# #", "###", "# #", "# #"]),
+    ('B', ["## ", "# #", "## ", "# #", "## "]),
+    ('C', [" ##", "#  ", "#  ", "#  ", " ##"]),
+    ('D', ["## ", "# #", "# #", "# #", "## "]),
+    ('E', ["###", "#  ", "## ", "#  ", "###"]),
+    ('F', ["###", "#  ", "## ", "#  ", "#  "]),
+    ('G', [" ##", "#  ", "# #", "# #", " ##"]),
+    ('H', ["# #", "# #", "###", "# #", "# #"]),
+    ('I', ["###", " # ", " # ", " # ", "###"]),
+    ('J', ["  #", "  #", "  #", "# #", " # "]),
+    ('K', ["# #", "# #", "## ", "# #", "# #"]),
+    ('L', ["#  ", "#  ", "#  ", "#  ", "###"]),
+    ('M', ["# #", "###", "###", "# #", "# #"]),
+    ('N', ["## ", "# #", "# #", "# #", "# #"]),
+    ('O', [" # ", "# #", "# #", "# #", " # "]),
+    ('P', ["## ", "# #", "## ", "#  ", "#  "]),
+    ('Q', [" # ", "# #", "# #", "## ", " ##"]),
+    ('R', ["## ", "# #", "## ", "# #", "# #"]),
+    ('S', [" ##", "#  ", " # ", "  #", "## "]),
+    ('T', ["###", " # ", " # ", " # ", " # "]),
+    ('U', ["# #", "# #", "# #", "# #", "###"]),
+    ('V', ["# #", "# #", "# #", "# #", " # "]),
+    ('W', ["# #", "# #", "###", "###", "# #"]),
+    ('X', ["# #", "# #", " # ", "# #", "# #"]),
+    ('Y', ["# #", "# #", " # ", " # ", " # "]),
+    ('Z', ["###", "  #", " # ", "#  ", "###"]),
+    ('0', ["###", "# #", "# #", "# #", "###"]),
+    ('1', [" # ", "## ", " # ", " # ", "###"]),
+    ('2', ["## ", "  #", " # ", "#  ", "###"]),
+    ('3', ["## ", "  #", " # ", "  #", "## "]),
+    ('4', ["# #", "# #", "###", "  #", "  #"]),
+    ('5', ["###", "#  ", "## ", "  #", "## "]),
+    ('6', [" ##", "#  ", "###", "# #", "###"]),
+    ('7', ["###", "  #", " # ", " # ", " # "]),
+    ('8', ["###", "# #", "###", "# #", "###"]),
+    ('9', ["###", "# #", "###", "  #", "## "]),
+    (' ', ["   ", "   ", "   ", "   ", "   "]),
+    ('!', [" # ", " # ", " # ", "   ", " # "]),
+    ('?', ["## ", "  #", " # ", "   ", " # "]),
+    ('.', ["   ", "   ", "   ", "   ", " # "]),
+    (',', ["   ", "   ", "   ", " # ", "#  "]),
+    ('-', ["   ", "   ", "###", "   ", "   "]),
+    ('\'', [" # ", " # ", "   ", "   ", "   "]),
+    (':', ["   ", " # ", "   ", " # ", "   "]),
+    ('<', ["  #", " # ", "#  ", " # ", "  #"]),
+    ('>', ["#  ", " # ", "  #", " # ", "#  "]),
+    ('+', ["   ", " # ", "###", " # ", "   "]),
+];
+
+/// 複数行のブロック文字でメッセージを描くためのフォント
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "FontData"))]
+pub struct Font {
+    height: usize,
+    // 文字と文字の間に空ける列数
+    spacing: usize,
+    glyphs: BTreeMap<char, Vec<String>>,
+}
+
+impl Font {
+    /// 組み込みのブロック文字フォント (英大文字・数字・一部の記号)
+    pub fn builtin() -> Self {
+        let glyphs = BUILTIN_GLYPHS
+            .iter()
+            .map(|(c, lines)| (*c, lines.iter().map(|line| line.to_string()).collect()))
+            .collect();
+        Font {
+            height: BUILTIN_HEIGHT,
+            spacing: 1,
+            glyphs,
+        }
+    }
+
+    /// FIGletフォント (.flf) を読み込む
+    pub fn read_flf<R: Read>(reader: R) -> Result<Self> {
+        let mut bytes = Vec::new();
+        reader
+            .take(MAX_FONT_BYTES + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|e| Error::Config(Reason::FontUnreadable(e)))?;
+
+        if bytes.len() as u64 > MAX_FONT_BYTES {
+            return Err(Error::Config(Reason::FontTooLarge {
+                max: MAX_FONT_BYTES,
+            }));
+        }
+
+        // 古いフォントにはLatin-1の文字が含まれることがあるため、壊れた文字は置き換える
+        Font::parse_flf(&String::from_utf8_lossy(&bytes))
+    }
+
+    /// FIGletフォント (.flf) の内容を解釈する
+    pub fn parse_flf(text: &str) -> Result<Self> {
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or("");
+
+        let signature = header
+            .strip_prefix("flf2a")
+            .ok_or_else(|| invalid_font(Reason::NotFiglet))?;
+        let mut chars = signature.chars();
+        let hardblank = chars
+            .next()
+            .ok_or_else(|| invalid_font(Reason::InvalidFigletHeader))?;
+        let params: Vec<usize> = chars
+            .as_str()
+            .split_whitespace()
+            .map(|p| p.parse::<i64>().map(|n| n.max(0) as usize))
+            .collect::<Result<_, _>>()
+            .map_err(|_| invalid_font(Reason::InvalidFigletHeader))?;
+
+        let (height, comment_lines) = match params.as_slice() {
+            [height, _baseline, _max_length, _old_layout, comment_lines, ..]
+                if (1..=MAX_FONT_HEIGHT).contains(height) =>
+            {
+                (*height, *comment_lines)
+            }
+            _ => return Err(invalid_font(Reason::InvalidFigletHeader)),
+        };
+
+        let mut lines = lines.skip(comment_lines);
+        let mut glyphs = BTreeMap::new();
+        for code in FIGLET_FIRST_CHAR..=FIGLET_LAST_CHAR {
+            let mut glyph = Vec::new();
+            for _ in 0..height {
+                let line = lines
+                    .next()
+                    .ok_or_else(|| invalid_font(Reason::FigletTruncated))?;
+                let row = strip_endmark(line).replace(hardblank, " ");
+                check_glyph_row(&row).map_err(invalid_font)?;
+                glyph.push(row);
+            }
+            glyphs.insert(code as char, glyph);
+        }
+
+        Ok(Font {
+            height,
+            spacing: 0,
+            glyphs,
+        })
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// テキストをブロック文字の行に展開する
+    pub fn render(&self, text: &str) -> Vec<String> {
+        self.render_indexed(text)
+            .into_iter()
+            .map(|line| {
+                line.into_iter()
+                    .map(|pixel| pixel.map_or(' ', |(c, _)| c))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// テキストをブロック文字の行に展開し、各画素が元のテキストの何文字目かを添えて返す
+    pub(crate) fn render_indexed(&self, text: &str) -> Vec<Vec<Option<(char, usize)>>> {
+        let mut lines = vec![Vec::new(); self.height];
+
+        for (index, c) in text.chars().enumerate() {
+            let glyph = self.glyph(c);
+            let width = glyph
+                .iter()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or(0);
+
+            for (line, row) in lines.iter_mut().zip(glyph) {
+                let mut pixels: Vec<_> = row
+                    .chars()
+                    .map(|p| if p == ' ' { None } else { Some((p, index)) })
+                    .collect();
+                // 行ごとに長さが違うフォントでも、文字の幅を揃える
+                pixels.resize(width + self.spacing, None);
+                line.extend(pixels);
+            }
+        }
+
+        // 末尾の文字の後ろには間を空けない
+        for line in &mut lines {
+            let width = line.len().saturating_sub(self.spacing);
+            line.truncate(width);
+        }
+        lines
+    }
+
+    fn glyph(&self, c: char) -> &[String] {
+        self.glyphs
+            .get(&c)
+            .or_else(|| self.glyphs.get(&c.to_ascii_uppercase()))
+            .or_else(|| self.glyphs.get(&FALLBACK_CHAR))
+            .map(|glyph| glyph.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+/// 読み込んだフォントの高さと文字の間の列数を確かめる前の内容
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct FontData {
+    height: usize,
+    spacing: usize,
+    glyphs: BTreeMap<char, Vec<String>>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<FontData> for Font {
+    type Error = Reason;
+
+    fn try_from(data: FontData) -> std::result::Result<Self, Self::Error> {
+        if !(1..=MAX_FONT_HEIGHT).contains(&data.height) {
+            return Err(Reason::FontHeight {
+                height: data.height,
+                max: MAX_FONT_HEIGHT,
+            });
+        }
+        if data.spacing > MAX_FONT_SPACING {
+            return Err(Reason::FontSpacing {
+                spacing: data.spacing,
+                max: MAX_FONT_SPACING,
+            });
+        }
+        data.glyphs
+            .values()
+            .flatten()
+            .try_for_each(|row| check_glyph_row(row))?;
+        Ok(Font {
+            height: data.height,
+            spacing: data.spacing,
+            glyphs: data.glyphs,
+        })
+    }
+}
+
+/// 文字の形の1行が端末にそのまま描ける内容かを確かめる
+///
+/// 制御文字を含む行はエスケープシーケンスとして端末を操作できてしまうため受け付けない
+fn check_glyph_row(row: &str) -> std::result::Result<(), Reason> {
+    if row.chars().any(char::is_control) {
+        return Err(Reason::FontControlCharacters);
+    }
+    if row.chars().count() > MAX_GLYPH_WIDTH {
+        return Err(Reason::GlyphTooWide {
+            max: MAX_GLYPH_WIDTH,
+        });
+    }
+    Ok(())
+}
+
+/// フォントの内容が不正な場合のエラー
+fn invalid_font(reason: Reason) -> Error {
+    Error::Config(reason)
+}
+
+/// FIGletの行末の終端記号 (通常は @、最終行は @@) を取り除く
+fn strip_endmark(line: &str) -> &str {
+    let line = line.trim_end_matches(['\r', '\n']);
+    match line.chars().last() {
+        Some(endmark) => line.trim_end_matches(endmark),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    // 2行の高さで、'!' と '"' だけ形を持つ最小のFIGletフォント
+    fn tiny_flf() -> String {
+        let mut flf = String::from("flf2a$ 2 1 4 -1 1\nthis is a comment\n");
+        for code in FIGLET_FIRST_CHAR..=FIGLET_LAST_CHAR {
+            match code {
+                b'!' => flf.push_str("|$@\n|$@@\n"),
+                b'"' => flf.push_str("\"\"#\n  #@#\n"),
+                _ => flf.push_str("  @\n  @@\n"),
+            }
+        }
+        flf
+    }
+
+    // ================================================================
+    // Font::builtin: 組み込みフォントの仕様
+    // ================================================================
+
+    mod describe_builtin {
+        use super::*;
+
+        mod テキストを展開した場合 {
+            use super::*;
+
+            #[test]
+            fn フォントの高さの行数になる() {
+                assert_eq!(Font::builtin().render("LOVE").len(), BUILTIN_HEIGHT);
+            }
+
+            #[test]
+            fn 文字の間に1列空ける() {
+                let lines = Font::builtin().render("HI");
+                assert_eq!(lines[0], "# # ###");
+                assert_eq!(lines[2], "###  # ");
+            }
+
+            #[test]
+            fn 小文字は大文字の形で描く() {
+                let font = Font::builtin();
+                assert_eq!(font.render("love"), font.render("LOVE"));
+            }
+
+            #[rstest]
+            #[case::絵文字("💜")]
+            #[case::未収録の記号("@")]
+            fn 未収録の文字は疑問符で描く(#[case] input: &str) {
+                let font = Font::builtin();
+                assert_eq!(font.render(input), font.render("?"));
+            }
+
+            #[test]
+            fn 空文字列は空の行を返す() {
+                let lines = Font::builtin().render("");
+                assert!(lines.iter().all(|line| line.is_empty()));
+            }
+        }
+
+        mod 全ての文字について {
+            use super::*;
+
+            #[test]
+            fn 高さと幅が揃っている() {
+                for (c, lines) in BUILTIN_GLYPHS {
+                    assert!(lines.iter().all(|line| line.len() == 3), "{}", c);
+                }
+            }
+        }
+    }
+
+    // ================================================================
+    // Font::parse_flf: FIGletフォントの読み込みの仕様
+    // ================================================================
+
+    mod describe_parse_flf {
+        use super::*;
+
+        mod 正しいフォントの場合 {
+            use super::*;
+
+            #[test]
+            fn ヘッダーの高さを使う() {
+                assert_eq!(Font::parse_flf(&tiny_flf()).unwrap().height(), 2);
+            }
+
+            #[test]
+            fn 終端記号を除きハードブランクを空白にする() {
+                let font = Font::parse_flf(&tiny_flf()).unwrap();
+                assert_eq!(font.render("!"), vec!["| ", "| "]);
+            }
+
+            #[test]
+            fn 終端記号が何であっても取り除く() {
+                let font = Font::parse_flf(&tiny_flf()).unwrap();
+                assert_eq!(font.render("\""), vec!["\"\"  ", "  #@"]);
+            }
+
+            #[test]
+            fn 文字の間は空けない() {
+                let font = Font::parse_flf(&tiny_flf()).unwrap();
+                assert_eq!(font.render("!!"), vec!["| | ", "| | "]);
+            }
+        }
+
+        mod 不正なフォントの場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::ヘッダーなし("hello\n", "missing flf2a header")]
+            #[case::空ファイル("", "missing flf2a header")]
+            #[case::数値でないヘッダー("flf2a$ x 1 4 -1 0\n", "Invalid FIGlet header")]
+            #[case::項目の足りないヘッダー("flf2a$ 2 1\n", "Invalid FIGlet header")]
+            #[case::高さゼロ("flf2a$ 0 1 4 -1 0\n", "Invalid FIGlet header")]
+            #[case::高すぎる("flf2a$ 99999999999999 1 1 0 0\n", "Invalid FIGlet header")]
+            #[case::上限を1つ超える("flf2a$ 65 1 1 0 0\n", "Invalid FIGlet header")]
+            #[case::文字が足りない("flf2a$ 2 1 4 -1 0\n  @\n  @@\n", "truncated")]
+            fn エラーを返す(#[case] input: &str, #[case] expected: &str) {
+                let err = Font::parse_flf(input).unwrap_err().to_string();
+                assert!(err.contains(expected), "{}", err);
+            }
+
+            #[rstest]
+            #[case::エスケープシーケンス("\x1b]0;PWNED\x07", "control characters")]
+            #[case::ベル("#\x07#", "control characters")]
+            #[case::幅が上限を超える(&"#".repeat(MAX_GLYPH_WIDTH + 1), "too wide")]
+            fn 文字の形が不正ならエラーを返す(
+                #[case] row: &str,
+                #[case] expected: &str,
+            ) {
+                let input = format!("flf2a$ 1 1 4 -1 0\n{}@@\n", row);
+                let err = Font::parse_flf(&input).unwrap_err().to_string();
+                assert!(err.contains(expected), "{}", err);
+            }
+
+            #[test]
+            fn 幅が上限ちょうどの文字は受け付ける() {
+                let row = "#".repeat(MAX_GLYPH_WIDTH);
+                let mut input = "flf2a$ 1 1 4 -1 0\n".to_string();
+                for _ in FIGLET_FIRST_CHAR..=FIGLET_LAST_CHAR {
+                    input.push_str(&format!("{}@@\n", row));
+                }
+                assert!(Font::parse_flf(&input).is_ok());
+            }
+        }
+    }
+
+    // ================================================================
+    // Font::read_flf: フォントファイルの読み込みの仕様
+    // ================================================================
+
+    mod describe_read_flf {
+        use super::*;
+
+        #[test]
+        fn 入力を読み込んで解釈する() {
+            let flf = tiny_flf();
+            assert_eq!(
+                Font::read_flf(flf.as_bytes()).unwrap(),
+                Font::parse_flf(&flf).unwrap()
+            );
+        }
+
+        #[test]
+        fn 上限を超える入力はエラーを返す() {
+            let input = vec![b'a'; MAX_FONT_BYTES as usize + 1];
+            assert!(Font::read_flf(input.as_slice())
+                .unwrap_err()
+                .to_string()
+                .contains("too large"));
+        }
+    }
+
+    // ================================================================
+    // Font (serde): 保存したフォントの読み込みの仕様
+    // ================================================================
+
+    #[cfg(feature = "serde")]
+    mod describe_deserialize {
+        use super::*;
+
+        #[test]
+        fn 保存したフォントを読み戻せる() {
+            let font = Font::builtin();
+            let json = serde_json::to_string(&font).unwrap();
+            assert_eq!(serde_json::from_str::<Font>(&json).unwrap(), font);
+        }
+
+        #[test]
+        fn 制御文字を含む文字の形はエラーを返す() {
+            let json = r#"{"height":1,"spacing":1,"glyphs":{"A":["\u001b]0;PWNED\u0007"]}}"#;
+            let err = serde_json::from_str::<Font>(json).unwrap_err().to_string();
+            assert!(err.contains("control characters"), "{}", err);
+        }
+
+        #[test]
+        fn 幅が上限を超える文字の形はエラーを返す() {
+            let json = serde_json::json!({
+                "height": 1,
+                "spacing": 1,
+                "glyphs": { "A": ["#".repeat(MAX_GLYPH_WIDTH + 1)] },
+            });
+            let err = serde_json::from_value::<Font>(json)
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("too wide"), "{}", err);
+        }
+
+        #[rstest]
+        #[case::高さゼロ(r#"{"height":0,"spacing":1,"glyphs":{}}"#, "height")]
+        #[case::高すぎる(r#"{"height":99999999999999,"spacing":1,"glyphs":{}}"#, "height")]
+        #[case::間が広すぎる(
+            r#"{"height":5,"spacing":99999999999999,"glyphs":{}}"#,
+            "spacing"
+        )]
+        fn 大きさが範囲外ならエラーを返す(
+            #[case] json: &str,
+            #[case] expected: &str,
+        ) {
+            let err = serde_json::from_str::<Font>(json).unwrap_err().to_string();
+            assert!(err.contains(expected), "{}", err);
+        }
+    }
+}
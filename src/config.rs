@@ -0,0 +1,325 @@
+use crate::bigtext::Font;
+use crate::effects::{MessageEffect, MESSAGE_EFFECT_NAMES};
+use crate::i18n::Locale;
+use crate::palette::{Palette, PALETTE_NAMES};
+use crate::{sanitize_input, HeartColor, HeartConfig, COLOR_NAMES, MAX_MESSAGE_LENGTH};
+use crossterm::style::Attributes;
+use std::fmt;
+
+/// ハート設定が不正な場合のエラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// メッセージが長すぎる
+    MessageTooLong { max: usize },
+    /// 知らない色名
+    UnknownColor(String),
+    /// 知らないエフェクト名
+    UnknownMessageEffect(String),
+    /// 知らないパレット名
+    UnknownPalette(String),
+    /// 一緒に指定できない設定
+    Conflict {
+        first: &'static str,
+        second: &'static str,
+    },
+}
+
+impl ConfigError {
+    /// `locale` の言語で表示するエラーの文言
+    pub fn localized(&self, locale: Locale) -> String {
+        match (self, locale) {
+            (ConfigError::MessageTooLong { max }, Locale::En) => {
+                format!("Message too long (max {} characters)", max)
+            }
+            (ConfigError::MessageTooLong { max }, Locale::Ja) => {
+                format!("メッセージが長すぎます (最大{}文字)", max)
+            }
+            (ConfigError::UnknownColor(name), Locale::En) => format!(
+                "Unknown color '{}' (available: {})",
+                name,
+                COLOR_NAMES.join(", ")
+            ),
+            (ConfigError::UnknownColor(name), Locale::Ja) => format!(
+                "知らない色名です: '{}' (使える色: {})",
+                name,
+                COLOR_NAMES.join(", ")
+            ),
+            (ConfigError::UnknownMessageEffect(name), Locale::En) => format!(
+                "Unknown message effect '{}' (available: {})",
+                name,
+                MESSAGE_EFFECT_NAMES.join(", ")
+            ),
+            (ConfigError::UnknownMessageEffect(name), Locale::Ja) => format!(
+                "知らないエフェクト名です: '{}' (使えるエフェクト: {})",
+                name,
+                MESSAGE_EFFECT_NAMES.join(", ")
+            ),
+            (ConfigError::UnknownPalette(name), Locale::En) => format!(
+                "Unknown palette '{}' (available: {})",
+                name,
+                PALETTE_NAMES.join(", ")
+            ),
+            (ConfigError::UnknownPalette(name), Locale::Ja) => format!(
+                "知らないパレット名です: '{}' (使えるパレット: {})",
+                name,
+                PALETTE_NAMES.join(", ")
+            ),
+            (ConfigError::Conflict { first, second }, Locale::En) => {
+                format!("'{}' cannot be used with '{}'", first, second)
+            }
+            (ConfigError::Conflict { first, second }, Locale::Ja) => {
+                format!("'{}' と '{}' は一緒に指定できません", first, second)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localized(Locale::En))
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// メッセージの長さを確かめ、制御文字を除いて返す
+pub(crate) fn check_message(message: &str) -> Result<String, ConfigError> {
+    if message.len() > MAX_MESSAGE_LENGTH {
+        return Err(ConfigError::MessageTooLong {
+            max: MAX_MESSAGE_LENGTH,
+        });
+    }
+    Ok(sanitize_input(message))
+}
+
+/// 1行でやりとりするため、メッセージの `\` と改行を `\\` と `\n` にする
+pub(crate) fn escape_message(message: &str) -> String {
+    message.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// `escape_message` で1行にしたメッセージを元に戻す
+pub(crate) fn unescape_message(line: &str) -> String {
+    let mut message = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                chars.next();
+                message.push('\n');
+            }
+            ('\\', Some('\\')) => {
+                chars.next();
+                message.push('\\');
+            }
+            _ => message.push(c),
+        }
+    }
+    message
+}
+
+/// 組み立て時にバリデーションする `HeartConfig` のビルダー
+#[derive(Debug, Clone, Default)]
+pub struct HeartConfigBuilder {
+    config: HeartConfig,
+}
+
+impl HeartConfig {
+    pub fn builder() -> HeartConfigBuilder {
+        HeartConfigBuilder::default()
+    }
+}
+
+impl HeartConfigBuilder {
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.config.message = Some(message.into());
+        self
+    }
+
+    pub fn petite(mut self, petite: bool) -> Self {
+        self.config.petite = petite;
+        self
+    }
+
+    pub fn color(mut self, color: HeartColor) -> Self {
+        self.config.color = color;
+        self
+    }
+
+    pub fn marquee(mut self, marquee: bool) -> Self {
+        self.config.marquee = marquee;
+        self
+    }
+
+    pub fn message_effect(mut self, effect: MessageEffect) -> Self {
+        self.config.message_effect = Some(effect);
+        self
+    }
+
+    pub fn font(mut self, font: Font) -> Self {
+        self.config.font = Some(font);
+        self
+    }
+
+    pub fn message_color(mut self, color: HeartColor) -> Self {
+        self.config.message_color = Some(color);
+        self
+    }
+
+    pub fn message_bg(mut self, color: HeartColor) -> Self {
+        self.config.message_bg = Some(color);
+        self
+    }
+
+    pub fn message_attributes(mut self, attributes: Attributes) -> Self {
+        self.config.message_attributes = attributes;
+        self
+    }
+
+    pub fn auto_contrast(mut self, auto_contrast: bool) -> Self {
+        self.config.auto_contrast = auto_contrast;
+        self
+    }
+
+    pub fn filled(mut self, filled: bool) -> Self {
+        self.config.filled = filled;
+        self
+    }
+
+    pub fn palette(mut self, palette: Palette) -> Self {
+        self.config.palette = Some(palette);
+        self
+    }
+
+    /// 設定をバリデーションして `HeartConfig` を返す (メッセージは制御文字を除く)
+    pub fn build(self) -> Result<HeartConfig, ConfigError> {
+        let mut config = self.config;
+
+        config.message = config.message.as_deref().map(check_message).transpose()?;
+        if config.message_color.is_some() && config.auto_contrast {
+            return Err(ConfigError::Conflict {
+                first: "message_color",
+                second: "auto_contrast",
+            });
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::style::Attribute;
+
+    // ================================================================
+    // HeartConfigBuilder: ビルダーの仕様
+    // ================================================================
+
+    mod describe_builder {
+        use super::*;
+
+        mod 何も指定しない場合 {
+            use super::*;
+
+            #[test]
+            fn 既定の設定を返す() {
+                assert_eq!(
+                    HeartConfig::builder().build().unwrap(),
+                    HeartConfig::default()
+                );
+            }
+
+            #[test]
+            fn 既定は白い大きなハートでメッセージなし() {
+                let config = HeartConfig::default();
+                assert_eq!(config.color, HeartColor::White);
+                assert!(!config.petite);
+                assert_eq!(config.message, None);
+            }
+        }
+
+        mod 設定を指定した場合 {
+            use super::*;
+
+            #[test]
+            fn 指定した値を持つ() {
+                let config = HeartConfig::builder()
+                    .message("Love")
+                    .petite(true)
+                    .color(HeartColor::Magenta)
+                    .message_effect(MessageEffect::Fade)
+                    .message_attributes(Attribute::Bold.into())
+                    .filled(true)
+                    .build()
+                    .unwrap();
+                assert_eq!(config.message.as_deref(), Some("Love"));
+                assert!(config.petite);
+                assert_eq!(config.color, HeartColor::Magenta);
+                assert_eq!(config.message_effect, Some(MessageEffect::Fade));
+                assert!(config.message_attributes.has(Attribute::Bold));
+                assert!(config.filled);
+            }
+
+            #[test]
+            fn メッセージの制御文字を除く() {
+                let config = HeartConfig::builder()
+                    .message("Hello\x1b[31mWorld")
+                    .build()
+                    .unwrap();
+                assert_eq!(config.message.as_deref(), Some("Hello[31mWorld"));
+            }
+        }
+
+        mod 不正な設定の場合 {
+            use super::*;
+
+            #[test]
+            fn 長すぎるメッセージはエラーを返す() {
+                let err = HeartConfig::builder()
+                    .message("a".repeat(MAX_MESSAGE_LENGTH + 1))
+                    .build()
+                    .unwrap_err();
+                assert_eq!(
+                    err,
+                    ConfigError::MessageTooLong {
+                        max: MAX_MESSAGE_LENGTH
+                    }
+                );
+                assert_eq!(err.to_string(), "Message too long (max 100 characters)");
+            }
+
+            #[test]
+            fn 文字色と自動コントラストの併用はエラーを返す() {
+                let err = HeartConfig::builder()
+                    .message_color(HeartColor::Red)
+                    .auto_contrast(true)
+                    .build()
+                    .unwrap_err();
+                assert!(matches!(err, ConfigError::Conflict { .. }));
+            }
+        }
+    }
+
+    // ================================================================
+    // ConfigError: エラー表示の仕様
+    // ================================================================
+
+    mod describe_config_error {
+        use super::*;
+
+        #[test]
+        fn 不明な色は選択肢つきで表示する() {
+            let err = ConfigError::UnknownColor("purple".to_string());
+            assert_eq!(
+                err.to_string(),
+                "Unknown color 'purple' (available: red, green, blue, yellow, magenta, cyan, white)"
+            );
+        }
+
+        #[test]
+        fn std_errorとして扱える() {
+            let err: Box<dyn std::error::Error> = Box::new(ConfigError::MessageTooLong { max: 1 });
+            assert!(err.to_string().contains("max 1"));
+        }
+    }
+}
@@ -0,0 +1,476 @@
+//! 動いている `love --daemon` を外から操作する (`love ctl`)
+//!
+//! Unix ドメインソケットに1回の接続で1行の操作を送り、`OK` か `ERR <理由>` の1行が返る。
+//!
+//! ```text
+//! message <メッセージ>   (`\` と改行は `\\` と `\n` にする。空ならメッセージを消す)
+//! color <色名>
+//! burst
+//! ```
+
+use crate::config::{escape_message, unescape_message};
+use crate::error::{Error, Reason, Result};
+use crate::{validate_message, HeartColor};
+use std::fmt;
+use std::str::FromStr;
+
+/// 動いているアニメーションへの操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// ハート内のメッセージを差し替える (`None` なら消す)
+    Message(Option<String>),
+    /// ハートの色を変える
+    Color(HeartColor),
+    /// ハートをまとめて浮かべる
+    Burst,
+}
+
+/// 1行の操作を読み、CLI と同じ規則 (`validate_message` と色名) でバリデーションする
+impl FromStr for ControlCommand {
+    type Err = Error;
+
+    fn from_str(line: &str) -> Result<Self> {
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "message" => {
+                let message = validate_message(&unescape_message(argument))?;
+                Ok(ControlCommand::Message(
+                    (!message.is_empty()).then_some(message),
+                ))
+            }
+            "color" => Ok(ControlCommand::Color(argument.parse()?)),
+            "burst" if argument.is_empty() => Ok(ControlCommand::Burst),
+            _ => Err(Error::Validation(Reason::UnknownCommand(
+                command.to_string(),
+            ))),
+        }
+    }
+}
+
+/// ソケットに送る1行 (改行は含まない)
+impl fmt::Display for ControlCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControlCommand::Message(message) => write!(
+                f,
+                "message {}",
+                escape_message(message.as_deref().unwrap_or(""))
+            ),
+            ControlCommand::Color(color) => write!(f, "color {}", color),
+            ControlCommand::Burst => f.write_str("burst"),
+        }
+    }
+}
+
+#[cfg(unix)]
+pub use self::socket::{default_socket_path, send_command, ControlSocket};
+
+#[cfg(unix)]
+mod socket {
+    use super::ControlCommand;
+    use crate::error::{Error, Reason, Result};
+    use crate::sanitize_input;
+    use rustix::fs::Mode;
+    use rustix::process::{getuid, umask};
+    use std::env;
+    use std::fs::{self, DirBuilder, Metadata, Permissions};
+    use std::io::{self, BufRead, BufReader, Read, Write};
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::{SyncSender, TrySendError};
+    use std::thread;
+    use std::time::Duration;
+
+    // 1行の操作として読む長さの上限
+    const MAX_COMMAND_BYTES: u64 = 1024;
+
+    // 1行の送受信の待ち時間 (黙ったままの相手で他の操作が詰まらないように)
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// `--control-socket` を省略した場合のソケットの場所
+    ///
+    /// `$XDG_RUNTIME_DIR` があればその中に、なければ一時ディレクトリに作るuidごとのディレクトリの中にする。
+    pub fn default_socket_path() -> Result<PathBuf> {
+        match env::var_os("XDG_RUNTIME_DIR") {
+            Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir).join("tolove-ru.sock")),
+            _ => {
+                let dir = env::temp_dir().join(format!("tolove-ru-{}", getuid().as_raw()));
+                private_dir(&dir)?;
+                Ok(dir.join("control.sock"))
+            }
+        }
+    }
+
+    /// 自分だけが入れる (0700 の) ディレクトリを用意する
+    ///
+    /// 他のユーザーが先に作ったものや、他のユーザーも入れるものは使わない。
+    pub(super) fn private_dir(dir: &Path) -> Result<()> {
+        match DirBuilder::new().mode(0o700).create(dir) {
+            Err(e) if e.kind() != io::ErrorKind::AlreadyExists => {
+                return Err(Error::Network(io::Error::new(
+                    e.kind(),
+                    Reason::CannotCreate {
+                        path: dir.to_path_buf(),
+                        source: e,
+                    },
+                )));
+            }
+            _ => {}
+        }
+        let metadata = fs::symlink_metadata(dir).map_err(Error::Network)?;
+        if !metadata.is_dir() || !is_owned(&metadata) || metadata.mode() & 0o077 != 0 {
+            return Err(permission_denied(Reason::NotPrivateDir(dir.to_path_buf())));
+        }
+        Ok(())
+    }
+
+    /// 実行しているユーザーが持ち主か
+    fn is_owned(metadata: &Metadata) -> bool {
+        metadata.uid() == getuid().as_raw()
+    }
+
+    fn permission_denied(reason: Reason) -> Error {
+        Error::Network(io::Error::new(io::ErrorKind::PermissionDenied, reason))
+    }
+
+    fn owned_by_another_user(path: &Path) -> Error {
+        permission_denied(Reason::OwnedByAnotherUser(path.to_path_buf()))
+    }
+
+    /// 操作を受け付けるソケット (Dropでソケットのファイルを消す)
+    pub struct ControlSocket {
+        path: PathBuf,
+    }
+
+    impl ControlSocket {
+        /// `path` で待ち受け、届いた操作をバリデーションしてから `commands` に渡し続ける
+        ///
+        /// 操作できるのはソケットの持ち主だけにする。
+        pub fn bind(path: &Path, commands: SyncSender<ControlCommand>) -> Result<Self> {
+            remove_stale_socket(path)?;
+            // 作ってからモードを変えるまでの間も他のユーザーが接続できないよう、最初から 0600 で作る
+            let mask = umask(Mode::from_raw_mode(0o177));
+            let listener = UnixListener::bind(path);
+            umask(mask);
+            let listener = listener.map_err(|e| {
+                Error::Network(io::Error::new(
+                    e.kind(),
+                    Reason::CannotListen {
+                        path: path.to_path_buf(),
+                        source: e,
+                    },
+                ))
+            })?;
+            let socket = ControlSocket {
+                path: path.to_path_buf(),
+            };
+            fs::set_permissions(path, Permissions::from_mode(0o600)).map_err(Error::Network)?;
+
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    // 途中で切られた操作は無視して、待ち受けは続ける
+                    let Ok(stream) = stream else { continue };
+                    let _ = handle(&stream, &commands);
+                }
+            });
+            Ok(socket)
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    impl Drop for ControlSocket {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    /// 前回落ちた時に残った自分のソケットだけを消す (動いているデーモンや通常のファイル、他のユーザーのソケットは残す)
+    fn remove_stale_socket(path: &Path) -> Result<()> {
+        let Ok(metadata) = fs::symlink_metadata(path) else {
+            return Ok(());
+        };
+        let in_use =
+            |reason: Reason| Error::Network(io::Error::new(io::ErrorKind::AddrInUse, reason));
+        if !metadata.file_type().is_socket() {
+            return Err(in_use(Reason::NotASocket(path.to_path_buf())));
+        }
+        if !is_owned(&metadata) {
+            return Err(owned_by_another_user(path));
+        }
+        if UnixStream::connect(path).is_ok() {
+            return Err(in_use(Reason::DaemonRunning(path.to_path_buf())));
+        }
+        fs::remove_file(path).map_err(Error::Network)
+    }
+
+    /// 1行の操作を読み、結果を返事する
+    fn handle(mut stream: &UnixStream, commands: &SyncSender<ControlCommand>) -> io::Result<()> {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut line = String::new();
+        BufReader::new(stream.take(MAX_COMMAND_BYTES)).read_line(&mut line)?;
+
+        let reply = line
+            .trim_end_matches(['\r', '\n'])
+            .parse()
+            .and_then(|command| match commands.try_send(command) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => Err(Error::Validation(Reason::TooManyCommands)),
+                Err(TrySendError::Disconnected(_)) => {
+                    Err(Error::Validation(Reason::AnimationStopped))
+                }
+            });
+        match reply {
+            Ok(()) => stream.write_all(b"OK\n"),
+            Err(e) => stream.write_all(format!("ERR {}\n", e).as_bytes()),
+        }
+    }
+
+    /// `path` で待ち受ける `love --daemon` に操作を送る
+    ///
+    /// 他のユーザーが作ったソケットには、なりすましたデーモンかもしれないため送らない。
+    pub fn send_command(path: &Path, command: &ControlCommand) -> Result<()> {
+        if let Ok(metadata) = fs::metadata(path) {
+            if !is_owned(&metadata) {
+                return Err(owned_by_another_user(path));
+            }
+        }
+        let mut stream = UnixStream::connect(path).map_err(|e| {
+            Error::Network(io::Error::new(
+                e.kind(),
+                Reason::NoDaemon {
+                    path: path.to_path_buf(),
+                    source: e,
+                },
+            ))
+        })?;
+        stream
+            .set_read_timeout(Some(TIMEOUT))
+            .map_err(Error::Network)?;
+        stream
+            .set_write_timeout(Some(TIMEOUT))
+            .map_err(Error::Network)?;
+        stream
+            .write_all(format!("{}\n", command).as_bytes())
+            .map_err(Error::Network)?;
+
+        let mut reply = String::new();
+        BufReader::new(stream.take(MAX_COMMAND_BYTES))
+            .read_line(&mut reply)
+            .map_err(Error::Network)?;
+        match reply.trim_end() {
+            "OK" => Ok(()),
+            // デーモンの送ってきた理由は訳さずにそのまま添える
+            reply => Err(Error::Validation(match reply.strip_prefix("ERR ") {
+                Some(reason) => Reason::CommandRejected(sanitize_input(reason)),
+                None => Reason::NoReply("love --daemon"),
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    // ================================================================
+    // ControlCommand: 操作の読み書きの仕様
+    // ================================================================
+
+    mod describe_control_command {
+        use super::*;
+
+        mod 書き出して読み込んだ場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::メッセージ(ControlCommand::Message(Some("Welcome Bob".to_string())))]
+            #[case::改行を含むメッセージ(ControlCommand::Message(Some("Welcome\nBob \\o/".to_string())))]
+            #[case::メッセージを消す(ControlCommand::Message(None))]
+            #[case::色(ControlCommand::Color(HeartColor::Red))]
+            #[case::バースト(ControlCommand::Burst)]
+            fn 元の操作に戻る(#[case] command: ControlCommand) {
+                assert_eq!(
+                    command.to_string().parse::<ControlCommand>().unwrap(),
+                    command
+                );
+            }
+        }
+
+        mod 不正な操作を読み込んだ場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::長すぎるメッセージ(&format!("message {}", "a".repeat(101)), Error::EXIT_VALIDATION, "Message too long")]
+            #[case::知らない色名("color purple", Error::EXIT_CONFIG, "Unknown color")]
+            #[case::知らない操作("reboot", Error::EXIT_VALIDATION, "Unknown command")]
+            #[case::引数つきのバースト(
+                "burst 3",
+                Error::EXIT_VALIDATION,
+                "Unknown command"
+            )]
+            fn エラーを返す(#[case] line: &str, #[case] code: i32, #[case] expected: &str) {
+                let err = line.parse::<ControlCommand>().unwrap_err();
+                assert_eq!(err.exit_code(), code);
+                assert!(err.to_string().contains(expected), "{}", err);
+            }
+
+            #[test]
+            fn メッセージの制御文字を除く() {
+                assert_eq!(
+                    "message Hi\x1b[2J".parse::<ControlCommand>().unwrap(),
+                    ControlCommand::Message(Some("Hi[2J".to_string()))
+                );
+            }
+        }
+    }
+
+    // ================================================================
+    // ControlSocket / send_command: ソケットでの操作の仕様
+    // ================================================================
+
+    #[cfg(unix)]
+    mod describe_control_socket {
+        use super::*;
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::net::UnixListener;
+        use std::path::{Path, PathBuf};
+        use std::sync::mpsc::{self, Receiver};
+
+        fn socket_path(name: &str) -> PathBuf {
+            std::env::temp_dir().join(format!("love-ctl-{}-{}.sock", name, std::process::id()))
+        }
+
+        fn start(path: &Path) -> (ControlSocket, Receiver<ControlCommand>) {
+            let (sender, commands) = mpsc::sync_channel(1);
+            (ControlSocket::bind(path, sender).unwrap(), commands)
+        }
+
+        #[test]
+        fn 届いた操作を渡す() {
+            let path = socket_path("deliver");
+            let (_socket, commands) = start(&path);
+
+            send_command(&path, &ControlCommand::Color(HeartColor::Red)).unwrap();
+            assert_eq!(
+                commands.try_recv().unwrap(),
+                ControlCommand::Color(HeartColor::Red)
+            );
+        }
+
+        #[test]
+        fn 持ち主だけが操作できる() {
+            let path = socket_path("mode");
+            let (_socket, _commands) = start(&path);
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        #[test]
+        fn 止めるとソケットを消す() {
+            let path = socket_path("drop");
+            let (socket, _commands) = start(&path);
+            drop(socket);
+            assert!(!path.exists());
+        }
+
+        #[test]
+        fn 待ちきれない操作は断る() {
+            let path = socket_path("full");
+            let (_socket, _commands) = start(&path);
+            send_command(&path, &ControlCommand::Burst).unwrap();
+
+            let err = send_command(&path, &ControlCommand::Burst).unwrap_err();
+            assert!(err.to_string().contains("Too many commands waiting"));
+        }
+
+        #[test]
+        fn 残っていた古いソケットは置き換える() {
+            let path = socket_path("stale");
+            drop(UnixListener::bind(&path).unwrap());
+            let (_socket, _commands) = start(&path);
+            send_command(&path, &ControlCommand::Burst).unwrap();
+        }
+
+        #[test]
+        fn 動いているデーモンのソケットは残す() {
+            let path = socket_path("busy");
+            let (_socket, _commands) = start(&path);
+
+            let (sender, _) = mpsc::sync_channel(1);
+            let err = ControlSocket::bind(&path, sender).err().unwrap();
+            assert!(err.to_string().contains("already listening"));
+            assert!(path.exists());
+        }
+
+        #[test]
+        fn ソケットでないファイルは消さない() {
+            let path = socket_path("file");
+            fs::write(&path, "keep me").unwrap();
+
+            let (sender, _) = mpsc::sync_channel(1);
+            let err = ControlSocket::bind(&path, sender).err().unwrap();
+            assert!(err.to_string().contains("is not a socket"));
+            assert_eq!(fs::read_to_string(&path).unwrap(), "keep me");
+            fs::remove_file(&path).unwrap();
+        }
+
+        #[test]
+        fn デーモンが動いていなければエラーを返す() {
+            let err = send_command(&socket_path("missing"), &ControlCommand::Burst).unwrap_err();
+            assert_eq!(err.exit_code(), Error::EXIT_NETWORK);
+            assert!(err.to_string().contains("No love --daemon is listening"));
+        }
+    }
+
+    #[cfg(unix)]
+    mod describe_private_dir {
+        use crate::control::socket::private_dir;
+        use std::fs::{self, Permissions};
+        use std::os::unix::fs::PermissionsExt;
+        use std::path::PathBuf;
+
+        fn dir_path(name: &str) -> PathBuf {
+            std::env::temp_dir().join(format!("love-ctl-dir-{}-{}", name, std::process::id()))
+        }
+
+        #[test]
+        fn なければ持ち主だけが入れるディレクトリを作る() {
+            let dir = dir_path("new");
+            private_dir(&dir).unwrap();
+            let mode = fs::metadata(&dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+
+            // 2回目は作ったものをそのまま使う
+            private_dir(&dir).unwrap();
+            fs::remove_dir(&dir).unwrap();
+        }
+
+        #[test]
+        fn 他のユーザーも入れるディレクトリは使わない() {
+            let dir = dir_path("shared");
+            fs::create_dir(&dir).unwrap();
+            fs::set_permissions(&dir, Permissions::from_mode(0o755)).unwrap();
+
+            let err = private_dir(&dir).unwrap_err();
+            assert!(err.to_string().contains("is not a private directory"));
+            fs::remove_dir(&dir).unwrap();
+        }
+
+        #[test]
+        fn ディレクトリでなければ使わない() {
+            let dir = dir_path("file");
+            fs::write(&dir, "").unwrap();
+
+            let err = private_dir(&dir).unwrap_err();
+            assert!(err.to_string().contains("is not a private directory"));
+            fs::remove_file(&dir).unwrap();
+        }
+    }
+}
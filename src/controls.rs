@@ -0,0 +1,241 @@
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use std::time::Duration;
+
+// 1行ぶん浮き上がる間隔 (ミリ秒)
+pub const DEFAULT_TICK_MS: u64 = 300;
+pub const MIN_TICK_MS: u64 = 50;
+pub const MAX_TICK_MS: u64 = 1000;
+const TICK_STEP_MS: u64 = 50;
+
+pub const HELP_OVERLAY: [&str; 8] = [
+    "┌-------------------------------┐",
+    "|  Space    pause / resume      |",
+    "|  + / -    speed up / down     |",
+    "|  c        cycle colors        |",
+    "|  s        toggle size         |",
+    "|  ?        toggle this help    |",
+    "|  q / Esc  quit                |",
+    "└-------------------------------┘",
+];
+
+/// アニメーション中に受け付ける操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    TogglePause,
+    SpeedUp,
+    SlowDown,
+    CycleColor,
+    ToggleSize,
+    ToggleHelp,
+    /// クリックした位置に新しいハートを出す
+    SpawnHeart {
+        column: u16,
+        row: u16,
+    },
+    Quit,
+}
+
+/// キー入力を操作に変換する
+pub fn action_for_key(key: &KeyEvent) -> Option<Action> {
+    // Windowsではキーを離したイベントも届くため、押下のみ扱う
+    if key.kind != KeyEventKind::Press {
+        return None;
+    }
+
+    // rawモードではCtrl-CがSIGINTにならないため、ここで終了として扱う
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        return match key.code {
+            KeyCode::Char('c') => Some(Action::Quit),
+            _ => None,
+        };
+    }
+
+    match key.code {
+        KeyCode::Char(' ') => Some(Action::TogglePause),
+        // '+' はシフトが必要な配列が多いため '=' も受け付ける
+        KeyCode::Char('+') | KeyCode::Char('=') => Some(Action::SpeedUp),
+        KeyCode::Char('-') => Some(Action::SlowDown),
+        KeyCode::Char('c') => Some(Action::CycleColor),
+        KeyCode::Char('s') => Some(Action::ToggleSize),
+        KeyCode::Char('?') => Some(Action::ToggleHelp),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+        _ => None,
+    }
+}
+
+/// マウス入力を操作に変換する
+pub fn action_for_mouse(mouse: &MouseEvent) -> Option<Action> {
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => Some(Action::SpawnHeart {
+            column: mouse.column,
+            row: mouse.row,
+        }),
+        _ => None,
+    }
+}
+
+/// 間隔を一段階短くする (下限あり)
+pub fn faster(tick: Duration) -> Duration {
+    let ms = (tick.as_millis() as u64).saturating_sub(TICK_STEP_MS);
+    Duration::from_millis(ms.max(MIN_TICK_MS))
+}
+
+/// 間隔を一段階長くする (上限あり)
+pub fn slower(tick: Duration) -> Duration {
+    let ms = (tick.as_millis() as u64).saturating_add(TICK_STEP_MS);
+    Duration::from_millis(ms.min(MAX_TICK_MS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEventState;
+    use rstest::rstest;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    // ================================================================
+    // action_for_key: キー割り当ての仕様
+    // ================================================================
+
+    mod describe_action_for_key {
+        use super::*;
+
+        mod 割り当て済みのキーの場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::スペース(KeyCode::Char(' '), Action::TogglePause)]
+            #[case::プラス(KeyCode::Char('+'), Action::SpeedUp)]
+            #[case::イコール(KeyCode::Char('='), Action::SpeedUp)]
+            #[case::マイナス(KeyCode::Char('-'), Action::SlowDown)]
+            #[case::c(KeyCode::Char('c'), Action::CycleColor)]
+            #[case::s(KeyCode::Char('s'), Action::ToggleSize)]
+            #[case::はてな(KeyCode::Char('?'), Action::ToggleHelp)]
+            #[case::q(KeyCode::Char('q'), Action::Quit)]
+            #[case::esc(KeyCode::Esc, Action::Quit)]
+            fn 対応する操作を返す(#[case] code: KeyCode, #[case] expected: Action) {
+                assert_eq!(action_for_key(&press(code)), Some(expected));
+            }
+        }
+
+        mod ctrl_cの場合 {
+            use super::*;
+
+            #[test]
+            fn 色の変更ではなく終了を返す() {
+                let key = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+                assert_eq!(action_for_key(&key), Some(Action::Quit));
+            }
+        }
+
+        mod 割り当てのないキーの場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::文字(KeyCode::Char('x'))]
+            #[case::エンター(KeyCode::Enter)]
+            #[case::矢印(KeyCode::Up)]
+            fn noneを返す(#[case] code: KeyCode) {
+                assert_eq!(action_for_key(&press(code)), None);
+            }
+        }
+
+        mod キーを離したイベントの場合 {
+            use super::*;
+
+            #[test]
+            fn noneを返す() {
+                let key = KeyEvent::new_with_kind_and_state(
+                    KeyCode::Char('q'),
+                    KeyModifiers::NONE,
+                    KeyEventKind::Release,
+                    KeyEventState::NONE,
+                );
+                assert_eq!(action_for_key(&key), None);
+            }
+        }
+    }
+
+    // ================================================================
+    // action_for_mouse: マウス操作の仕様
+    // ================================================================
+
+    mod describe_action_for_mouse {
+        use super::*;
+
+        fn mouse(kind: MouseEventKind) -> MouseEvent {
+            MouseEvent {
+                kind,
+                column: 12,
+                row: 34,
+                modifiers: KeyModifiers::NONE,
+            }
+        }
+
+        mod 左クリックの場合 {
+            use super::*;
+
+            #[test]
+            fn クリック位置にハートを出す() {
+                let event = mouse(MouseEventKind::Down(MouseButton::Left));
+                assert_eq!(
+                    action_for_mouse(&event),
+                    Some(Action::SpawnHeart {
+                        column: 12,
+                        row: 34
+                    })
+                );
+            }
+        }
+
+        mod その他のマウス操作の場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::右クリック(MouseEventKind::Down(MouseButton::Right))]
+            #[case::ボタンを離す(MouseEventKind::Up(MouseButton::Left))]
+            #[case::移動(MouseEventKind::Moved)]
+            #[case::スクロール(MouseEventKind::ScrollUp)]
+            fn noneを返す(#[case] kind: MouseEventKind) {
+                assert_eq!(action_for_mouse(&mouse(kind)), None);
+            }
+        }
+    }
+
+    // ================================================================
+    // faster / slower: 速度変更の仕様
+    // ================================================================
+
+    mod describe_速度変更 {
+        use super::*;
+
+        #[test]
+        fn fasterは間隔を短くする() {
+            let tick = Duration::from_millis(DEFAULT_TICK_MS);
+            assert!(faster(tick) < tick);
+        }
+
+        #[test]
+        fn slowerは間隔を長くする() {
+            let tick = Duration::from_millis(DEFAULT_TICK_MS);
+            assert!(slower(tick) > tick);
+        }
+
+        #[test]
+        fn fasterは下限を下回らない() {
+            let tick = Duration::from_millis(MIN_TICK_MS);
+            assert_eq!(faster(tick), tick);
+        }
+
+        #[test]
+        fn slowerは上限を上回らない() {
+            let tick = Duration::from_millis(MAX_TICK_MS);
+            assert_eq!(slower(tick), tick);
+        }
+    }
+}
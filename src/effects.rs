@@ -0,0 +1,373 @@
+use crate::config::ConfigError;
+use crossterm::style::Color;
+use std::str::FromStr;
+use std::time::Duration;
+
+// タイプライターとスパークルで1文字が現れる間隔
+const TYPEWRITER_CHAR_MS: u64 = 80;
+const SPARKLE_CHAR_MS: u64 = 60;
+
+// フェードインにかける時間と明るさの段階数
+const FADE_MS: u64 = 1200;
+const FADE_STEPS: u64 = 8;
+
+// スパークルの表示順を毎フレーム同じにするためのシード
+const SPARKLE_SEED: u64 = 0x10be;
+
+/// `--message-effect` で指定できる名前の一覧
+pub const MESSAGE_EFFECT_NAMES: [&str; 3] = ["typewriter", "fade", "sparkle"];
+
+/// メッセージを時間をかけて表示するエフェクト
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum MessageEffect {
+    /// カーソルつきで1文字ずつタイプする
+    Typewriter,
+    /// 暗い色から少しずつ明るくする
+    Fade,
+    /// ランダムな順番で1文字ずつ現す
+    Sparkle,
+}
+
+impl FromStr for MessageEffect {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "typewriter" => Ok(MessageEffect::Typewriter),
+            "fade" => Ok(MessageEffect::Fade),
+            "sparkle" => Ok(MessageEffect::Sparkle),
+            _ => Err(ConfigError::UnknownMessageEffect(s.to_string())),
+        }
+    }
+}
+
+/// メッセージの1文字をその時点でどう見せるか
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reveal {
+    /// まだ見せない
+    Hidden,
+    /// タイプライターのカーソルを置く
+    Cursor,
+    /// 現れる瞬間のきらめきを置く
+    Sparkle,
+    /// 0.0 (消灯) から 1.0 (本来の明るさ) の明るさで見せる
+    Dimmed(f32),
+    /// そのまま見せる
+    Shown,
+}
+
+impl MessageEffect {
+    /// 長さ `len` のメッセージが出揃うまでの時間
+    pub fn duration(self, len: usize) -> Duration {
+        match self {
+            MessageEffect::Typewriter => Duration::from_millis(TYPEWRITER_CHAR_MS * len as u64),
+            MessageEffect::Fade => Duration::from_millis(FADE_MS),
+            MessageEffect::Sparkle => Duration::from_millis(SPARKLE_CHAR_MS * len as u64),
+        }
+    }
+
+    /// 長さ `len` のメッセージの `index` 文字目を、表示開始から `elapsed` 経った時点でどう見せるか
+    pub fn reveal(self, len: usize, index: usize, elapsed: Duration) -> Reveal {
+        let elapsed = elapsed.as_millis() as u64;
+        match self {
+            MessageEffect::Typewriter => {
+                let typed = (elapsed / TYPEWRITER_CHAR_MS) as usize;
+                if index < typed {
+                    Reveal::Shown
+                } else if index == typed {
+                    Reveal::Cursor
+                } else {
+                    Reveal::Hidden
+                }
+            }
+            MessageEffect::Fade => {
+                let step = (elapsed / (FADE_MS / FADE_STEPS)).min(FADE_STEPS);
+                if step == FADE_STEPS {
+                    Reveal::Shown
+                } else {
+                    Reveal::Dimmed(step as f32 / FADE_STEPS as f32)
+                }
+            }
+            MessageEffect::Sparkle => {
+                let revealed = (elapsed / SPARKLE_CHAR_MS) as usize;
+                let rank = sparkle_order(len)[index];
+                if rank < revealed {
+                    Reveal::Shown
+                } else if rank == revealed {
+                    Reveal::Sparkle
+                } else {
+                    Reveal::Hidden
+                }
+            }
+        }
+    }
+}
+
+/// 各文字が何番目に現れるか (同じ長さなら毎回同じ並び)
+fn sparkle_order(len: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..len).collect();
+    fastrand::Rng::with_seed(SPARKLE_SEED ^ len as u64).shuffle(&mut order);
+
+    let mut rank = vec![0; len];
+    for (i, &index) in order.iter().enumerate() {
+        rank[index] = i;
+    }
+    rank
+}
+
+/// 色をRGBで返す (ターミナルの明るい方の16色に合わせる)
+pub fn color_rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Rgb { r, g, b } => (r, g, b),
+        Color::Red => (255, 0, 0),
+        Color::Green => (0, 255, 0),
+        Color::Blue => (92, 92, 255),
+        Color::Yellow => (255, 255, 0),
+        Color::Magenta => (255, 0, 255),
+        Color::Cyan => (0, 255, 255),
+        Color::Black => (0, 0, 0),
+        _ => (255, 255, 255),
+    }
+}
+
+/// ハートの色に対して目立つ色を返す (補色、補色のない白には赤)
+pub fn complement(color: Color) -> Color {
+    match color {
+        Color::Red => Color::Cyan,
+        Color::Green => Color::Magenta,
+        Color::Blue => Color::Yellow,
+        Color::Yellow => Color::Blue,
+        Color::Magenta => Color::Green,
+        Color::Cyan => Color::Red,
+        _ => Color::Red,
+    }
+}
+
+/// 背景色の上で読みやすい文字色 (黒か白) を返す
+pub fn contrast(background: Color) -> Color {
+    let (r, g, b) = color_rgb(background);
+    let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    if luminance > 128.0 {
+        Color::Black
+    } else {
+        Color::White
+    }
+}
+
+/// 色の明るさを `brightness` 倍にしたtruecolorを返す
+pub fn dim(color: Color, brightness: f32) -> Color {
+    let (r, g, b) = color_rgb(color);
+    let scale = |c: u8| (c as f32 * brightness.clamp(0.0, 1.0)).round() as u8;
+    Color::Rgb {
+        r: scale(r),
+        g: scale(g),
+        b: scale(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    // ================================================================
+    // MessageEffect::from_str: エフェクト名のパースの仕様
+    // ================================================================
+
+    mod describe_from_str {
+        use super::*;
+
+        #[rstest]
+        #[case::タイプライター("typewriter", MessageEffect::Typewriter)]
+        #[case::フェード("fade", MessageEffect::Fade)]
+        #[case::スパークル("sparkle", MessageEffect::Sparkle)]
+        fn 有効な名前は対応するエフェクトを返す(
+            #[case] input: &str,
+            #[case] expected: MessageEffect,
+        ) {
+            assert_eq!(input.parse::<MessageEffect>().unwrap(), expected);
+        }
+
+        #[rstest]
+        #[case::不明な名前("blink")]
+        #[case::空文字列("")]
+        #[case::大文字("FADE")]
+        fn 無効な名前は選択肢つきのエラーを返す(#[case] input: &str) {
+            let err = input.parse::<MessageEffect>().unwrap_err();
+            assert_eq!(err, ConfigError::UnknownMessageEffect(input.to_string()));
+            assert!(err.to_string().contains("typewriter, fade, sparkle"));
+        }
+    }
+
+    // ================================================================
+    // MessageEffect::reveal: 文字の見せ方の仕様
+    // ================================================================
+
+    mod describe_reveal {
+        use super::*;
+
+        mod タイプライターの場合 {
+            use super::*;
+
+            #[test]
+            fn 開始直後は先頭にカーソルを置き残りは隠す() {
+                let effect = MessageEffect::Typewriter;
+                assert_eq!(effect.reveal(5, 0, ms(0)), Reveal::Cursor);
+                assert_eq!(effect.reveal(5, 1, ms(0)), Reveal::Hidden);
+            }
+
+            #[test]
+            fn 打ち終えた文字を見せてカーソルを進める() {
+                let effect = MessageEffect::Typewriter;
+                let elapsed = ms(TYPEWRITER_CHAR_MS * 2);
+                assert_eq!(effect.reveal(5, 1, elapsed), Reveal::Shown);
+                assert_eq!(effect.reveal(5, 2, elapsed), Reveal::Cursor);
+                assert_eq!(effect.reveal(5, 3, elapsed), Reveal::Hidden);
+            }
+
+            #[test]
+            fn 所要時間が過ぎたら全文字を見せる() {
+                let effect = MessageEffect::Typewriter;
+                let elapsed = effect.duration(5);
+                assert!((0..5).all(|i| effect.reveal(5, i, elapsed) == Reveal::Shown));
+            }
+        }
+
+        mod フェードの場合 {
+            use super::*;
+
+            #[test]
+            fn 開始直後は消灯している() {
+                assert_eq!(MessageEffect::Fade.reveal(5, 0, ms(0)), Reveal::Dimmed(0.0));
+            }
+
+            #[test]
+            fn 時間とともに段階的に明るくなる() {
+                let effect = MessageEffect::Fade;
+                let brightness = |elapsed| match effect.reveal(5, 0, ms(elapsed)) {
+                    Reveal::Dimmed(b) => b,
+                    Reveal::Shown => 1.0,
+                    other => panic!("unexpected {:?}", other),
+                };
+                let steps: Vec<f32> = (0..=FADE_MS).step_by(100).map(brightness).collect();
+                assert!(steps.windows(2).all(|w| w[0] <= w[1]));
+                assert!(steps.iter().any(|&b| b > 0.0 && b < 1.0));
+            }
+
+            #[test]
+            fn 所要時間が過ぎたら本来の明るさで見せる() {
+                let effect = MessageEffect::Fade;
+                assert_eq!(effect.reveal(5, 0, effect.duration(5)), Reveal::Shown);
+            }
+        }
+
+        mod スパークルの場合 {
+            use super::*;
+
+            #[test]
+            fn 一度に1文字ずつ現す() {
+                let effect = MessageEffect::Sparkle;
+                for step in 0..10 {
+                    let elapsed = ms(SPARKLE_CHAR_MS * step);
+                    let shown = (0..10)
+                        .filter(|&i| effect.reveal(10, i, elapsed) == Reveal::Shown)
+                        .count();
+                    assert_eq!(shown, step as usize);
+                }
+            }
+
+            #[test]
+            fn 先頭から順番には現さない() {
+                let effect = MessageEffect::Sparkle;
+                let order: Vec<usize> = (0..20)
+                    .map(|step| {
+                        (0..20)
+                            .find(|&i| {
+                                effect.reveal(20, i, ms(SPARKLE_CHAR_MS * step)) == Reveal::Sparkle
+                            })
+                            .unwrap()
+                    })
+                    .collect();
+                assert_ne!(order, (0..20).collect::<Vec<_>>());
+            }
+
+            #[test]
+            fn 同じ時点なら毎回同じ見せ方になる() {
+                let effect = MessageEffect::Sparkle;
+                let elapsed = ms(SPARKLE_CHAR_MS * 4);
+                let first: Vec<_> = (0..10).map(|i| effect.reveal(10, i, elapsed)).collect();
+                let second: Vec<_> = (0..10).map(|i| effect.reveal(10, i, elapsed)).collect();
+                assert_eq!(first, second);
+            }
+        }
+    }
+
+    // ================================================================
+    // contrast: 読みやすい文字色の仕様
+    // ================================================================
+
+    mod describe_contrast {
+        use super::*;
+
+        #[rstest]
+        #[case::白(Color::White)]
+        #[case::黄(Color::Yellow)]
+        #[case::シアン(Color::Cyan)]
+        #[case::緑(Color::Green)]
+        fn 明るい背景には黒を返す(#[case] background: Color) {
+            assert_eq!(contrast(background), Color::Black);
+        }
+
+        #[rstest]
+        #[case::赤(Color::Red)]
+        #[case::青(Color::Blue)]
+        #[case::黒(Color::Black)]
+        #[case::マゼンタ(Color::Magenta)]
+        fn 暗い背景には白を返す(#[case] background: Color) {
+            assert_eq!(contrast(background), Color::White);
+        }
+    }
+
+    // ================================================================
+    // dim: 明るさ調整の仕様
+    // ================================================================
+
+    mod describe_dim {
+        use super::*;
+
+        #[test]
+        fn 明るさ0は黒になる() {
+            assert_eq!(dim(Color::Red, 0.0), Color::Rgb { r: 0, g: 0, b: 0 });
+        }
+
+        #[test]
+        fn 明るさ1は元の色のrgbになる() {
+            assert_eq!(
+                dim(Color::Cyan, 1.0),
+                Color::Rgb {
+                    r: 0,
+                    g: 255,
+                    b: 255
+                }
+            );
+        }
+
+        #[test]
+        fn 中間の明るさは各成分を比例して暗くする() {
+            assert_eq!(
+                dim(Color::White, 0.5),
+                Color::Rgb {
+                    r: 128,
+                    g: 128,
+                    b: 128
+                }
+            );
+        }
+    }
+}
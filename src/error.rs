@@ -0,0 +1,669 @@
+use crate::config::ConfigError;
+use crate::i18n::Locale;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// `tolove_ru` の処理で起きるエラー
+///
+/// 種類ごとにプロセスの終了コードを分けている (`exit_code`)。
+#[derive(Debug)]
+pub enum Error {
+    /// メッセージが不正 (長すぎる・空・件数が多すぎるなど)
+    Validation(Reason),
+    /// 設定やその読み込みが不正 (組み合わせ・フォントやメッセージのファイルなど)
+    Config(Reason),
+    /// ターミナルの入出力に失敗した
+    Terminal(io::Error),
+    /// フレームや設定の書き出しに失敗した
+    Export(io::Error),
+    /// サーバーの待ち受けや通信に失敗した
+    Network(io::Error),
+}
+
+/// `tolove_ru::Error` を使う `Result`
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// エラーの理由
+///
+/// 表示する文言は `localized` で言語ごとに組み立てる (`Display` は英語)。
+/// 入出力のエラーの中身にするときは `io::Error::new(kind, reason)` で包む。
+#[derive(Debug)]
+pub enum Reason {
+    /// ハートの設定が不正
+    Config(ConfigError),
+    /// ファイルを開けない
+    OpenFailed { path: PathBuf, source: io::Error },
+    /// ファイルを読めない
+    ReadFailed { path: PathBuf, source: io::Error },
+    /// ファイルやディレクトリを作れない
+    CannotCreate { path: PathBuf, source: io::Error },
+    /// ファイルの中身が不正 (どのファイルかを添える)
+    InFile { path: PathBuf, error: Box<Error> },
+    /// フォントを読めない
+    FontUnreadable(io::Error),
+    /// フォントのファイルが大きすぎる
+    FontTooLarge { max: u64 },
+    /// FIGletのヘッダーがない
+    NotFiglet,
+    /// FIGletのヘッダーが不正
+    InvalidFigletHeader,
+    /// FIGletフォントが途中で切れている
+    FigletTruncated,
+    /// フォントの高さが範囲外
+    FontHeight { height: usize, max: usize },
+    /// フォントの字間が範囲外
+    FontSpacing { spacing: usize, max: usize },
+    /// フォントの文字の形に制御文字がある
+    FontControlCharacters,
+    /// フォントの文字の幅が広すぎる
+    GlyphTooWide { max: usize },
+    /// メッセージの一覧を読めない
+    MessagesUnreadable(io::Error),
+    /// メッセージの一覧が大きすぎる
+    MessagesTooLarge { max: u64 },
+    /// メッセージの一覧がUTF-8でない
+    MessagesNotUtf8,
+    /// メッセージの一覧の行が不正 (行番号は1から)
+    Line { line: usize, error: ConfigError },
+    /// メッセージの一覧の件数が多すぎる
+    TooManyMessages { max: usize },
+    /// メッセージの一覧が空
+    NoMessages,
+    /// 知らないクエリの項目
+    UnknownQueryParameter(String),
+    /// クエリの真偽値が不正
+    InvalidFlag { key: String, value: String },
+    /// クエリの広さが範囲外
+    InvalidSize {
+        key: String,
+        value: String,
+        max: u16,
+    },
+    /// 送り先がターミナルでない
+    NotATerminal(PathBuf),
+    /// 送り先のターミナル (またはユーザー) がメッセージを受け付けていない
+    MessagesDisabled(String),
+    /// 送り先のターミナルに書き込めない
+    CannotWrite { path: PathBuf, source: io::Error },
+    /// 知らないユーザー
+    UnknownUser(String),
+    /// ユーザーがログインしていない
+    NotLoggedIn(String),
+    /// 合言葉のファイルを読めない
+    SecretUnreadable { path: PathBuf, source: io::Error },
+    /// 合言葉のファイルが空
+    SecretEmpty(PathBuf),
+    /// 送り先のホストが見つからない
+    HostNotFound,
+    /// 相手が `love listen` でない
+    NotALetterListener,
+    /// 相手に手紙を断られた (相手の送ってきた理由をそのまま添える)
+    LetterRejected(String),
+    /// 相手から返事がない
+    NoReply(&'static str),
+    /// 手紙の知らない項目
+    UnknownField(String),
+    /// 手紙が途中で切れている
+    IncompleteLetter,
+    /// 合言葉の署名がない
+    AuthenticationRequired,
+    /// 合言葉の署名が違う
+    AuthenticationFailed,
+    /// 待っている手紙が多すぎる
+    TooManyLetters,
+    /// デーモンへの知らない操作
+    UnknownCommand(String),
+    /// デーモンで待っている操作が多すぎる
+    TooManyCommands,
+    /// デーモンのアニメーションが止まっている
+    AnimationStopped,
+    /// デーモンに操作を断られた (デーモンの送ってきた理由をそのまま添える)
+    CommandRejected(String),
+    /// デーモンが待ち受けていない
+    NoDaemon { path: PathBuf, source: io::Error },
+    /// 別のデーモンが待ち受けている
+    DaemonRunning(PathBuf),
+    /// ソケットを置く場所にソケットでないファイルがある
+    NotASocket(PathBuf),
+    /// ソケットを置くディレクトリが自分だけのものでない
+    NotPrivateDir(PathBuf),
+    /// 別のユーザーのソケット
+    OwnedByAnotherUser(PathBuf),
+    /// ソケットで待ち受けできない
+    CannotListen { path: PathBuf, source: io::Error },
+    /// Unixでしか使えない機能
+    UnixOnly(&'static str),
+    /// serde 機能なしでビルドしたため使えない機能
+    NeedsSerde(&'static str),
+}
+
+impl Error {
+    /// メッセージが不正な場合の終了コード (sysexits の EX_DATAERR)
+    pub const EXIT_VALIDATION: i32 = 65;
+    /// ターミナルの入出力に失敗した場合の終了コード (EX_IOERR)
+    pub const EXIT_TERMINAL: i32 = 74;
+    /// 書き出しに失敗した場合の終了コード (EX_CANTCREAT)
+    pub const EXIT_EXPORT: i32 = 73;
+    /// 待ち受けや通信に失敗した場合の終了コード (EX_UNAVAILABLE)
+    pub const EXIT_NETWORK: i32 = 69;
+    /// 設定が不正な場合の終了コード (EX_CONFIG)
+    pub const EXIT_CONFIG: i32 = 78;
+
+    /// エラーの種類に応じたプロセスの終了コード
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Validation(_) => Error::EXIT_VALIDATION,
+            Error::Config(_) => Error::EXIT_CONFIG,
+            Error::Terminal(_) => Error::EXIT_TERMINAL,
+            Error::Export(_) => Error::EXIT_EXPORT,
+            Error::Network(_) => Error::EXIT_NETWORK,
+        }
+    }
+
+    /// `locale` の言語で表示するエラーの文言
+    ///
+    /// OSから返ってきた入出力のエラーの文言は訳さずにそのまま添える。
+    pub fn localized(&self, locale: Locale) -> String {
+        match (self, locale) {
+            (Error::Validation(reason) | Error::Config(reason), _) => reason.localized(locale),
+            (Error::Terminal(e), Locale::En) => {
+                format!("Terminal I/O failed: {}", io_localized(e, locale))
+            }
+            (Error::Terminal(e), Locale::Ja) => {
+                format!(
+                    "ターミナルの入出力に失敗しました: {}",
+                    io_localized(e, locale)
+                )
+            }
+            (Error::Export(e), Locale::En) => format!("Export failed: {}", io_localized(e, locale)),
+            (Error::Export(e), Locale::Ja) => {
+                format!("書き出しに失敗しました: {}", io_localized(e, locale))
+            }
+            (Error::Network(e), Locale::En) => {
+                format!("Network I/O failed: {}", io_localized(e, locale))
+            }
+            (Error::Network(e), Locale::Ja) => {
+                format!("通信に失敗しました: {}", io_localized(e, locale))
+            }
+        }
+    }
+}
+
+impl Reason {
+    /// `locale` の言語で表示するエラーの文言
+    ///
+    /// ファイル名や相手の送ってきた理由など、差し込む値は訳さない。
+    pub fn localized(&self, locale: Locale) -> String {
+        use Locale::{En, Ja};
+
+        match (self, locale) {
+            (Reason::Config(e), _) => e.localized(locale),
+            (Reason::OpenFailed { path, source }, En) => format!(
+                "Failed to open {}: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::OpenFailed { path, source }, Ja) => format!(
+                "{} を開けませんでした: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::ReadFailed { path, source }, En) => format!(
+                "Failed to read {}: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::ReadFailed { path, source }, Ja) => format!(
+                "{} を読み込めませんでした: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::CannotCreate { path, source }, En) => format!(
+                "Cannot create {}: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::CannotCreate { path, source }, Ja) => format!(
+                "{} を作れません: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::InFile { path, error }, _) => {
+                format!("{}: {}", path.display(), error.localized(locale))
+            }
+            (Reason::FontUnreadable(e), En) => {
+                format!("Failed to read font: {}", io_localized(e, locale))
+            }
+            (Reason::FontUnreadable(e), Ja) => {
+                format!(
+                    "フォントを読み込めませんでした: {}",
+                    io_localized(e, locale)
+                )
+            }
+            (Reason::FontTooLarge { max }, En) => {
+                format!("Font file too large (max {} bytes)", max)
+            }
+            (Reason::FontTooLarge { max }, Ja) => {
+                format!("フォントファイルが大きすぎます (最大{}バイト)", max)
+            }
+            (Reason::NotFiglet, En) => "Not a FIGlet font (missing flf2a header)".to_string(),
+            (Reason::NotFiglet, Ja) => {
+                "FIGletフォントではありません (flf2a のヘッダーがありません)".to_string()
+            }
+            (Reason::InvalidFigletHeader, En) => "Invalid FIGlet header".to_string(),
+            (Reason::InvalidFigletHeader, Ja) => "FIGletフォントのヘッダーが不正です".to_string(),
+            (Reason::FigletTruncated, En) => "FIGlet font is truncated".to_string(),
+            (Reason::FigletTruncated, Ja) => "FIGletフォントが途中で切れています".to_string(),
+            (Reason::FontHeight { height, max }, En) => {
+                format!("Invalid font height {} (must be 1 to {})", height, max)
+            }
+            (Reason::FontHeight { height, max }, Ja) => {
+                format!("フォントの高さ {} は使えません (1〜{})", height, max)
+            }
+            (Reason::FontSpacing { spacing, max }, En) => {
+                format!("Invalid font spacing {} (max {})", spacing, max)
+            }
+            (Reason::FontSpacing { spacing, max }, Ja) => {
+                format!("フォントの字間 {} は使えません (最大{})", spacing, max)
+            }
+            (Reason::FontControlCharacters, En) => "Font contains control characters".to_string(),
+            (Reason::FontControlCharacters, Ja) => "フォントに制御文字が含まれています".to_string(),
+            (Reason::GlyphTooWide { max }, En) => {
+                format!("Font glyph too wide (max {} columns)", max)
+            }
+            (Reason::GlyphTooWide { max }, Ja) => {
+                format!("フォントの文字の幅が広すぎます (最大{}列)", max)
+            }
+            (Reason::MessagesUnreadable(e), En) => {
+                format!("Failed to read messages: {}", io_localized(e, locale))
+            }
+            (Reason::MessagesUnreadable(e), Ja) => {
+                format!(
+                    "メッセージを読み込めませんでした: {}",
+                    io_localized(e, locale)
+                )
+            }
+            (Reason::MessagesTooLarge { max }, En) => {
+                format!("Messages input too large (max {} bytes)", max)
+            }
+            (Reason::MessagesTooLarge { max }, Ja) => {
+                format!("メッセージの入力が大きすぎます (最大{}バイト)", max)
+            }
+            (Reason::MessagesNotUtf8, En) => "Messages must be valid UTF-8".to_string(),
+            (Reason::MessagesNotUtf8, Ja) => "メッセージはUTF-8で書いてください".to_string(),
+            (Reason::Line { line, error }, En) => {
+                format!("Line {}: {}", line, error.localized(locale))
+            }
+            (Reason::Line { line, error }, Ja) => {
+                format!("{}行目: {}", line, error.localized(locale))
+            }
+            (Reason::TooManyMessages { max }, En) => {
+                format!("Too many messages (max {} messages)", max)
+            }
+            (Reason::TooManyMessages { max }, Ja) => {
+                format!("メッセージが多すぎます (最大{}件)", max)
+            }
+            (Reason::NoMessages, En) => "No messages found".to_string(),
+            (Reason::NoMessages, Ja) => "メッセージが見つかりません".to_string(),
+            (Reason::UnknownQueryParameter(key), En) => {
+                format!("Unknown query parameter '{}'", key)
+            }
+            (Reason::UnknownQueryParameter(key), Ja) => {
+                format!("知らないクエリの項目です: '{}'", key)
+            }
+            (Reason::InvalidFlag { key, value }, En) => {
+                format!(
+                    "Invalid value '{}' for '{}' (use true or false)",
+                    value, key
+                )
+            }
+            (Reason::InvalidFlag { key, value }, Ja) => {
+                format!("'{}' の値 '{}' は使えません (true か false)", key, value)
+            }
+            (Reason::InvalidSize { key, value, max }, En) => {
+                format!("Invalid value '{}' for '{}' (1 to {})", value, key, max)
+            }
+            (Reason::InvalidSize { key, value, max }, Ja) => {
+                format!("'{}' の値 '{}' は使えません (1〜{})", key, value, max)
+            }
+            (Reason::NotATerminal(path), En) => format!("{} is not a terminal", path.display()),
+            (Reason::NotATerminal(path), Ja) => {
+                format!("{} はターミナルではありません", path.display())
+            }
+            (Reason::MessagesDisabled(whom), En) => {
+                format!("{} has messages disabled (mesg n)", whom)
+            }
+            (Reason::MessagesDisabled(whom), Ja) => {
+                format!("{} はメッセージを受け付けていません (mesg n)", whom)
+            }
+            (Reason::CannotWrite { path, source }, En) => format!(
+                "Cannot write to {}: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::CannotWrite { path, source }, Ja) => format!(
+                "{} に書き込めません: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::UnknownUser(user), En) => format!("Unknown user '{}'", user),
+            (Reason::UnknownUser(user), Ja) => format!("知らないユーザーです: '{}'", user),
+            (Reason::NotLoggedIn(user), En) => format!("{} is not logged in", user),
+            (Reason::NotLoggedIn(user), Ja) => format!("{} はログインしていません", user),
+            (Reason::SecretUnreadable { path, source }, En) => format!(
+                "Failed to read secret file '{}': {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::SecretUnreadable { path, source }, Ja) => format!(
+                "合言葉のファイル '{}' を読み込めませんでした: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::SecretEmpty(path), En) => {
+                format!("Secret file '{}' is empty", path.display())
+            }
+            (Reason::SecretEmpty(path), Ja) => {
+                format!("合言葉のファイル '{}' が空です", path.display())
+            }
+            (Reason::HostNotFound, En) => "Host not found".to_string(),
+            (Reason::HostNotFound, Ja) => "ホストが見つかりません".to_string(),
+            (Reason::NotALetterListener, En) => "Not a love letter listener".to_string(),
+            (Reason::NotALetterListener, Ja) => "love listen ではない相手です".to_string(),
+            (Reason::LetterRejected(reason), En) => format!("Letter rejected: {}", reason),
+            (Reason::LetterRejected(reason), Ja) => {
+                format!("ハートを受け取ってもらえませんでした: {}", reason)
+            }
+            (Reason::NoReply(whom), En) => format!("No reply from {}", whom),
+            (Reason::NoReply(whom), Ja) => format!("{} から返事がありません", whom),
+            (Reason::UnknownField(key), En) => format!("Unknown field '{}'", key),
+            (Reason::UnknownField(key), Ja) => format!("知らない項目です: '{}'", key),
+            (Reason::IncompleteLetter, En) => "Incomplete letter".to_string(),
+            (Reason::IncompleteLetter, Ja) => "ハートが途中で途切れています".to_string(),
+            (Reason::AuthenticationRequired, En) => "Authentication required".to_string(),
+            (Reason::AuthenticationRequired, Ja) => "合言葉が必要です".to_string(),
+            (Reason::AuthenticationFailed, En) => "Authentication failed".to_string(),
+            (Reason::AuthenticationFailed, Ja) => "合言葉が違います".to_string(),
+            (Reason::TooManyLetters, En) => {
+                "Too many letters waiting, please try again later".to_string()
+            }
+            (Reason::TooManyLetters, Ja) => {
+                "待っているハートが多すぎます。しばらくしてから送ってください".to_string()
+            }
+            (Reason::UnknownCommand(command), En) => format!(
+                "Unknown command '{}' (available: message, color, burst)",
+                command
+            ),
+            (Reason::UnknownCommand(command), Ja) => format!(
+                "知らない操作です: '{}' (使える操作: message, color, burst)",
+                command
+            ),
+            (Reason::TooManyCommands, En) => {
+                "Too many commands waiting, please try again".to_string()
+            }
+            (Reason::TooManyCommands, Ja) => {
+                "待っている操作が多すぎます。もう一度試してください".to_string()
+            }
+            (Reason::AnimationStopped, En) => "The animation has stopped".to_string(),
+            (Reason::AnimationStopped, Ja) => "アニメーションは止まっています".to_string(),
+            (Reason::CommandRejected(reason), En) => format!("Command rejected: {}", reason),
+            (Reason::CommandRejected(reason), Ja) => {
+                format!("操作を受け付けてもらえませんでした: {}", reason)
+            }
+            (Reason::NoDaemon { path, source }, En) => format!(
+                "No love --daemon is listening on {}: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::NoDaemon { path, source }, Ja) => format!(
+                "{} で待ち受けている love --daemon がありません: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::DaemonRunning(path), En) => format!(
+                "Another love --daemon is already listening on {}",
+                path.display()
+            ),
+            (Reason::DaemonRunning(path), Ja) => {
+                format!(
+                    "{} では別の love --daemon が待ち受けています",
+                    path.display()
+                )
+            }
+            (Reason::NotASocket(path), En) => {
+                format!("{} already exists and is not a socket", path.display())
+            }
+            (Reason::NotASocket(path), Ja) => {
+                format!(
+                    "{} はソケットではないファイルとしてすでにあります",
+                    path.display()
+                )
+            }
+            (Reason::NotPrivateDir(path), En) => {
+                format!("{} is not a private directory owned by you", path.display())
+            }
+            (Reason::NotPrivateDir(path), Ja) => {
+                format!(
+                    "{} はあなただけが使えるディレクトリではありません",
+                    path.display()
+                )
+            }
+            (Reason::OwnedByAnotherUser(path), En) => {
+                format!("{} is owned by another user", path.display())
+            }
+            (Reason::OwnedByAnotherUser(path), Ja) => {
+                format!("{} は別のユーザーのものです", path.display())
+            }
+            (Reason::CannotListen { path, source }, En) => format!(
+                "Cannot listen on {}: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::CannotListen { path, source }, Ja) => format!(
+                "{} で待ち受けできません: {}",
+                path.display(),
+                io_localized(source, locale)
+            ),
+            (Reason::UnixOnly(feature), En) => format!("{} is only supported on Unix", feature),
+            (Reason::UnixOnly(feature), Ja) => format!("{} はUnixでのみ使えます", feature),
+            (Reason::NeedsSerde(feature), En) => format!("{} needs the serde feature", feature),
+            (Reason::NeedsSerde(feature), Ja) => {
+                format!("{} には serde 機能が必要です", feature)
+            }
+        }
+    }
+}
+
+/// 入出力のエラーの文言 (中身が `Reason` なら `locale` の言語にし、OSのエラーはそのまま)
+fn io_localized(e: &io::Error, locale: Locale) -> String {
+    match e.get_ref().and_then(|inner| inner.downcast_ref::<Reason>()) {
+        Some(reason) => reason.localized(locale),
+        None => e.to_string(),
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localized(Locale::En))
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.localized(Locale::En))
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Terminal(e) | Error::Export(e) | Error::Network(e) => Some(e),
+            Error::Validation(_) | Error::Config(_) => None,
+        }
+    }
+}
+
+impl std::error::Error for Reason {}
+
+/// ターミナル以外の入出力は呼び出し側で `Config` や `Export` に包む
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Terminal(e)
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(e: ConfigError) -> Self {
+        match e {
+            ConfigError::MessageTooLong { .. } => Error::Validation(Reason::Config(e)),
+            ConfigError::UnknownColor(_)
+            | ConfigError::UnknownMessageEffect(_)
+            | ConfigError::UnknownPalette(_)
+            | ConfigError::Conflict { .. } => Error::Config(Reason::Config(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    // ================================================================
+    // Error::exit_code: 終了コードの仕様
+    // ================================================================
+
+    mod describe_exit_code {
+        use super::*;
+
+        #[rstest]
+        #[case::メッセージ(Error::Validation(Reason::NoMessages), 65)]
+        #[case::設定(Error::Config(Reason::InvalidFigletHeader), 78)]
+        #[case::ターミナル(Error::Terminal(io::Error::other("x")), 74)]
+        #[case::書き出し(Error::Export(io::Error::other("x")), 73)]
+        #[case::通信(Error::Network(io::Error::other("x")), 69)]
+        fn 種類ごとに異なる終了コードを返す(
+            #[case] error: Error,
+            #[case] expected: i32,
+        ) {
+            assert_eq!(error.exit_code(), expected);
+        }
+
+        #[test]
+        fn clapの使い方のエラーと重ならない() {
+            for code in [
+                Error::EXIT_VALIDATION,
+                Error::EXIT_CONFIG,
+                Error::EXIT_TERMINAL,
+                Error::EXIT_EXPORT,
+                Error::EXIT_NETWORK,
+            ] {
+                assert!(code != 0 && code != 1 && code != 2);
+            }
+        }
+    }
+
+    // ================================================================
+    // From<ConfigError>: 設定エラーの変換の仕様
+    // ================================================================
+
+    mod describe_from_config_error {
+        use super::*;
+
+        #[test]
+        fn 長すぎるメッセージはメッセージのエラーにする() {
+            let error = Error::from(ConfigError::MessageTooLong { max: 100 });
+            assert!(matches!(error, Error::Validation(_)));
+            assert_eq!(error.to_string(), "Message too long (max 100 characters)");
+        }
+
+        #[rstest]
+        #[case::色(ConfigError::UnknownColor("purple".to_string()))]
+        #[case::エフェクト(ConfigError::UnknownMessageEffect("wobble".to_string()))]
+        #[case::パレット(ConfigError::UnknownPalette("sepia".to_string()))]
+        fn 知らない名前は設定のエラーにする(#[case] error: ConfigError) {
+            assert_eq!(Error::from(error).exit_code(), Error::EXIT_CONFIG);
+        }
+    }
+
+    // ================================================================
+    // Display: エラー表示の仕様
+    // ================================================================
+
+    mod describe_display {
+        use super::*;
+
+        #[test]
+        fn 入出力のエラーは原因を添えて表示する() {
+            let error = Error::from(io::Error::other("not a tty"));
+            assert_eq!(error.to_string(), "Terminal I/O failed: not a tty");
+            assert!(std::error::Error::source(&error).is_some());
+        }
+    }
+
+    // ================================================================
+    // Error::localized: 言語ごとのエラー表示の仕様
+    // ================================================================
+
+    mod describe_localized {
+        use super::*;
+
+        #[rstest]
+        #[case::メッセージ(Error::from(ConfigError::MessageTooLong { max: 100 }))]
+        #[case::行(Error::Validation(Reason::Line {
+            line: 2,
+            error: ConfigError::UnknownColor("purple".to_string()),
+        }))]
+        #[case::フォント(Error::Config(Reason::NotFiglet))]
+        #[case::通信(Error::Network(io::Error::other(Reason::HostNotFound)))]
+        #[case::機能(Error::Config(Reason::NeedsSerde("love serve")))]
+        fn 日本語では英語と異なる文言にする(#[case] error: Error) {
+            assert_ne!(error.localized(Locale::Ja), error.localized(Locale::En));
+        }
+
+        #[test]
+        fn 英語の文言は表示と同じ() {
+            let error = Error::Validation(Reason::TooManyMessages { max: 3 });
+            assert_eq!(error.localized(Locale::En), error.to_string());
+        }
+
+        #[test]
+        fn 入出力のエラーに包んだ理由も訳す() {
+            let error = Error::Network(io::Error::other(Reason::HostNotFound));
+            assert_eq!(
+                error.localized(Locale::Ja),
+                "通信に失敗しました: ホストが見つかりません"
+            );
+        }
+
+        #[test]
+        fn 端末から返ったエラーの文言はそのまま添える() {
+            let error = Error::Terminal(io::Error::other("not a tty"));
+            assert_eq!(
+                error.localized(Locale::Ja),
+                "ターミナルの入出力に失敗しました: not a tty"
+            );
+        }
+
+        #[test]
+        fn 相手の送ってきた理由は訳さない() {
+            let error = Error::Network(io::Error::other(Reason::LetterRejected(
+                "Message too long".to_string(),
+            )));
+            assert!(error.localized(Locale::Ja).ends_with(": Message too long"));
+        }
+
+        #[test]
+        fn 日本語の行番号を添える() {
+            let error = Error::Validation(Reason::Line {
+                line: 2,
+                error: ConfigError::MessageTooLong { max: 100 },
+            });
+            assert_eq!(
+                error.localized(Locale::Ja),
+                "2行目: メッセージが長すぎます (最大100文字)"
+            );
+        }
+    }
+}
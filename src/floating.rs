@@ -0,0 +1,257 @@
+use crate::{heart_sizes, message_row, HeartConfig, MessageState};
+use std::time::Duration;
+
+// 揺れ幅 (列) と、揺れ1周にかかるtick数
+const SWAY_AMPLITUDE: f64 = 4.0;
+const SWAY_PERIOD: f64 = 16.0;
+
+/// 動かないハートを置いておくtick数
+pub const STILL_LIFETIME: i32 = 40;
+
+/// ハートの浮かび方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    /// まっすぐ浮かぶ
+    Rise,
+    /// 左右に揺れながら浮かぶ
+    Sway,
+    /// 1tickごとに指定した列数だけ横に流れながら浮かぶ
+    Drift(i32),
+    /// その場から動かず、`STILL_LIFETIME` tickで消える (--reduced-motion)
+    Still,
+}
+
+/// 左上の座標を持ち、他のハートとは独立に浮かぶハート
+pub struct FloatingHeart {
+    pub config: HeartConfig,
+    pub motion: Motion,
+    left: i32,
+    top: i32,
+    age: i32,
+}
+
+impl FloatingHeart {
+    pub fn new(config: HeartConfig, left: i32, top: i32, motion: Motion) -> Self {
+        FloatingHeart {
+            config,
+            motion,
+            left,
+            top,
+            age: 0,
+        }
+    }
+
+    /// 指定した位置を中心にハートを置く
+    pub fn centered_at(config: HeartConfig, column: i32, row: i32, motion: Motion) -> Self {
+        let (heart_size, half_size) = heart_sizes(&config);
+        FloatingHeart::new(config, column - (heart_size + 1), row - half_size, motion)
+    }
+
+    /// 現在の左端の列
+    pub fn left(&self) -> i32 {
+        match self.motion {
+            Motion::Rise | Motion::Still => self.left,
+            Motion::Sway => {
+                let phase = self.age as f64 / SWAY_PERIOD * std::f64::consts::TAU;
+                self.left + (phase.sin() * SWAY_AMPLITUDE).round() as i32
+            }
+            Motion::Drift(step) => self.left + step * self.age,
+        }
+    }
+
+    /// 現在の上端の行
+    pub fn top(&self) -> i32 {
+        self.top
+    }
+
+    /// 1行浮かせる (動かないハートは時間だけ進める)
+    pub fn advance(&mut self) {
+        if self.motion != Motion::Still {
+            self.top -= 1;
+        }
+        self.age += 1;
+    }
+
+    /// 1tickを `tick` として、浮かび始めてからの時間で決めたメッセージの状態
+    ///
+    /// エフェクトはメッセージ行が見えてから (動かないハートは置いた時から) 進める。
+    pub fn message_state(&self, tick: Duration, marquee_speed: u64) -> MessageState {
+        let started = tick.saturating_mul(self.age.max(0) as u32);
+        let shown = match self.motion {
+            Motion::Still => self.age,
+            _ => self.age - message_row(&self.config),
+        };
+        let started_ms = u64::try_from(started.as_millis()).unwrap_or(u64::MAX);
+        MessageState {
+            marquee_offset: (started_ms.saturating_mul(marquee_speed) / 1000) as usize,
+            elapsed: tick.saturating_mul(shown.max(0) as u32),
+        }
+    }
+
+    /// 画面の上端を抜けきったか (動かないハートは置いておく時間が過ぎたか)
+    pub fn is_gone(&self) -> bool {
+        if self.motion == Motion::Still {
+            return self.age >= STILL_LIFETIME;
+        }
+        let (heart_size, _) = heart_sizes(&self.config);
+        self.top + heart_size < 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn heart(motion: Motion) -> FloatingHeart {
+        FloatingHeart::new(HeartConfig::default(), 10, 5, motion)
+    }
+
+    // ================================================================
+    // FloatingHeart: 浮かぶハートの仕様
+    // ================================================================
+
+    mod describe_floating_heart {
+        use super::*;
+
+        mod advanceした場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::まっすぐ(Motion::Rise)]
+            #[case::揺れる(Motion::Sway)]
+            #[case::流れる(Motion::Drift(1))]
+            fn 上に1行浮かぶ(#[case] motion: Motion) {
+                let mut heart = heart(motion);
+                heart.advance();
+                assert_eq!(heart.top(), 4);
+            }
+
+            #[test]
+            fn まっすぐ浮かぶハートは横に動かない() {
+                let mut heart = heart(Motion::Rise);
+                heart.advance();
+                assert_eq!(heart.left(), 10);
+            }
+
+            #[test]
+            fn 流れるハートは1tickごとに横へ動く() {
+                let mut heart = heart(Motion::Drift(-2));
+                heart.advance();
+                heart.advance();
+                assert_eq!(heart.left(), 6);
+            }
+
+            #[test]
+            fn 揺れるハートは揺れ幅の範囲で左右に動く() {
+                let mut heart = heart(Motion::Sway);
+                let mut lefts = Vec::new();
+                for _ in 0..SWAY_PERIOD as i32 {
+                    heart.advance();
+                    lefts.push(heart.left());
+                }
+                assert!(lefts.iter().any(|&left| left > 10));
+                assert!(lefts.iter().any(|&left| left < 10));
+                assert!(lefts
+                    .iter()
+                    .all(|&left| (left - 10).abs() <= SWAY_AMPLITUDE as i32));
+            }
+        }
+
+        mod centered_atで置いた場合 {
+            use super::*;
+
+            #[test]
+            fn 指定位置がハートの中心になる() {
+                let config = HeartConfig::builder().petite(true).build().unwrap();
+                let heart = FloatingHeart::centered_at(config, 40, 20, Motion::Rise);
+                assert_eq!(heart.left(), 29);
+                assert_eq!(heart.top(), 15);
+            }
+        }
+
+        mod 上端を抜けきったかの判定 {
+            use super::*;
+
+            #[test]
+            fn 最下行が画面内にある間はfalseを返す() {
+                let mut heart = heart(Motion::Rise);
+                for _ in 0..25 {
+                    heart.advance();
+                }
+                assert!(!heart.is_gone());
+            }
+
+            #[test]
+            fn 最下行が画面外に出たらtrueを返す() {
+                let mut heart = heart(Motion::Rise);
+                for _ in 0..26 {
+                    heart.advance();
+                }
+                assert!(heart.is_gone());
+            }
+        }
+
+        mod 動かないハートの場合 {
+            use super::*;
+
+            #[test]
+            fn advanceしても位置が変わらない() {
+                let mut heart = heart(Motion::Still);
+                heart.advance();
+                assert_eq!((heart.left(), heart.top()), (10, 5));
+            }
+
+            #[test]
+            fn 置いておく時間が過ぎたら消える() {
+                let mut heart = heart(Motion::Still);
+                for _ in 1..STILL_LIFETIME {
+                    heart.advance();
+                }
+                assert!(!heart.is_gone());
+                heart.advance();
+                assert!(heart.is_gone());
+            }
+        }
+
+        mod メッセージの状態 {
+            use super::*;
+
+            const TICK: Duration = Duration::from_millis(100);
+
+            fn with_message(motion: Motion) -> FloatingHeart {
+                let config = HeartConfig::builder().message("Hi").build().unwrap();
+                FloatingHeart::new(config, 10, 24, motion)
+            }
+
+            #[test]
+            fn 浮かぶハートはメッセージ行が見えてからエフェクトを進める() {
+                let mut heart = with_message(Motion::Rise);
+                for _ in 0..message_row(&heart.config) {
+                    assert_eq!(heart.message_state(TICK, 8).elapsed, Duration::ZERO);
+                    heart.advance();
+                }
+                heart.advance();
+                heart.advance();
+                assert_eq!(heart.message_state(TICK, 8).elapsed, TICK * 2);
+            }
+
+            #[test]
+            fn 動かないハートは置いた時から進める() {
+                let mut heart = with_message(Motion::Still);
+                heart.advance();
+                assert_eq!(heart.message_state(TICK, 8).elapsed, TICK);
+            }
+
+            #[test]
+            fn マーキーは浮かび始めてからの時間で流す() {
+                let mut heart = with_message(Motion::Rise);
+                for _ in 0..10 {
+                    heart.advance();
+                }
+                // 1秒で8文字
+                assert_eq!(heart.message_state(TICK, 8).marquee_offset, 8);
+            }
+        }
+    }
+}
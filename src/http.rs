@@ -0,0 +1,532 @@
+//! ブラウザにアニメーションを配信する小さなHTTPサーバー (`love serve`)
+//!
+//! `GET /` でページを返し、ページは `GET /events` から Server-Sent Events でフレームを受け取って描く。
+//! フレームは `love --output json` と同じJSONで、ハートは `Animation` がそのまま描いたもの。
+
+use crate::animation::Animation;
+use crate::bigtext::Font;
+use crate::effects::MessageEffect;
+use crate::error::{Error, Reason, Result};
+use crate::palette::Palette;
+use crate::server::{
+    ConnectionLimiter, Limits, Refusal, DEFAULT_SCREEN_SIZE, MAX_SCREEN_COLS, MAX_SCREEN_ROWS,
+    REFUSAL_TIMEOUT,
+};
+use crate::{validate_message, HeartColor, HeartConfig};
+use crossterm::style::Attribute;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 同時に配信するクライアントの上限 (超えた分は 503、同じアドレスからの多すぎる接続は 429 を返す)
+// ページとイベントで1つのブラウザから2つ接続し、イベントは切れると自動でつなぎ直す
+pub const HTTP_LIMITS: Limits = Limits {
+    max_clients: 32,
+    max_clients_per_address: 8,
+    max_connects_per_address: 30,
+    window: Duration::from_secs(10),
+};
+
+// リクエスト行とヘッダーの合計の上限 (リソース枯渇対策)
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+// リクエストを受け取りきるまでの期限と、1回の送信の待ち時間 (遅いクライアントで詰まらないように)
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const INDEX_HTML: &str = include_str!("http/index.html");
+
+/// クエリから組み立てた配信の内容
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamRequest {
+    pub config: HeartConfig,
+    pub cols: u16,
+    pub rows: u16,
+    /// 浮かべる代わりに中央に置いたまま、現れる時と消える時だけフェードする
+    pub reduced_motion: bool,
+}
+
+/// `?message=Hi&color=red` のようなクエリを、`HeartConfig` の項目名で解釈する
+///
+/// 装飾は `bold` / `italic` / `underline` / `blink`、ブロック文字は `big_text`、
+/// フレームの広さは `cols` / `rows`、動きを控えるかは `reduced_motion` で指定する。
+/// メッセージは `validate_message` を通す。
+pub fn parse_query(query: &str) -> Result<StreamRequest> {
+    let mut builder = HeartConfig::builder();
+    let mut attributes = crossterm::style::Attributes::default();
+    let (mut cols, mut rows) = DEFAULT_SCREEN_SIZE;
+    let mut reduced_motion = false;
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = percent_decode(key);
+        let value = percent_decode(value);
+
+        builder = match key.as_str() {
+            "message" => builder.message(validate_message(&value)?),
+            "color" => builder.color(parse_color(&value)?),
+            "message_color" => builder.message_color(parse_color(&value)?),
+            "message_bg" => builder.message_bg(parse_color(&value)?),
+            "message_effect" => builder.message_effect(value.parse::<MessageEffect>()?),
+            "palette" => builder.palette(value.parse::<Palette>()?),
+            "petite" => builder.petite(parse_flag(&key, &value)?),
+            "marquee" => builder.marquee(parse_flag(&key, &value)?),
+            "auto_contrast" => builder.auto_contrast(parse_flag(&key, &value)?),
+            "filled" => builder.filled(parse_flag(&key, &value)?),
+            "big_text" if parse_flag(&key, &value)? => builder.font(Font::builtin()),
+            "big_text" => builder,
+            "bold" | "italic" | "underline" | "blink" => {
+                if parse_flag(&key, &value)? {
+                    attributes.set(match key.as_str() {
+                        "bold" => Attribute::Bold,
+                        "italic" => Attribute::Italic,
+                        "underline" => Attribute::Underlined,
+                        _ => Attribute::SlowBlink,
+                    });
+                }
+                builder
+            }
+            "cols" => {
+                cols = parse_size(&key, &value, MAX_SCREEN_COLS)?;
+                builder
+            }
+            "rows" => {
+                rows = parse_size(&key, &value, MAX_SCREEN_ROWS)?;
+                builder
+            }
+            "reduced_motion" => {
+                reduced_motion = parse_flag(&key, &value)?;
+                builder
+            }
+            _ => {
+                return Err(Error::Validation(Reason::UnknownQueryParameter(
+                    key.to_string(),
+                )))
+            }
+        };
+    }
+
+    Ok(StreamRequest {
+        config: builder.message_attributes(attributes).build()?,
+        cols,
+        rows,
+        reduced_motion,
+    })
+}
+
+fn parse_color(value: &str) -> Result<HeartColor> {
+    Ok(value.parse::<HeartColor>()?)
+}
+
+/// `bold` `bold=1` `bold=true` `bold=on` を真、`0` `false` `off` を偽とする
+fn parse_flag(key: &str, value: &str) -> Result<bool> {
+    match value {
+        "" | "1" | "true" | "on" => Ok(true),
+        "0" | "false" | "off" => Ok(false),
+        _ => Err(Error::Validation(Reason::InvalidFlag {
+            key: key.to_string(),
+            value: value.to_string(),
+        })),
+    }
+}
+
+fn parse_size(key: &str, value: &str, max: u16) -> Result<u16> {
+    match value.parse::<u16>() {
+        Ok(size) if (1..=max).contains(&size) => Ok(size),
+        _ => Err(Error::Validation(Reason::InvalidSize {
+            key: key.to_string(),
+            value: value.to_string(),
+            max,
+        })),
+    }
+}
+
+/// `%XX` と `+` をもとの文字に戻す (不正なUTF-8は置き換える)
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match input
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            {
+                Some(hex) => {
+                    decoded.push(u8::from_str_radix(hex, 16).unwrap_or(b'?'));
+                    i += 2;
+                }
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// アニメーションを配信するHTTPサーバー
+pub struct HttpServer {
+    listener: TcpListener,
+    limiter: Arc<ConnectionLimiter>,
+}
+
+impl HttpServer {
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self> {
+        Ok(HttpServer {
+            listener: TcpListener::bind(addr).map_err(Error::Network)?,
+            limiter: ConnectionLimiter::new(HTTP_LIMITS),
+        })
+    }
+
+    /// 同時接続数と接続の頻度の上限を変える
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limiter = ConnectionLimiter::new(limits);
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener.local_addr().map_err(Error::Network)
+    }
+
+    /// 接続を待ち受け、1接続につき1スレッドで応答し続ける
+    pub fn run(self) -> Result<()> {
+        for stream in self.listener.incoming() {
+            // 接続の途中で切られただけなら、待ち受けは続ける
+            let Ok(stream) = stream else { continue };
+            let Ok(peer) = stream.peer_addr() else {
+                continue;
+            };
+
+            match self.limiter.acquire(peer.ip(), Instant::now()) {
+                Ok(connection) => {
+                    thread::spawn(move || {
+                        let _connection = connection;
+                        // 送れなくなったクライアントは黙って切る
+                        let _ = handle(stream);
+                    });
+                }
+                Err(refusal) => {
+                    let status = match refusal {
+                        Refusal::TooManyClients => "503 Service Unavailable",
+                        Refusal::TooManyFromAddress | Refusal::TooFrequent => {
+                            "429 Too Many Requests"
+                        }
+                    };
+                    let _ = stream.set_write_timeout(Some(REFUSAL_TIMEOUT));
+                    let _ = respond(stream, status, "text/plain", &format!("{}\n", refusal));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 1つの接続のリクエストを読み、応答する
+fn handle(stream: TcpStream) -> io::Result<()> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let Some(target) = read_request_target(&stream, Instant::now() + REQUEST_TIMEOUT)? else {
+        return respond(stream, "400 Bad Request", "text/plain", "Bad request\n");
+    };
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+
+    match path {
+        "/" => respond(stream, "200 OK", "text/html; charset=utf-8", INDEX_HTML),
+        "/events" => match parse_query(query) {
+            Ok(request) => stream_events(stream, request),
+            Err(e) => respond(stream, "400 Bad Request", "text/plain", &format!("{}\n", e)),
+        },
+        _ => respond(stream, "404 Not Found", "text/plain", "Not found\n"),
+    }
+}
+
+/// リクエスト行から GET の対象を取り出す (GET 以外や壊れたリクエストは None)
+///
+/// 少しずつ送り続けるクライアントに居座られないよう、`deadline` までに読みきれなければエラーにする。
+fn read_request_target(stream: &TcpStream, deadline: Instant) -> io::Result<Option<String>> {
+    let stream = DeadlineReader { stream, deadline };
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // ヘッダーは使わないが、応答の前に読み切っておく
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    Ok(match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) if target.starts_with('/') => Some(target.to_string()),
+        _ => None,
+    })
+}
+
+/// 読むたびに期限までの残り時間を読み込みの待ち時間にする
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Request was not received in time",
+            ));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+fn respond(mut stream: TcpStream, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// クライアントが切断するまで、フレームを Server-Sent Events で送り続ける
+///
+/// ハートが抜けきったら、また下から浮かべ直す。
+fn stream_events(mut stream: TcpStream, request: StreamRequest) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n"
+    )?;
+
+    loop {
+        let animation = Animation::new(request.config.clone(), request.cols, request.rows)
+            .with_reduced_motion(request.reduced_motion);
+        for frame in animation {
+            let json = serde_json::to_string(&frame)?;
+            write!(stream, "data: {}\n\n", json)?;
+            stream.flush()?;
+            thread::sleep(frame.delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    // ================================================================
+    // parse_query: クエリの仕様
+    // ================================================================
+
+    mod describe_parse_query {
+        use super::*;
+
+        mod 空の場合 {
+            use super::*;
+
+            #[test]
+            fn 既定の設定と広さを返す() {
+                assert_eq!(
+                    parse_query("").unwrap(),
+                    StreamRequest {
+                        config: HeartConfig::default(),
+                        cols: 80,
+                        rows: 24,
+                        reduced_motion: false,
+                    }
+                );
+            }
+        }
+
+        mod 項目を指定した場合 {
+            use super::*;
+
+            #[test]
+            fn heart_configの項目に対応させる() {
+                let request = parse_query(
+                    "message=I+love%20you&color=magenta&petite&message_effect=fade&bold=1&filled=true&cols=40&rows=30&reduced_motion",
+                )
+                .unwrap();
+                let config = request.config;
+                assert_eq!(config.message.as_deref(), Some("I love you"));
+                assert_eq!(config.color, HeartColor::Magenta);
+                assert!(config.petite);
+                assert_eq!(config.message_effect, Some(MessageEffect::Fade));
+                assert!(config.message_attributes.has(Attribute::Bold));
+                assert!(config.filled);
+                assert_eq!((request.cols, request.rows), (40, 30));
+                assert!(request.reduced_motion);
+            }
+
+            #[test]
+            fn paletteで色を置き換える() {
+                let request = parse_query("color=red&palette=high-contrast").unwrap();
+                assert_eq!(request.config.palette, Some(Palette::HighContrast));
+            }
+
+            #[test]
+            fn big_textは組み込みフォントを使う() {
+                let request = parse_query("message=Hi&big_text").unwrap();
+                assert_eq!(request.config.font, Some(Font::builtin()));
+            }
+
+            #[test]
+            fn メッセージの制御文字を除く() {
+                let request = parse_query("message=Hello%1B%5B31mWorld").unwrap();
+                assert_eq!(request.config.message.as_deref(), Some("Hello[31mWorld"));
+            }
+        }
+
+        mod 不正な値の場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::長すぎるメッセージ(&format!("message={}", "a".repeat(101)), "Message too long")]
+            #[case::知らない色(&"color=purple".to_string(), "Unknown color")]
+            #[case::知らないエフェクト(&"message_effect=blink".to_string(), "Unknown message effect")]
+            #[case::知らないパレット(&"palette=sepia".to_string(), "Unknown palette")]
+            #[case::真偽値でない(&"petite=maybe".to_string(), "Invalid value")]
+            #[case::広すぎる(&"cols=1000".to_string(), "Invalid value")]
+            #[case::広さゼロ(&"rows=0".to_string(), "Invalid value")]
+            #[case::知らない項目(&"font=/etc/passwd".to_string(), "Unknown query parameter")]
+            #[case::組み合わせ(&"message_color=red&auto_contrast".to_string(), "cannot be used")]
+            fn エラーを返す(#[case] query: &str, #[case] expected: &str) {
+                let err = parse_query(query).unwrap_err();
+                assert!(err.to_string().contains(expected), "{}", err);
+            }
+        }
+    }
+
+    // ================================================================
+    // percent_decode: URLデコードの仕様
+    // ================================================================
+
+    mod describe_percent_decode {
+        use super::*;
+
+        #[rstest]
+        #[case::そのまま("Love", "Love")]
+        #[case::プラスは空白("I+love+you", "I love you")]
+        #[case::パーセント("I%20%E2%9D%A4", "I ❤")]
+        #[case::不完全なパーセント("100%", "100%")]
+        #[case::十六進でない("%zz", "%zz")]
+        #[case::符号つき("%+1", "% 1")]
+        fn もとの文字に戻す(#[case] input: &str, #[case] expected: &str) {
+            assert_eq!(percent_decode(input), expected);
+        }
+    }
+
+    // ================================================================
+    // HttpServer: 待ち受けの仕様
+    // ================================================================
+
+    mod describe_http_server {
+        use super::*;
+
+        fn start() -> SocketAddr {
+            let server = HttpServer::bind("127.0.0.1:0").unwrap();
+            let addr = server.local_addr().unwrap();
+            thread::spawn(move || server.run());
+            addr
+        }
+
+        /// リクエストを送り、ヘッダーと本文の先頭 (最初の空行の後の1行) を返す
+        fn get(addr: SocketAddr, target: &str) -> (String, String) {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", target).unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim_end().is_empty() {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            let mut body = String::new();
+            reader.read_line(&mut body).unwrap();
+            (head, body)
+        }
+
+        #[test]
+        fn ページを返す() {
+            let (head, body) = get(start(), "/");
+            assert!(head.starts_with("HTTP/1.1 200 OK"));
+            assert!(head.contains("text/html"));
+            assert!(body.contains("<!DOCTYPE html>"));
+        }
+
+        #[test]
+        fn イベントでフレームを送る() {
+            let (head, body) = get(start(), "/events?message=Hi&color=red&cols=40&rows=30");
+            assert!(head.contains("text/event-stream"));
+            let json = body.strip_prefix("data: ").unwrap();
+            let frame: serde_json::Value = serde_json::from_str(json).unwrap();
+            assert_eq!(frame["width"], 40);
+            assert_eq!(frame["height"], 30);
+        }
+
+        #[test]
+        fn 不正なクエリは400を返す() {
+            let (head, _) = get(start(), &format!("/events?message={}", "a".repeat(101)));
+            assert!(head.starts_with("HTTP/1.1 400"));
+        }
+
+        #[test]
+        fn 知らないパスは404を返す() {
+            let (head, _) = get(start(), "/admin");
+            assert!(head.starts_with("HTTP/1.1 404"));
+        }
+
+        #[test]
+        fn 少しずつ送り続けても期限で読むのをやめる() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            let (stream, _) = listener.accept().unwrap();
+            thread::spawn(move || {
+                client.write_all(b"GET / HTTP/1.1\r\n").unwrap();
+                // 1回の読み込みの待ち時間より短い間隔でヘッダーを1文字ずつ送る
+                while client.write_all(b"X").is_ok() {
+                    thread::sleep(Duration::from_millis(50));
+                }
+            });
+
+            let started = Instant::now();
+            let deadline = started + Duration::from_millis(300);
+            let err = read_request_target(&stream, deadline).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+            assert!(started.elapsed() < Duration::from_secs(2));
+        }
+
+        #[test]
+        fn 同じアドレスからの接続が多すぎると429を返す() {
+            let server = HttpServer::bind("127.0.0.1:0")
+                .unwrap()
+                .with_limits(Limits {
+                    max_connects_per_address: 1,
+                    ..HTTP_LIMITS
+                });
+            let addr = server.local_addr().unwrap();
+            thread::spawn(move || server.run());
+
+            let (head, _) = get(addr, "/");
+            assert!(head.starts_with("HTTP/1.1 200"));
+            let (head, body) = get(addr, "/");
+            assert!(head.starts_with("HTTP/1.1 429"));
+            assert!(body.contains("slow down"));
+        }
+    }
+}
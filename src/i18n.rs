@@ -0,0 +1,465 @@
+//! ヘルプ・エラーメッセージ・起動時の案内の言語を `LANG` / `LC_MESSAGES` から選ぶ
+//!
+//! ヘルプの文言は英語をそのまま鍵にして日本語の訳を引き、訳のない文言は英語のまま表示する。
+//! エラーの文言は、エラーの種類ごとに `localized` で言語に合わせて組み立てる。
+
+use std::env;
+use unicode_width::UnicodeWidthStr;
+
+/// 表示に使う言語
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Ja,
+}
+
+// 案内の枠の内側の幅 (ターミナルの列数)
+const BANNER_WIDTH: usize = 75;
+
+// 案内の文言を書き始める列 (左側にハートの絵を描く)
+const BANNER_TEXT_COLUMN: usize = 24;
+
+// 案内の左側に描くハートの絵
+const BANNER_ART: [&str; 8] = [
+    "  vvvvvv  vvvvvvv",
+    "vvvvvvvvvvvvvvvvvv",
+    "vvvvvvvvvvvvvvvvvvv",
+    "vvvvvvvvvvvvvvvvvv",
+    "  vvvvvvvvvvvvvv",
+    "    vvvvvvvvvv",
+    "      vvvvvv",
+    "        vv",
+];
+
+// 案内の文言を描く行 (ハートの絵の何行目の右に書くか)
+const BANNER_TEXT_ROWS: [usize; 5] = [0, 2, 3, 4, 6];
+
+const BANNER_EN: [&str; 5] = [
+    "A lovely terminal heart animation.",
+    "Watch the heart float up...",
+    "Add your message inside...",
+    "And share the love!",
+    "Type 'love --help' for more details",
+];
+
+const BANNER_JA: [&str; 5] = [
+    "ターミナルに浮かぶ、かわいいハート",
+    "ハートがふわりと浮かびます...",
+    "中にメッセージを書き込んで...",
+    "想いを届けましょう!",
+    "詳しくは 'love --help' を見てください",
+];
+
+/// 英語の文言 (`{}` は差し込まれる値) と日本語の訳
+const JA: &[(&str, &str)] = &[
+    // エラーの表示 (文言はエラーの種類ごとに `localized` で組み立てる)
+    ("error", "エラー"),
+    // ヘルプの見出しと組み込みのオプション
+    ("Usage:", "使い方:"),
+    ("Options", "オプション"),
+    ("Arguments", "引数"),
+    ("Commands", "コマンド"),
+    ("Print help", "ヘルプを表示する"),
+    ("Print version", "バージョンを表示する"),
+    (
+        "Print this message or the help of the given subcommand(s)",
+        "このヘルプか、指定したサブコマンドのヘルプを表示する",
+    ),
+    // love (love play) のオプション
+    ("Message shown inside the heart", "ハートの中に表示するメッセージ"),
+    ("Heart color", "ハートの色"),
+    ("Draw a smaller heart", "小さいハートを描く"),
+    (
+        "Float varied hearts endlessly until any key or mouse input",
+        "キーかマウスの入力があるまで、いろいろなハートを浮かべ続ける",
+    ),
+    (
+        "Read messages (one per line) from a file and show one per heart",
+        "ファイルからメッセージを1行ずつ読み込み、ハートごとに1つ表示する",
+    ),
+    (
+        "Read messages (one per line) from standard input and show one per heart",
+        "標準入力からメッセージを1行ずつ読み込み、ハートごとに1つ表示する",
+    ),
+    (
+        "Show the messages in random order",
+        "メッセージをランダムな順に表示する",
+    ),
+    (
+        "Keep one heart looping and rotate its message every SECONDS",
+        "1つのハートを浮かべ続け、SECONDS秒ごとにメッセージを入れ替える",
+    ),
+    (
+        "Fill the whole screen with a background color",
+        "画面全体を背景色で塗る",
+    ),
+    (
+        "Print the effective configuration as json or toml instead of playing",
+        "再生せずに、実際に使う設定をjsonかtomlで表示する",
+    ),
+    (
+        "Where to send the frames: draw them in the terminal, or write one JSON object per line",
+        "フレームの出力先: ターミナルに描くか、1行に1つのJSONとして書き出す",
+    ),
+    (
+        "Keep the heart looping and accept `love ctl` commands on a control socket (Unix only)",
+        "ハートを浮かべ続け、ソケットで `love ctl` の操作を受け付ける (Unixのみ)",
+    ),
+    (
+        "Control socket for --daemon (defaults to $XDG_RUNTIME_DIR/tolove-ru.sock)",
+        "--daemon の操作を受け付けるソケット (既定は $XDG_RUNTIME_DIR/tolove-ru.sock)",
+    ),
+    (
+        "Keep the heart still and fade it in and out instead of floating it up",
+        "ハートを浮かべずに止めたまま、ゆっくり現れて消えるように表示する",
+    ),
+    (
+        "Print a plain-text description of the heart to stderr before playing",
+        "再生する前に、ハートの説明を文字だけで標準エラー出力に書き出す",
+    ),
+    (
+        "Print a plain-text description of the heart instead of drawing it",
+        "ハートを描く代わりに、ハートの説明を文字だけで書き出す",
+    ),
+    (
+        "Scroll messages that do not fit inside the heart",
+        "ハートに収まらないメッセージを横に流す",
+    ),
+    (
+        "Marquee scrolling speed in characters per second",
+        "メッセージを流す速さ (1秒あたりの文字数)",
+    ),
+    (
+        "Reveal the message over time (typewriter, fade, sparkle)",
+        "メッセージを時間をかけて表示する (typewriter, fade, sparkle)",
+    ),
+    (
+        "Draw the message in a large block font inside the heart",
+        "ハートの中にメッセージを大きなブロック文字で描く",
+    ),
+    (
+        "Draw the message in big text with a FIGlet font (.flf)",
+        "FIGletフォント (.flf) でメッセージを大きく描く",
+    ),
+    (
+        "Message text color (defaults to the heart color)",
+        "メッセージの文字色 (既定はハートの色)",
+    ),
+    ("Message background color", "メッセージの背景色"),
+    ("Draw the message in bold", "メッセージを太字で描く"),
+    ("Draw the message in italics", "メッセージを斜体で描く"),
+    ("Underline the message", "メッセージに下線を引く"),
+    ("Make the message blink", "メッセージを点滅させる"),
+    (
+        "Draw the message in black or white on the heart color, whichever reads better",
+        "ハートの色の上で読みやすい黒か白でメッセージを描く",
+    ),
+    (
+        "Draw the heart as a solid shape of colored cells instead of \"v\" glyphs",
+        "ハートを \"v\" の文字ではなく色で塗りつぶして描く",
+    ),
+    (
+        "Remap every color (deuteranopia, protanopia, tritanopia, high-contrast; see `love palettes`)",
+        "全ての色を置き換える (deuteranopia, protanopia, tritanopia, high-contrast。`love palettes` を参照)",
+    ),
+    // サブコマンド
+    (
+        "Float the heart up this terminal (the default when no command is given)",
+        "このターミナルにハートを浮かべる (コマンドを省略した場合と同じ)",
+    ),
+    (
+        "Print a still heart to stdout, e.g. for a motd or a commit message",
+        "ハートの静止画を標準出力に書き出す (motdやコミットメッセージなどに)",
+    ),
+    (
+        "Write every frame of the animation to a file or stdout without playing it",
+        "再生せずに、アニメーションの全てのフレームをファイルか標準出力に書き出す",
+    ),
+    ("File to write (defaults to stdout)", "書き出すファイル (既定は標準出力)"),
+    (
+        "json writes one frame per line; asciicast can be replayed with `asciinema play`",
+        "jsonは1行に1フレーム、asciicastは `asciinema play` で再生できる形式",
+    ),
+    (
+        "Width of the frames (defaults to the terminal width, or 80 when not a terminal)",
+        "フレームの幅 (既定はターミナルの幅、ターミナルでなければ80)",
+    ),
+    (
+        "Height of the frames (defaults to the terminal height, or 24 when not a terminal)",
+        "フレームの高さ (既定はターミナルの高さ、ターミナルでなければ24)",
+    ),
+    (
+        "Print the effective configuration as json or toml",
+        "実際に使う設定をjsonかtomlで表示する",
+    ),
+    (
+        "Serve the animation to browsers, e.g. http://localhost:8080/?message=Hi&color=red",
+        "ブラウザにアニメーションを配信する (例: http://localhost:8080/?message=Hi&color=red)",
+    ),
+    ("Port to listen on", "待ち受けるポート"),
+    (
+        "Address to listen on (use 0.0.0.0 to let other machines on the network connect)",
+        "待ち受けるアドレス (0.0.0.0 にするとネットワーク上の他のマシンからつなげる)",
+    ),
+    (
+        "Serve the animation to terminals over TCP, e.g. `nc localhost 2323` or `telnet localhost 2323`",
+        "TCPでターミナルにアニメーションを配信する (例: `nc localhost 2323` や `telnet localhost 2323`)",
+    ),
+    (
+        "Number of terminals served at the same time",
+        "同時に配信するターミナルの数",
+    ),
+    (
+        "Send a heart to `love listen` on another host, or to another terminal here like write(1)",
+        "別のホストの `love listen` か、write(1) のようにこのマシンの別のターミナルにハートを送る",
+    ),
+    ("Host running `love listen`", "`love listen` を動かしているホスト"),
+    ("Port the listener is waiting on", "相手が待ち受けているポート"),
+    (
+        "Sign the letter with the shared secret in this file",
+        "このファイルの合言葉でハートに署名する",
+    ),
+    (
+        "Terminal to send to, e.g. /dev/pts/3 (Unix only)",
+        "送り先のターミナル (例: /dev/pts/3、Unixのみ)",
+    ),
+    (
+        "Send to the user's most recently used terminal that accepts messages (Unix only)",
+        "ユーザーがメッセージを受け付けていて最近使ったターミナルに送る (Unixのみ)",
+    ),
+    (
+        "Wait for hearts sent with `love send <host>` and play each one as it arrives",
+        "`love send <host>` で送られたハートを待ち、届いた順に流す",
+    ),
+    (
+        "Address to listen on (use 0.0.0.0 to let other machines on the network send hearts)",
+        "待ち受けるアドレス (0.0.0.0 にするとネットワーク上の他のマシンから送れる)",
+    ),
+    (
+        "Only accept letters signed with the shared secret in this file",
+        "このファイルの合言葉で署名されたハートだけを受け取る",
+    ),
+    (
+        "Change a running `love --daemon` without restarting it (Unix only)",
+        "動いている `love --daemon` を再起動せずに操作する (Unixのみ)",
+    ),
+    (
+        "Control socket of the daemon (defaults to the same place as --daemon)",
+        "操作するデーモンのソケット (既定は --daemon と同じ場所)",
+    ),
+    (
+        "Replace the message inside the heart (an empty message removes it)",
+        "ハートの中のメッセージを差し替える (空にするとメッセージを消す)",
+    ),
+    ("Change the heart color", "ハートの色を変える"),
+    (
+        "Float a burst of hearts across the screen",
+        "画面のあちこちからハートをまとめて浮かべる",
+    ),
+    (
+        "List the color palettes for --palette with a preview swatch of each",
+        "--palette で選べるパレットを色の見本つきで一覧表示する",
+    ),
+    // `love palettes` の説明
+    (
+        "For green-weak color vision (Okabe-Ito colors)",
+        "緑を感じにくい色覚向け (Okabe-Ito の配色)",
+    ),
+    (
+        "For red-weak color vision (red becomes a bright orange)",
+        "赤を感じにくい色覚向け (赤を明るい橙にする)",
+    ),
+    (
+        "For blue-yellow color blindness (told apart by brightness)",
+        "青と黄を見分けにくい色覚向け (明るさの差で見分ける)",
+    ),
+    (
+        "Bright, saturated colors that stand out on a dark background",
+        "暗い背景の上で見やすい、明るく鮮やかな色",
+    ),
+    (
+        "Print a shell completion script for bash, zsh, fish, powershell or elvish",
+        "bash, zsh, fish, powershell, elvish の補完スクリプトを表示する",
+    ),
+    (
+        "Print the man page in roff format, e.g. `love man > ~/.local/share/man/man1/love.1`",
+        "manページをroff形式で表示する (例: `love man > ~/.local/share/man/man1/love.1`)",
+    ),
+];
+
+impl Locale {
+    /// 環境変数 `LC_ALL`・`LC_MESSAGES`・`LANG` の順に見て言語を選ぶ
+    pub fn from_env() -> Self {
+        Locale::detect(|name| env::var(name).ok())
+    }
+
+    /// `var` で引いた環境変数から言語を選ぶ (最初に空でない値で決め、知らない言語は英語)
+    pub fn detect(var: impl Fn(&str) -> Option<String>) -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(var)
+            .find(|value| !value.is_empty())
+            .map_or(Locale::En, |value| Locale::parse(&value))
+    }
+
+    /// `ja_JP.UTF-8` のようなロケール名の言語 (知らない言語は英語)
+    pub fn parse(value: &str) -> Self {
+        let language = value.split(['_', '.', '@', '-']).next().unwrap_or("");
+        match language {
+            "ja" => Locale::Ja,
+            _ => Locale::En,
+        }
+    }
+
+    /// `love --help` の先頭に出す案内 (枠は文字の表示幅に合わせて揃える)
+    pub fn about(self) -> String {
+        let texts = match self {
+            Locale::En => BANNER_EN,
+            Locale::Ja => BANNER_JA,
+        };
+        let border = "-".repeat(BANNER_WIDTH);
+
+        let mut banner = format!("\n┌{}┐", border);
+        for (row, art) in BANNER_ART.iter().enumerate() {
+            let text = BANNER_TEXT_ROWS
+                .iter()
+                .position(|&text_row| text_row == row)
+                .map_or("", |i| texts[i]);
+            let line = match text {
+                "" => format!(" {}", art),
+                text => format!(" {}{}{}", art, pad(art, BANNER_TEXT_COLUMN - 1), text),
+            };
+            banner.push_str(&format!("\n|{}{}|", line, pad(&line, BANNER_WIDTH)));
+        }
+        banner.push_str(&format!("\n└{}┘", border));
+        banner
+    }
+
+    /// ヘルプなどの決まった英語の文言を訳す (訳がなければそのまま返す)
+    ///
+    /// 値を差し込む文言は、エラーなら `Error::localized` のように種類ごとに組み立てる。
+    pub fn translate(self, text: &str) -> &str {
+        match self {
+            Locale::En => text,
+            Locale::Ja => JA
+                .iter()
+                .find(|(english, _)| *english == text)
+                .map_or(text, |(_, japanese)| japanese),
+        }
+    }
+}
+
+/// `text` の表示幅を `width` 列まで埋める空白
+fn pad(text: &str, width: usize) -> String {
+    " ".repeat(width.saturating_sub(text.width()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ABOUT_MESSAGE;
+    use rstest::rstest;
+
+    // ================================================================
+    // Locale::detect: 言語の選び方の仕様
+    // ================================================================
+
+    mod describe_detect {
+        use super::*;
+
+        fn detect(vars: &[(&str, &str)]) -> Locale {
+            Locale::detect(|name| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            })
+        }
+
+        #[rstest]
+        #[case::日本語(&[("LANG", "ja_JP.UTF-8")], Locale::Ja)]
+        #[case::英語(&[("LANG", "en_US.UTF-8")], Locale::En)]
+        #[case::言語だけ(&[("LANG", "ja")], Locale::Ja)]
+        #[case::cロケール(&[("LANG", "C")], Locale::En)]
+        #[case::訳のない言語(&[("LANG", "fr_FR.UTF-8")], Locale::En)]
+        #[case::何も指定しない(&[], Locale::En)]
+        #[case::lc_messagesを優先(&[("LANG", "en_US.UTF-8"), ("LC_MESSAGES", "ja_JP.UTF-8")], Locale::Ja)]
+        #[case::lc_allを最優先(&[("LC_ALL", "C"), ("LC_MESSAGES", "ja_JP.UTF-8")], Locale::En)]
+        #[case::空の値は飛ばす(&[("LC_ALL", ""), ("LANG", "ja_JP.UTF-8")], Locale::Ja)]
+        fn 環境変数から言語を選ぶ(
+            #[case] vars: &[(&str, &str)],
+            #[case] expected: Locale,
+        ) {
+            assert_eq!(detect(vars), expected);
+        }
+    }
+
+    // ================================================================
+    // Locale::about: 案内の仕様
+    // ================================================================
+
+    mod describe_about {
+        use super::*;
+
+        #[test]
+        fn 英語はabout_messageと同じ() {
+            assert_eq!(Locale::En.about(), ABOUT_MESSAGE);
+        }
+
+        #[test]
+        fn 日本語の案内を表示する() {
+            assert!(Locale::Ja
+                .about()
+                .contains("ターミナルに浮かぶ、かわいいハート"));
+        }
+
+        #[rstest]
+        #[case::英語(Locale::En)]
+        #[case::日本語(Locale::Ja)]
+        fn 全角文字を含んでも枠の右端が揃う(#[case] locale: Locale) {
+            let about = locale.about();
+            let lines: Vec<&str> = about.lines().skip(1).collect();
+            assert_eq!(lines.len(), BANNER_ART.len() + 2);
+            for line in lines {
+                assert_eq!(line.width(), BANNER_WIDTH + 2, "{}", line);
+            }
+        }
+    }
+
+    // ================================================================
+    // Locale::translate: 文言の訳の仕様
+    // ================================================================
+
+    mod describe_translate {
+        use super::*;
+
+        #[rstest]
+        #[case::見出し("Usage:", "使い方:")]
+        #[case::オプションの説明("Heart color", "ハートの色")]
+        #[case::エラーの見出し("error", "エラー")]
+        #[case::訳のない文言("Something else", "Something else")]
+        fn 日本語に訳す(#[case] text: &str, #[case] expected: &str) {
+            assert_eq!(Locale::Ja.translate(text), expected);
+        }
+
+        #[test]
+        fn 英語ではそのまま返す() {
+            assert_eq!(Locale::En.translate("Heart color"), "Heart color");
+        }
+
+        #[rstest]
+        #[case::一部だけ一致("Heart color!")]
+        #[case::前に値を添えた文言("love.flf: Heart color")]
+        fn 文言全体が一致しなければ訳さない(#[case] text: &str) {
+            assert_eq!(Locale::Ja.translate(text), text);
+        }
+
+        #[test]
+        fn 値を差し込む文言を含まない() {
+            for (english, japanese) in JA {
+                assert!(!english.contains("{}"), "{}", english);
+                assert!(!japanese.contains("{}"), "{}", japanese);
+            }
+        }
+    }
+}
@@ -0,0 +1,673 @@
+//! 同じネットワークの相手にハートを届けるラブレター (`love listen` / `love send <host>`)
+//!
+//! 1通ごとに1つのTCP接続で、次の行をやりとりする (どの行も `\n` で終わる)。
+//!
+//! ```text
+//! 受け手: LOVE/1 <nonce>
+//! 送り手: from <名前>
+//!         color <色名>
+//!         petite                  (小さいハートの場合だけ)
+//!         message <メッセージ>    (`\` と改行は `\\` と `\n` にする)
+//!         auth <署名>             (合言葉を使う場合だけ)
+//!         (空行)
+//! 受け手: OK | ERR <理由>
+//! ```
+//!
+//! 署名は、nonce と `auth` より前の行を合言葉で HMAC-SHA256 にかけたものの16進数。
+//! 受け手が合言葉を決めている場合は、署名が合わない手紙を断る。
+
+use crate::animation::{Animation, Frame};
+use crate::config::{escape_message, unescape_message};
+use crate::error::{Error, Reason, Result};
+use crate::server::{ConnectionLimiter, Limits, REFUSAL_TIMEOUT};
+use crate::{sanitize_input, validate_message, HeartColor, HeartConfig};
+use crossterm::cursor::{Hide, Show};
+use crossterm::queue;
+use crossterm::style::ResetColor;
+use crossterm::terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::mpsc::{SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const PROTOCOL: &str = "LOVE/1";
+
+/// `love listen` が待ち受ける既定のポート
+pub const DEFAULT_LETTER_PORT: u16 = 2424;
+
+// 同時接続数と接続の頻度の上限 (手紙は一瞬で届くので少なめにする)
+pub const LETTER_LIMITS: Limits = Limits {
+    max_clients: 8,
+    max_clients_per_address: 2,
+    max_connects_per_address: 10,
+    window: Duration::from_secs(60),
+};
+
+// 1通の手紙として読む長さの上限
+const MAX_LETTER_BYTES: u64 = 1024;
+
+// 1行の送受信の待ち時間 (黙ったままの相手で詰まらないように)
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// 送り主として添える名前の長さの上限
+const MAX_SENDER_LENGTH: usize = 32;
+
+// 使い回しを防ぐため、接続ごとに変える値の長さ (バイト数)
+const NONCE_BYTES: usize = 16;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 送り主の名前と、届けるハートの見た目
+#[derive(Debug, Clone, PartialEq)]
+pub struct Letter {
+    sender: String,
+    config: HeartConfig,
+}
+
+impl Letter {
+    /// 送り主の名前はサニタイズし、長すぎる分は切り詰める (空なら anonymous)
+    pub fn new(sender: &str, config: HeartConfig) -> Self {
+        let sender: String = sanitize_input(sender)
+            .replace('\n', " ")
+            .trim()
+            .chars()
+            .take(MAX_SENDER_LENGTH)
+            .collect();
+        Letter {
+            sender: if sender.is_empty() {
+                "anonymous".to_string()
+            } else {
+                sender
+            },
+            config,
+        }
+    }
+
+    pub fn sender(&self) -> &str {
+        &self.sender
+    }
+
+    pub fn config(&self) -> &HeartConfig {
+        &self.config
+    }
+
+    /// 署名する行 (`auth` と終わりの空行を除く)
+    fn encode(&self) -> String {
+        let mut lines = format!("from {}\ncolor {}\n", self.sender, self.config.color);
+        if self.config.petite {
+            lines.push_str("petite\n");
+        }
+        if let Some(message) = &self.config.message {
+            lines.push_str(&format!("message {}\n", escape_message(message)));
+        }
+        lines
+    }
+
+    /// 受け取った行から手紙を組み立て、メッセージを `validate_message` でバリデーションする
+    pub(crate) fn decode(lines: &str) -> Result<Self> {
+        let mut sender = "";
+        let mut builder = HeartConfig::builder();
+        for line in lines.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            builder = match key {
+                "from" => {
+                    sender = value;
+                    builder
+                }
+                "color" => builder.color(HeartColor::from_str(value)?),
+                "petite" => builder.petite(true),
+                "message" => builder.message(validate_message(&unescape_message(value))?),
+                _ => return Err(Error::Validation(Reason::UnknownField(key.to_string()))),
+            };
+        }
+        Ok(Letter::new(sender, builder.build()?))
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn mac(secret: &[u8], nonce: &str, lines: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(nonce.as_bytes());
+    mac.update(b"\n");
+    mac.update(lines.as_bytes());
+    mac
+}
+
+/// 合言葉のファイルを読む (前後の空白と改行は除く)
+pub fn read_secret(path: &Path) -> Result<Vec<u8>> {
+    let text = fs::read_to_string(path).map_err(|source| {
+        Error::Config(Reason::SecretUnreadable {
+            path: path.to_path_buf(),
+            source,
+        })
+    })?;
+    let secret = text.trim();
+    if secret.is_empty() {
+        return Err(Error::Config(Reason::SecretEmpty(path.to_path_buf())));
+    }
+    Ok(secret.as_bytes().to_vec())
+}
+
+/// `addr` で待ち受ける相手に手紙を届ける
+pub fn send_letter(addr: impl ToSocketAddrs, letter: &Letter, secret: Option<&[u8]>) -> Result<()> {
+    let addr = addr
+        .to_socket_addrs()
+        .map_err(Error::Network)?
+        .next()
+        .ok_or_else(|| Error::Network(io::Error::other(Reason::HostNotFound)))?;
+    let stream = TcpStream::connect_timeout(&addr, TIMEOUT).map_err(Error::Network)?;
+    exchange(&stream, letter, secret).map_err(Error::Network)
+}
+
+fn exchange(mut stream: &TcpStream, letter: &Letter, secret: Option<&[u8]>) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream.take(MAX_LETTER_BYTES));
+
+    let mut greeting = String::new();
+    reader.read_line(&mut greeting)?;
+    // 満員などで断られた場合は、あいさつの代わりに ERR が届く
+    if let Some(reason) = greeting.trim_end().strip_prefix("ERR ") {
+        return Err(rejected(reason));
+    }
+    let nonce = greeting
+        .trim_end()
+        .strip_prefix(PROTOCOL)
+        .and_then(|rest| rest.strip_prefix(' '))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, Reason::NotALetterListener))?;
+
+    let lines = letter.encode();
+    let mut request = lines.clone();
+    if let Some(secret) = secret {
+        let signature = mac(secret, nonce, &lines).finalize().into_bytes();
+        request.push_str(&format!("auth {}\n", to_hex(&signature)));
+    }
+    request.push('\n');
+    stream.write_all(request.as_bytes())?;
+
+    let mut reply = String::new();
+    reader.read_line(&mut reply)?;
+    match reply.trim_end() {
+        "OK" => Ok(()),
+        reply => match reply.strip_prefix("ERR ") {
+            Some(reason) => Err(rejected(reason)),
+            None => Err(io::Error::other(Reason::NoReply("love listen"))),
+        },
+    }
+}
+
+/// 相手に断られた場合のエラー (相手の送ってきた理由は訳さずにそのまま添える)
+fn rejected(reason: &str) -> io::Error {
+    io::Error::other(Reason::LetterRejected(sanitize_input(reason)))
+}
+
+/// 手紙を待ち受けるサーバー
+pub struct LetterServer {
+    listener: TcpListener,
+    secret: Option<Arc<[u8]>>,
+    limiter: Arc<ConnectionLimiter>,
+}
+
+impl LetterServer {
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self> {
+        Ok(LetterServer {
+            listener: TcpListener::bind(addr).map_err(Error::Network)?,
+            secret: None,
+            limiter: ConnectionLimiter::new(LETTER_LIMITS),
+        })
+    }
+
+    /// 合言葉で署名された手紙だけを受け取る
+    pub fn with_secret(mut self, secret: impl Into<Vec<u8>>) -> Self {
+        self.secret = Some(secret.into().into());
+        self
+    }
+
+    /// 同時接続数と接続の頻度の上限を変える
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limiter = ConnectionLimiter::new(limits);
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener.local_addr().map_err(Error::Network)
+    }
+
+    /// 届いた手紙を `letters` に渡し続ける (渡しきれない手紙は断る)
+    pub fn run(self, letters: SyncSender<Letter>) -> Result<()> {
+        for stream in self.listener.incoming() {
+            // 接続の途中で切られただけなら、待ち受けは続ける
+            let Ok(stream) = stream else { continue };
+            let Ok(peer) = stream.peer_addr() else {
+                continue;
+            };
+
+            match self.limiter.acquire(peer.ip(), Instant::now()) {
+                Ok(connection) => {
+                    let secret = self.secret.clone();
+                    let letters = letters.clone();
+                    thread::spawn(move || {
+                        let _connection = connection;
+                        // 返事を送れなくても、受け取った手紙はそのまま届ける
+                        let _ = receive(&stream, peer, secret.as_deref(), &letters);
+                    });
+                }
+                Err(refusal) => {
+                    let _ = stream.set_write_timeout(Some(REFUSAL_TIMEOUT));
+                    let _ = (&stream).write_all(format!("ERR {}\n", refusal).as_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 1通の手紙を受け取り、結果を返事する
+fn receive(
+    mut stream: &TcpStream,
+    peer: SocketAddr,
+    secret: Option<&[u8]>,
+    letters: &SyncSender<Letter>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    // 相手に次の nonce を予想されて署名を使い回されないよう、OSの乱数で作る
+    let mut nonce = [0; NONCE_BYTES];
+    getrandom::fill(&mut nonce)?;
+    let nonce = to_hex(&nonce);
+    stream.write_all(format!("{} {}\n", PROTOCOL, nonce).as_bytes())?;
+
+    let reply = read_letter(stream, &nonce, secret).and_then(|mut letter| {
+        // 名乗りは自由なので、どこから届いたかを添える
+        letter.sender = format!("{}@{}", letter.sender, peer.ip());
+        match letters.try_send(letter) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => {
+                Err(Error::Validation(Reason::TooManyLetters))
+            }
+        }
+    });
+    let reply = match reply {
+        Ok(()) => "OK\n".to_string(),
+        Err(e) => format!("ERR {}\n", e),
+    };
+    stream.write_all(reply.as_bytes())
+}
+
+/// 空行まで届かずに切れた手紙のエラー
+pub(crate) fn incomplete_letter() -> Error {
+    Error::Validation(Reason::IncompleteLetter)
+}
+
+/// 空行までを読み、署名を確かめてから手紙にする
+fn read_letter(stream: &TcpStream, nonce: &str, secret: Option<&[u8]>) -> Result<Letter> {
+    let mut reader = BufReader::new(stream.take(MAX_LETTER_BYTES));
+    let mut lines = String::new();
+    let mut signature = None;
+    loop {
+        let mut line = String::new();
+        if reader
+            .read_line(&mut line)
+            .map_err(|_| incomplete_letter())?
+            == 0
+            || !line.ends_with('\n')
+        {
+            return Err(incomplete_letter());
+        }
+        match line.trim_end_matches(['\r', '\n']) {
+            "" => break,
+            line => match line.strip_prefix("auth ") {
+                Some(auth) => signature = Some(auth.to_string()),
+                None => {
+                    lines.push_str(line);
+                    lines.push('\n');
+                }
+            },
+        }
+    }
+
+    if let Some(secret) = secret {
+        let signature = signature.ok_or(Error::Validation(Reason::AuthenticationRequired))?;
+        let verified = from_hex(&signature)
+            .is_some_and(|signature| mac(secret, nonce, &lines).verify_slice(&signature).is_ok());
+        if !verified {
+            return Err(Error::Validation(Reason::AuthenticationFailed));
+        }
+    }
+    Letter::decode(&lines)
+}
+
+/// アニメーションを1回流し、元の画面に戻してから送り主とメッセージを1行残す
+///
+/// 元の画面を壊さないよう代替スクリーンで流す。フレームの間の待ち時間は `sleep` に任せる。
+pub fn play(
+    out: &mut impl Write,
+    letter: &Letter,
+    (cols, rows): (u16, u16),
+    mut sleep: impl FnMut(Duration),
+) -> io::Result<()> {
+    queue!(out, EnterAlternateScreen, Hide, Clear(ClearType::All))?;
+    for frame in Animation::new(letter.config.clone(), cols, rows) {
+        frame.write_ansi(out, None)?;
+        out.flush()?;
+        sleep(frame.delay);
+    }
+    queue!(out, ResetColor, Show, LeaveAlternateScreen)?;
+    write!(out, "\r\n{}\r\n", note(letter))?;
+    out.flush()
+}
+
+/// 静止したハートと送り主とメッセージを、今のカーソルの位置から書き足す
+///
+/// 他の人の端末に書き込むため、`write(1)` と同じく画面を切り替えたり消したりしない。
+/// 相手の端末が改行で行頭に戻らない設定でも崩れないよう、改行は全て `\r\n` で送る。
+pub fn write_inline(out: &mut impl Write, letter: &Letter) -> io::Result<()> {
+    // 静止画では時間が進まないため、エフェクトをかけずにメッセージを見せる
+    let mut config = letter.config.clone();
+    config.set_message_effect(None);
+
+    let mut lines = Vec::new();
+    Frame::still(&config).write_ansi_lines(&mut lines, None)?;
+    let text = String::from_utf8_lossy(&lines).replace('\n', "\r\n");
+    write!(out, "\r\n{}{}\r\n", text, note(letter))?;
+    out.flush()
+}
+
+/// 最後に残す送り主とメッセージの行 (メッセージの改行も行頭に戻す)
+fn note(letter: &Letter) -> String {
+    let note = match &letter.config.message {
+        Some(message) => format!("Love from {}: {}", letter.sender, message),
+        None => format!("Love from {}", letter.sender),
+    };
+    note.replace('\n', "\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::DEFAULT_SCREEN_SIZE;
+    use rstest::rstest;
+    use std::sync::mpsc::{self, Receiver};
+
+    fn letter(message: &str) -> Letter {
+        let config = HeartConfig::builder()
+            .message(message)
+            .color(HeartColor::Magenta)
+            .petite(true)
+            .build()
+            .unwrap();
+        Letter::new("alice", config)
+    }
+
+    // ================================================================
+    // Letter: 手紙の読み書きの仕様
+    // ================================================================
+
+    mod describe_letter {
+        use super::*;
+
+        mod 書き出して読み込んだ場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::ふつうのメッセージ("Great demo!")]
+            #[case::改行とバックスラッシュ("C:\\new\nline")]
+            fn 元の手紙に戻る(#[case] message: &str) {
+                let sent = letter(message);
+                assert_eq!(Letter::decode(&sent.encode()).unwrap(), sent);
+            }
+        }
+
+        mod 送り主の名前 {
+            use super::*;
+
+            #[rstest]
+            #[case::制御文字("al\x1b]0;x\x07ice", "al]0;xice")]
+            #[case::空("", "anonymous")]
+            #[case::長すぎる(&"a".repeat(100), &"a".repeat(MAX_SENDER_LENGTH))]
+            fn サニタイズして切り詰める(#[case] sender: &str, #[case] expected: &str) {
+                assert_eq!(
+                    Letter::new(sender, HeartConfig::default()).sender(),
+                    expected
+                );
+            }
+        }
+
+        mod 不正な手紙を読み込んだ場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::長すぎるメッセージ(&format!("message {}\n", "a".repeat(101)), "Message too long")]
+            #[case::知らない色名("color purple\n", "Unknown color")]
+            #[case::知らない項目("size 3\n", "Unknown field")]
+            fn エラーを返す(#[case] lines: &str, #[case] expected: &str) {
+                let err = Letter::decode(lines).unwrap_err();
+                assert!(err.to_string().contains(expected), "{}", err);
+            }
+
+            #[test]
+            fn メッセージの制御文字を除く() {
+                let letter = Letter::decode("message Hi\x1b[31m\n").unwrap();
+                assert_eq!(letter.config().message.as_deref(), Some("Hi[31m"));
+            }
+        }
+    }
+
+    // ================================================================
+    // LetterServer / send_letter: 手紙のやりとりの仕様
+    // ================================================================
+
+    mod describe_letter_server {
+        use super::*;
+
+        fn start(secret: Option<&str>) -> (SocketAddr, Receiver<Letter>) {
+            let mut server = LetterServer::bind("127.0.0.1:0").unwrap();
+            if let Some(secret) = secret {
+                server = server.with_secret(secret.as_bytes());
+            }
+            let addr = server.local_addr().unwrap();
+            let (sender, letters) = mpsc::sync_channel(1);
+            thread::spawn(move || server.run(sender));
+            (addr, letters)
+        }
+
+        fn received(letters: &Receiver<Letter>) -> Letter {
+            letters.recv_timeout(Duration::from_secs(5)).unwrap()
+        }
+
+        mod 合言葉がない場合 {
+            use super::*;
+
+            #[test]
+            fn 届いた手紙に送り元のアドレスを添える() {
+                let (addr, letters) = start(None);
+                send_letter(addr, &letter("Hi"), None).unwrap();
+
+                let letter = received(&letters);
+                assert_eq!(letter.sender(), "alice@127.0.0.1");
+                assert_eq!(letter.config().message.as_deref(), Some("Hi"));
+                assert_eq!(letter.config().color, HeartColor::Magenta);
+                assert!(letter.config().petite);
+            }
+
+            #[test]
+            fn 待ちきれない手紙は断る() {
+                let (addr, _letters) = start(None);
+                send_letter(addr, &letter("first"), None).unwrap();
+
+                let err = send_letter(addr, &letter("second"), None).unwrap_err();
+                assert!(err.to_string().contains("Too many letters waiting"));
+            }
+        }
+
+        mod 合言葉がある場合 {
+            use super::*;
+
+            #[test]
+            fn 同じ合言葉で署名した手紙を受け取る() {
+                let (addr, letters) = start(Some("s3cret"));
+                send_letter(addr, &letter("Hi"), Some(b"s3cret")).unwrap();
+                assert_eq!(received(&letters).config().message.as_deref(), Some("Hi"));
+            }
+
+            #[rstest]
+            #[case::署名なし(None, "Authentication required")]
+            #[case::違う合言葉(Some(b"guess".as_slice()), "Authentication failed")]
+            fn 署名が合わない手紙は断る(
+                #[case] secret: Option<&[u8]>,
+                #[case] expected: &str,
+            ) {
+                let (addr, letters) = start(Some("s3cret"));
+                let err = send_letter(addr, &letter("Hi"), secret).unwrap_err();
+                assert_eq!(err.exit_code(), Error::EXIT_NETWORK);
+                assert!(err.to_string().contains(expected), "{}", err);
+                assert!(letters.try_recv().is_err());
+            }
+
+            #[test]
+            fn 署名を使い回した手紙は断る() {
+                let (addr, letters) = start(Some("s3cret"));
+                let mut stream = TcpStream::connect(addr).unwrap();
+                let mut greeting = String::new();
+                BufReader::new(&stream).read_line(&mut greeting).unwrap();
+
+                // 別の nonce で作った署名は通らない
+                let lines = letter("Hi").encode();
+                let signature = mac(b"s3cret", "0000", &lines).finalize().into_bytes();
+                write!(stream, "{}auth {}\n\n", lines, to_hex(&signature)).unwrap();
+
+                let mut reply = String::new();
+                stream.read_to_string(&mut reply).unwrap();
+                assert_eq!(reply, "ERR Authentication failed\n");
+                assert!(letters.try_recv().is_err());
+            }
+        }
+
+        mod 相手が手紙を待ち受けていない場合 {
+            use super::*;
+
+            #[test]
+            fn エラーを返す() {
+                let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+                let addr = listener.local_addr().unwrap();
+                thread::spawn(move || {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    stream.write_all(b"HTTP/1.1 400 Bad Request\r\n").unwrap();
+                });
+
+                let err = send_letter(addr, &letter("Hi"), None).unwrap_err();
+                assert!(err.to_string().contains("Not a love letter listener"));
+            }
+        }
+    }
+
+    // ================================================================
+    // play: 手紙を見せる仕様
+    // ================================================================
+
+    mod describe_play {
+        use super::*;
+
+        fn played(letter: &Letter) -> (String, usize) {
+            let mut out = Vec::new();
+            let mut frames = 0;
+            play(&mut out, letter, DEFAULT_SCREEN_SIZE, |_| frames += 1).unwrap();
+            (String::from_utf8(out).unwrap(), frames)
+        }
+
+        #[test]
+        fn 代替スクリーンでアニメーションを1回流して元に戻す() {
+            let letter = Letter::new("alice", HeartConfig::default());
+            let (out, frames) = played(&letter);
+            assert_eq!(
+                frames,
+                Animation::new(HeartConfig::default(), 80, 24).count()
+            );
+            assert!(out.starts_with("\x1b[?1049h"));
+            assert!(out.contains("\x1b[?1049l"));
+        }
+
+        #[test]
+        fn 送り主とメッセージを最後に残す() {
+            let (out, _) = played(&letter("Lunch?"));
+            assert!(out.ends_with("\x1b[?1049l\r\nLove from alice: Lunch?\r\n"));
+        }
+
+        #[test]
+        fn メッセージがなければ送り主だけを残す() {
+            let (out, _) = played(&Letter::new("alice", HeartConfig::default()));
+            assert!(out.ends_with("\r\nLove from alice\r\n"));
+        }
+
+        #[test]
+        fn メッセージの改行は行頭に戻す() {
+            let (out, _) = played(&letter("Lunch?\nAt noon"));
+            assert!(out.ends_with("Love from alice: Lunch?\r\nAt noon\r\n"));
+        }
+    }
+
+    // ================================================================
+    // write_inline: 他の人の端末へのハートの書き出しの仕様
+    // ================================================================
+
+    mod describe_write_inline {
+        use super::*;
+
+        fn written(letter: &Letter) -> String {
+            let mut out = Vec::new();
+            write_inline(&mut out, letter).unwrap();
+            String::from_utf8(out).unwrap()
+        }
+
+        #[test]
+        fn 画面を切り替えたり消したりしない() {
+            let out = written(&letter("Lunch?"));
+            assert!(!out.contains("\x1b[?1049"));
+            assert!(!out.contains("\x1b[2J"));
+            assert!(!out.contains("\x1b[H"));
+        }
+
+        #[test]
+        fn 静止したハートを書き足す() {
+            let out = written(&letter("Lunch?"));
+            let (drawn, _) = out.rsplit_once("Love from").unwrap();
+            let heart = Frame::still(letter("Lunch?").config()).text();
+            assert!(heart.contains('v'));
+            assert_eq!(drawn.matches('v').count(), heart.matches('v').count());
+        }
+
+        #[test]
+        fn 改行は全て行頭に戻す() {
+            let out = written(&letter("Lunch?\nAt noon"));
+            assert!(out.contains('\n'));
+            assert!(out
+                .match_indices('\n')
+                .all(|(i, _)| out[..i].ends_with('\r')));
+        }
+
+        #[test]
+        fn 送り主とメッセージを最後に残す() {
+            assert!(written(&letter("Lunch?")).ends_with("\r\nLove from alice: Lunch?\r\n"));
+        }
+    }
+}
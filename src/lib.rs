@@ -1,9 +1,44 @@
-use crossterm::style::Color;
+use bigtext::Font;
+use crossterm::style::{Attributes, Color};
+use effects::{MessageEffect, Reveal};
+pub use error::{Error, Reason, Result};
+use palette::Palette;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub mod accessibility;
+pub mod animation;
+pub mod bigtext;
+pub mod config;
+pub mod control;
+pub mod controls;
+pub mod effects;
+pub mod error;
+pub mod floating;
+#[cfg(feature = "serde")]
+pub mod http;
+pub mod i18n;
+pub mod letter;
+pub mod palette;
+pub mod playlist;
+pub mod screensaver;
+#[cfg(unix)]
+pub mod send;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod server;
+pub mod telnet;
+#[cfg(feature = "ratatui")]
+pub mod widget;
 
 // ハートの描画領域
 pub const HEART_SIZE_L: i32 = 20;
 pub const HEART_SIZE_S: i32 = 10;
 
+// マーキー表示でメッセージの末尾から次の先頭までに空ける文字数
+const MARQUEE_GAP: usize = 4;
+
 pub const ABOUT_MESSAGE: &str = "
 ┌---------------------------------------------------------------------------┐
 |   vvvvvv  vvvvvvv      A lovely terminal heart animation.                 |
@@ -17,10 +52,107 @@ pub const ABOUT_MESSAGE: &str = "
 └---------------------------------------------------------------------------┘";
 
 /// CLIフレームワーク非依存のハート設定
+///
+/// `HeartConfig::builder()` で組み立て、メッセージや組み合わせをバリデーションする。
+/// 項目は読み取り専用で、組み立てた後に変えられるのは `set_*` の項目だけ。
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "serialize::HeartConfigData"))]
 pub struct HeartConfig {
-    pub message: Option<String>,
-    pub petite: bool,
-    pub color: String,
+    message: Option<String>,
+    petite: bool,
+    color: HeartColor,
+    /// ハートの内側に収まらないメッセージを横に流して表示する
+    marquee: bool,
+    /// メッセージを時間をかけて表示するエフェクト
+    message_effect: Option<MessageEffect>,
+    /// メッセージを複数行のブロック文字で描くフォント (大きいハートのみ)
+    font: Option<Font>,
+    /// メッセージの文字色 (Noneはハートに合わせる)
+    message_color: Option<HeartColor>,
+    /// メッセージの背景色 (Noneはターミナルの背景のまま)
+    message_bg: Option<HeartColor>,
+    /// メッセージの装飾 (太字・斜体・下線・点滅)
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize::attributes::serialize")
+    )]
+    message_attributes: Attributes,
+    /// ハートの色に対して読みやすい黒か白をメッセージの文字色にする
+    auto_contrast: bool,
+    /// ハートを "v" の文字ではなく色で塗りつぶしたセルで描く
+    filled: bool,
+    /// 描く色を置き換えるパレット (Noneはターミナルの7色のまま)
+    palette: Option<Palette>,
+}
+
+impl HeartConfig {
+    /// ハートの中のメッセージ
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub fn petite(&self) -> bool {
+        self.petite
+    }
+
+    pub fn color(&self) -> HeartColor {
+        self.color
+    }
+
+    pub fn marquee(&self) -> bool {
+        self.marquee
+    }
+
+    pub fn message_effect(&self) -> Option<MessageEffect> {
+        self.message_effect
+    }
+
+    pub fn font(&self) -> Option<&Font> {
+        self.font.as_ref()
+    }
+
+    pub fn message_color(&self) -> Option<HeartColor> {
+        self.message_color
+    }
+
+    pub fn message_bg(&self) -> Option<HeartColor> {
+        self.message_bg
+    }
+
+    pub fn message_attributes(&self) -> Attributes {
+        self.message_attributes
+    }
+
+    pub fn auto_contrast(&self) -> bool {
+        self.auto_contrast
+    }
+
+    pub fn filled(&self) -> bool {
+        self.filled
+    }
+
+    pub fn palette(&self) -> Option<Palette> {
+        self.palette
+    }
+
+    /// メッセージを差し替える (ビルダーと同じくバリデーションし、長すぎる場合は元のまま)
+    pub fn set_message(&mut self, message: Option<&str>) -> Result<(), config::ConfigError> {
+        self.message = message.map(config::check_message).transpose()?;
+        Ok(())
+    }
+
+    pub fn set_petite(&mut self, petite: bool) {
+        self.petite = petite;
+    }
+
+    pub fn set_color(&mut self, color: HeartColor) {
+        self.color = color;
+    }
+
+    pub fn set_message_effect(&mut self, effect: Option<MessageEffect>) {
+        self.message_effect = effect;
+    }
 }
 
 /// 入力をサニタイズし、制御文字やエスケープシーケンスを除去する
@@ -36,18 +168,80 @@ pub fn sanitize_input(input: &str) -> String {
         .collect()
 }
 
+/// メッセージの最大長 (バイト数)
+pub const MAX_MESSAGE_LENGTH: usize = 100;
+
 /// メッセージ入力のバリデーションとサニタイズ
-pub fn validate_message(s: &str) -> Result<String, String> {
-    const MAX_MESSAGE_LENGTH: usize = 100;
+pub fn validate_message(s: &str) -> Result<String> {
+    Ok(config::check_message(s)?)
+}
 
-    if s.len() > MAX_MESSAGE_LENGTH {
-        return Err(format!(
-            "Message too long (max {} characters)",
-            MAX_MESSAGE_LENGTH
-        ));
+/// `parse_color` が受け付ける色名の一覧
+pub const COLOR_NAMES: [&str; 7] = ["red", "green", "blue", "yellow", "magenta", "cyan", "white"];
+
+/// ハートとメッセージに使える色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum HeartColor {
+    Red,
+    Green,
+    Blue,
+    Yellow,
+    Magenta,
+    Cyan,
+    #[default]
+    White,
+}
+
+impl HeartColor {
+    /// `COLOR_NAMES` と同じ並びの全ての色
+    pub const ALL: [HeartColor; 7] = [
+        HeartColor::Red,
+        HeartColor::Green,
+        HeartColor::Blue,
+        HeartColor::Yellow,
+        HeartColor::Magenta,
+        HeartColor::Cyan,
+        HeartColor::White,
+    ];
+
+    /// 色名 (`COLOR_NAMES` の1つ)
+    pub fn name(self) -> &'static str {
+        COLOR_NAMES[self as usize]
     }
 
-    Ok(sanitize_input(s))
+    /// 一覧で次の色 (末尾の次は先頭)
+    pub fn next(self) -> HeartColor {
+        HeartColor::ALL[(self as usize + 1) % HeartColor::ALL.len()]
+    }
+
+    pub fn to_color(self) -> Color {
+        parse_color(self.name())
+    }
+}
+
+impl FromStr for HeartColor {
+    type Err = config::ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HeartColor::ALL
+            .into_iter()
+            .find(|color| color.name() == s)
+            .ok_or_else(|| config::ConfigError::UnknownColor(s.to_string()))
+    }
+}
+
+impl fmt::Display for HeartColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl From<HeartColor> for Color {
+    fn from(color: HeartColor) -> Color {
+        color.to_color()
+    }
 }
 
 /// 色名文字列を対応するColorに変換する
@@ -98,27 +292,360 @@ pub fn heart_sizes(config: &HeartConfig) -> (i32, i32) {
     (HEART_SIZE_L, HEART_SIZE_L / 2)
 }
 
+/// メッセージを表示する行
+pub fn message_row(config: &HeartConfig) -> i32 {
+    let (_, half_size) = heart_sizes(config);
+    half_size - 1
+}
+
+/// メッセージ行でハートの内側にあたる文字位置の範囲 (縁の1セルぶんは除く)
+pub fn message_span(config: &HeartConfig) -> std::ops::Range<usize> {
+    let (heart_size, _) = heart_sizes(config);
+    let y = message_row(config);
+
+    let cells: Vec<i32> = (0..=heart_size)
+        .filter(|&x| is_in_love(x, y, config))
+        .collect();
+    match (cells.first(), cells.last()) {
+        (Some(&first), Some(&last)) if last - first >= 2 => {
+            ((first + 1) as usize * 2)..(last as usize * 2)
+        }
+        _ => 0..0,
+    }
+}
+
+/// メッセージが前後の空白込みでハートの内側に収まるか
+pub fn message_fits(config: &HeartConfig) -> bool {
+    match config.message {
+        Some(ref message) => message.len() + 2 <= message_span(config).len(),
+        None => true,
+    }
+}
+
+/// メッセージをブロック文字で描くか (小さいハートには収まらないため、大きいハートのみ)
+pub fn uses_big_text(config: &HeartConfig) -> bool {
+    config.font.is_some() && config.message.is_some() && !config.petite
+}
+
+/// 行内の1文字が何を表すか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyph {
+    /// ハートの外側
+    Blank,
+    /// ハートの内側
+    Heart,
+    /// メッセージの前後やマーキーの切れ目に置く空白
+    MessagePad,
+    /// メッセージのi文字目
+    MessageChar(usize),
+    /// ブロック文字の1画素 (画素の文字と、メッセージの何文字目か)
+    BigText { ch: char, index: usize },
+}
+
+/// 色つきの1文字
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Color,
+    /// 背景色 (Noneはターミナルの背景のまま)
+    pub bg: Option<Color>,
+    #[cfg_attr(feature = "serde", serde(with = "serialize::attributes"))]
+    pub attributes: Attributes,
+}
+
+impl Cell {
+    /// 背景色も装飾もない1文字
+    pub fn new(ch: char, fg: Color) -> Self {
+        Cell {
+            ch,
+            fg,
+            bg: None,
+            attributes: Attributes::default(),
+        }
+    }
+}
+
+/// メッセージの文字の色と装飾
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageStyle {
+    pub fg: Color,
+    pub bg: Option<Color>,
+    pub attributes: Attributes,
+}
+
+impl MessageStyle {
+    fn cell(&self, ch: char, fg: Color) -> Cell {
+        Cell {
+            ch,
+            fg,
+            bg: self.bg,
+            attributes: self.attributes,
+        }
+    }
+}
+
+/// メッセージの色と装飾を決める
+///
+/// 文字色の指定がなければ、通常のメッセージはハートの色、ブロック文字はハートの補色にする。
+/// 自動コントラストでは、背景 (指定がなければハートの色) に対して読みやすい黒か白を選ぶ。
+/// 塗りつぶしたハートでは、形が欠けないよう常に自動コントラストで描く。
+/// パレットの指定があれば、色を決めた後でパレットの色に置き換える。
+pub fn message_style(config: &HeartConfig) -> MessageStyle {
+    let heart = config.color.to_color();
+    let auto_contrast = config.auto_contrast || config.filled;
+    let bg = match config.message_bg {
+        Some(bg) => Some(bg.to_color()),
+        None => auto_contrast.then_some(heart),
+    };
+    let fg = match config.message_color {
+        Some(fg) => fg.to_color(),
+        None if auto_contrast => effects::contrast(bg.unwrap_or(heart)),
+        None if uses_big_text(config) => effects::complement(heart),
+        None => heart,
+    };
+
+    MessageStyle {
+        fg: palette::paint(config.palette, fg),
+        bg: bg.map(|bg| palette::paint(config.palette, bg)),
+        attributes: config.message_attributes,
+    }
+}
+
+/// アニメーション中のメッセージの状態
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageState {
+    /// マーキー表示で流れた文字数
+    pub marquee_offset: usize,
+    /// メッセージ行が見えてからの経過時間 (エフェクトの進み具合)
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "elapsed_ms", with = "serialize::millis")
+    )]
+    pub elapsed: Duration,
+}
+
+/// ハートのy行目を文字列として返す (左端が原点、メッセージ行にはメッセージを含む)
+pub fn love_row(y: i32, config: &HeartConfig) -> String {
+    love_row_scrolled(y, config, 0)
+}
+
+/// マーキー表示のメッセージを `offset` 文字だけ流した状態で、ハートのy行目を返す
+pub fn love_row_scrolled(y: i32, config: &HeartConfig, offset: usize) -> String {
+    let message: Vec<char> = config.message.as_deref().unwrap_or("").chars().collect();
+
+    love_glyphs(y, config, offset)
+        .into_iter()
+        .map(|glyph| match glyph {
+            Glyph::Blank | Glyph::MessagePad => ' ',
+            Glyph::Heart => 'v',
+            Glyph::MessageChar(i) => message[i],
+            Glyph::BigText { ch, .. } => ch,
+        })
+        .collect()
+}
+
+/// パレットを反映したハートの色
+pub fn heart_color(config: &HeartConfig) -> Color {
+    palette::paint(config.palette, config.color.to_color())
+}
+
+/// ハートの内側の1文字 (塗りつぶす場合は背景色で描き、文字は見せない)
+pub fn heart_cell(config: &HeartConfig, ch: char) -> Cell {
+    let color = heart_color(config);
+    if config.filled {
+        Cell {
+            bg: Some(color),
+            ..Cell::new(' ', color)
+        }
+    } else {
+        Cell::new(ch, color)
+    }
+}
+
+/// ハートのy行目を、メッセージのエフェクトを反映した色つきの文字で返す
+pub fn love_cells(y: i32, config: &HeartConfig, state: &MessageState) -> Vec<Cell> {
+    glyph_cells(love_glyphs(y, config, state.marquee_offset), config, state)
+}
+
+/// 前後の空白を含むメッセージ1行を、エフェクトを反映した色つきの文字で返す (メッセージがなければ空)
+pub fn message_cells(config: &HeartConfig, state: &MessageState) -> Vec<Cell> {
+    let len = match config.message {
+        Some(ref message) => message.chars().count(),
+        None => return Vec::new(),
+    };
+    let glyphs = std::iter::once(Glyph::MessagePad)
+        .chain((0..len).map(Glyph::MessageChar))
+        .chain(std::iter::once(Glyph::MessagePad));
+    glyph_cells(glyphs, config, state)
+}
+
+fn glyph_cells(
+    glyphs: impl IntoIterator<Item = Glyph>,
+    config: &HeartConfig,
+    state: &MessageState,
+) -> Vec<Cell> {
+    let color = heart_color(config);
+    let style = message_style(config);
+    let message: Vec<char> = config.message.as_deref().unwrap_or("").chars().collect();
+    let reveal = |i| match config.message_effect {
+        Some(effect) => effect.reveal(message.len(), i, state.elapsed),
+        None => Reveal::Shown,
+    };
+
+    glyphs
+        .into_iter()
+        .map(|glyph| match glyph {
+            Glyph::Blank => Cell::new(' ', color),
+            Glyph::Heart => heart_cell(config, 'v'),
+            Glyph::MessagePad => style.cell(' ', style.fg),
+            Glyph::MessageChar(i) => match reveal(i) {
+                Reveal::Hidden => style.cell(' ', style.fg),
+                Reveal::Cursor => style.cell('_', style.fg),
+                Reveal::Sparkle => style.cell('*', style.fg),
+                Reveal::Dimmed(brightness) => {
+                    style.cell(message[i], effects::dim(style.fg, brightness))
+                }
+                Reveal::Shown => style.cell(message[i], style.fg),
+            },
+            // ブロック文字はまだ現れていない画素の所にハートを透かす
+            Glyph::BigText { ch, index } => match reveal(index) {
+                Reveal::Hidden => heart_cell(config, 'v'),
+                Reveal::Cursor => heart_cell(config, ch),
+                Reveal::Sparkle => style.cell('*', style.fg),
+                Reveal::Dimmed(brightness) => style.cell(ch, effects::dim(style.fg, brightness)),
+                Reveal::Shown => style.cell(ch, style.fg),
+            },
+        })
+        .collect()
+}
+
+/// ハートのy行目の各文字が何を表すかを返す
+pub fn love_glyphs(y: i32, config: &HeartConfig, offset: usize) -> Vec<Glyph> {
+    if uses_big_text(config) {
+        return big_text_glyphs(y, config);
+    }
+    if config.marquee && !message_fits(config) && y == message_row(config) {
+        return marquee_glyphs(y, config, offset);
+    }
+
+    let (heart_size, half_size) = heart_sizes(config);
+
+    let message_len = match config.message {
+        Some(ref string) => string.len() + 2,
+        None => 0,
+    };
+    let message_indent = (half_size - (message_len / 4) as i32) - 1;
+
+    let mut row = Vec::new();
+    let mut x = 0;
+
+    loop {
+        let glyph = if is_in_love(x, y, config) {
+            Glyph::Heart
+        } else {
+            Glyph::Blank
+        };
+        row.extend([glyph, glyph]);
+        if y == half_size - 1 && x == message_indent {
+            if let Some(ref string) = config.message {
+                row.push(Glyph::MessagePad);
+                row.extend((0..string.chars().count()).map(Glyph::MessageChar));
+                row.push(Glyph::MessagePad);
+            }
+            x += (message_len / 2) as i32;
+        }
+
+        if x >= heart_size {
+            break;
+        }
+
+        x += 1;
+    }
+
+    row
+}
+
+/// メッセージを流しながら、ハートの形で切り抜いたメッセージ行を返す
+fn marquee_glyphs(y: i32, config: &HeartConfig, offset: usize) -> Vec<Glyph> {
+    let (heart_size, _) = heart_sizes(config);
+    let span = message_span(config);
+
+    let message_len = config.message.as_deref().unwrap_or("").chars().count();
+    let cycle = message_len + MARQUEE_GAP;
+
+    let mut row = Vec::new();
+    for x in 0..=heart_size {
+        for position in (x as usize * 2)..(x as usize * 2 + 2) {
+            let glyph = if !is_in_love(x, y, config) {
+                Glyph::Blank
+            } else if span.contains(&position) {
+                match (position - span.start + offset) % cycle {
+                    i if i < message_len => Glyph::MessageChar(i),
+                    _ => Glyph::MessagePad,
+                }
+            } else {
+                Glyph::Heart
+            };
+            row.push(glyph);
+        }
+    }
+
+    row
+}
+
+/// メッセージをブロック文字に展開し、ハートの中央に重ねてハートの形で切り抜いた行を返す
+fn big_text_glyphs(y: i32, config: &HeartConfig) -> Vec<Glyph> {
+    let (heart_size, _) = heart_sizes(config);
+    let (font, message) = match (&config.font, &config.message) {
+        (Some(font), Some(message)) => (font, message),
+        _ => return Vec::new(),
+    };
+
+    let lines = font.render_indexed(message);
+    let width = lines.iter().map(Vec::len).max().unwrap_or(0) as i32;
+    // ブロック文字の中段がメッセージ行に来るように置く
+    let top = message_row(config) - font.height() as i32 / 2;
+    let left = heart_size + 1 - width / 2;
+    let line = usize::try_from(y - top).ok().and_then(|i| lines.get(i));
+
+    let mut row = Vec::new();
+    for x in 0..=heart_size {
+        for position in (x * 2)..(x * 2 + 2) {
+            let pixel = line
+                .zip(usize::try_from(position - left).ok())
+                .and_then(|(line, i)| line.get(i).copied().flatten());
+            let glyph = match pixel {
+                _ if !is_in_love(x, y, config) => Glyph::Blank,
+                Some((ch, index)) => Glyph::BigText { ch, index },
+                None => Glyph::Heart,
+            };
+            row.push(glyph);
+        }
+    }
+
+    row
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::HeartConfigBuilder;
     use crossterm::style::Color;
     use rstest::rstest;
 
     // テスト用のヘルパー: デフォルトのHeartConfigを生成
     fn default_config() -> HeartConfig {
-        HeartConfig {
-            message: None,
-            petite: false,
-            color: "white".to_string(),
-        }
+        HeartConfig::default()
     }
 
     fn petite_config() -> HeartConfig {
-        HeartConfig {
-            message: None,
-            petite: true,
-            color: "white".to_string(),
-        }
+        HeartConfig::builder().petite(true).build().unwrap()
+    }
+
+    fn message_config(message: &str) -> HeartConfig {
+        HeartConfig::builder().message(message).build().unwrap()
     }
 
     // ================================================================
@@ -226,8 +753,9 @@ mod tests {
             fn 最大長を超えるとエラーを返す(#[case] length: usize) {
                 let input = "a".repeat(length);
                 let result = validate_message(&input);
-                assert!(result.is_err());
-                assert!(result.unwrap_err().contains("Message too long"));
+                let err = result.unwrap_err();
+                assert!(matches!(err, Error::Validation(_)));
+                assert!(err.to_string().contains("Message too long"));
             }
         }
 
@@ -297,6 +825,87 @@ mod tests {
         }
     }
 
+    // ================================================================
+    // HeartConfig::set_message: 組み立てた後のメッセージの差し替えの仕様
+    // ================================================================
+
+    mod describe_set_message {
+        use super::*;
+
+        #[test]
+        fn ビルダーと同じく制御文字を除く() {
+            let mut config = HeartConfig::default();
+            config.set_message(Some("\x1b[2JHi")).unwrap();
+            assert_eq!(config.message(), Some("[2JHi"));
+        }
+
+        #[test]
+        fn 長すぎるメッセージはエラーにして元のまま残す() {
+            let mut config = HeartConfig::builder().message("Hi").build().unwrap();
+            let long = "a".repeat(MAX_MESSAGE_LENGTH + 1);
+            assert!(config.set_message(Some(&long)).is_err());
+            assert_eq!(config.message(), Some("Hi"));
+        }
+
+        #[test]
+        fn noneでメッセージを消す() {
+            let mut config = HeartConfig::builder().message("Hi").build().unwrap();
+            config.set_message(None).unwrap();
+            assert_eq!(config.message(), None);
+        }
+    }
+
+    // ================================================================
+    // HeartColor: 色の仕様
+    // ================================================================
+
+    mod describe_heart_color {
+        use super::*;
+
+        mod 色名から変換する場合 {
+            use super::*;
+
+            #[test]
+            fn 全ての色名を名前と同じ色にする() {
+                for name in COLOR_NAMES {
+                    let color: HeartColor = name.parse().unwrap();
+                    assert_eq!(color.name(), name);
+                    assert_eq!(color.to_string(), name);
+                    assert_eq!(Color::from(color), parse_color(name));
+                }
+            }
+
+            #[test]
+            fn 知らない色名はエラーを返す() {
+                assert_eq!(
+                    "purple".parse::<HeartColor>(),
+                    Err(config::ConfigError::UnknownColor("purple".to_string()))
+                );
+            }
+        }
+
+        mod 次の色の場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::赤から緑(HeartColor::Red, HeartColor::Green)]
+            #[case::シアンから白(HeartColor::Cyan, HeartColor::White)]
+            #[case::白から赤に戻る(HeartColor::White, HeartColor::Red)]
+            fn 次の色を返す(#[case] color: HeartColor, #[case] expected: HeartColor) {
+                assert_eq!(color.next(), expected);
+            }
+
+            #[test]
+            fn 全色を一巡して元に戻る() {
+                let mut color = HeartColor::Red;
+                for _ in 0..HeartColor::ALL.len() {
+                    color = color.next();
+                }
+                assert_eq!(color, HeartColor::Red);
+            }
+        }
+    }
+
     // ================================================================
     // heart_sizes: ハートサイズの仕様
     // ================================================================
@@ -327,6 +936,582 @@ mod tests {
         }
     }
 
+    // ================================================================
+    // love_row: ハート1行分の文字列の仕様
+    // ================================================================
+
+    mod describe_love_row {
+        use super::*;
+
+        mod メッセージなしの場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::通常サイズ(default_config(), 42)]
+            #[case::petiteサイズ(petite_config(), 22)]
+            fn 各セルを2文字幅で並べる(
+                #[case] config: HeartConfig,
+                #[case] width: usize,
+            ) {
+                for y in 0..=heart_sizes(&config).0 {
+                    assert_eq!(love_row(y, &config).len(), width);
+                }
+            }
+
+            #[test]
+            fn ハート内部のセルはvvで描く() {
+                let row = love_row(10, &default_config());
+                assert_eq!(&row[20..22], "vv");
+            }
+
+            #[test]
+            fn ハート外部の行は空白のみになる() {
+                assert!(love_row(20, &default_config()).trim().is_empty());
+            }
+        }
+
+        mod メッセージありの場合 {
+            use super::*;
+
+            #[test]
+            fn 中央の行にメッセージを埋め込む() {
+                let config = message_config("Love");
+                assert!(love_row(9, &config).contains(" Love "));
+            }
+
+            #[test]
+            fn 他の行にはメッセージを含めない() {
+                let config = message_config("Love");
+                assert!(!love_row(10, &config).contains("Love"));
+            }
+        }
+    }
+
+    // ================================================================
+    // love_row_scrolled: マーキー表示の仕様
+    // ================================================================
+
+    mod describe_love_row_scrolled {
+        use super::*;
+
+        fn marquee_config(message: &str, petite: bool) -> HeartConfig {
+            HeartConfig::builder()
+                .message(message)
+                .petite(petite)
+                .marquee(true)
+                .build()
+                .unwrap()
+        }
+
+        const LONG_MESSAGE: &str = "Thank you for all the wonderful work this year";
+
+        mod 内側に収まらないメッセージの場合 {
+            use super::*;
+
+            #[test]
+            fn ハートの幅からはみ出さない() {
+                let config = marquee_config(LONG_MESSAGE, false);
+                let plain = love_row(message_row(&config), &default_config());
+                for offset in 0..60 {
+                    let row = love_row_scrolled(message_row(&config), &config, offset);
+                    assert_eq!(row.len(), plain.len());
+                }
+            }
+
+            #[test]
+            fn ハートの外側には文字を置かない() {
+                let config = marquee_config(LONG_MESSAGE, true);
+                let plain = love_row(message_row(&config), &petite_config());
+                let row = love_row_scrolled(message_row(&config), &config, 3);
+                for (a, b) in plain.chars().zip(row.chars()) {
+                    if a == ' ' {
+                        assert_eq!(b, ' ');
+                    }
+                }
+            }
+
+            #[test]
+            fn 内側の範囲にメッセージの先頭を表示する() {
+                let config = marquee_config(LONG_MESSAGE, false);
+                let span = message_span(&config);
+                let row = love_row_scrolled(message_row(&config), &config, 0);
+                assert_eq!(&row[span.clone()], &LONG_MESSAGE[..span.len()]);
+            }
+
+            #[test]
+            fn offsetの分だけ左に流れる() {
+                let config = marquee_config(LONG_MESSAGE, false);
+                let span = message_span(&config);
+                let row = love_row_scrolled(message_row(&config), &config, 5);
+                assert!(row[span].starts_with(&LONG_MESSAGE[5..10]));
+            }
+
+            #[test]
+            fn 末尾の後は間を空けて先頭に戻る() {
+                let config = marquee_config("abcdefghijklmnopqrstuvwxyz0123456789", true);
+                let cycle = 36 + MARQUEE_GAP;
+                let y = message_row(&config);
+                assert_eq!(
+                    love_row_scrolled(y, &config, 1),
+                    love_row_scrolled(y, &config, 1 + cycle)
+                );
+            }
+
+            #[test]
+            fn 縁の1セルはハートのまま残す() {
+                let config = marquee_config(LONG_MESSAGE, false);
+                let row = love_row_scrolled(message_row(&config), &config, 0);
+                assert!(row.trim().starts_with("vv"));
+                assert!(row.trim().ends_with("vv"));
+            }
+        }
+
+        mod 内側に収まるメッセージの場合 {
+            use super::*;
+
+            #[test]
+            fn 流さずに通常どおり表示する() {
+                let config = marquee_config("Love", false);
+                let y = message_row(&config);
+                assert_eq!(love_row_scrolled(y, &config, 7), love_row(y, &config));
+                assert!(love_row(y, &config).contains(" Love "));
+            }
+        }
+
+        mod マーキーを指定していない場合 {
+            use super::*;
+
+            #[test]
+            fn 長いメッセージも流さない() {
+                let config = message_config(LONG_MESSAGE);
+                let y = message_row(&config);
+                assert_eq!(love_row_scrolled(y, &config, 7), love_row(y, &config));
+            }
+        }
+    }
+
+    // ================================================================
+    // love_cells: 色つきの行の仕様
+    // ================================================================
+
+    mod describe_love_cells {
+        use super::*;
+
+        fn effect_config(effect: Option<MessageEffect>) -> HeartConfig {
+            let mut config = HeartConfig::builder()
+                .message("Love")
+                .color(HeartColor::Red)
+                .build()
+                .unwrap();
+            config.set_message_effect(effect);
+            config
+        }
+
+        fn text(cells: &[Cell]) -> String {
+            cells.iter().map(|cell| cell.ch).collect()
+        }
+
+        fn at(elapsed: u64) -> MessageState {
+            MessageState {
+                marquee_offset: 0,
+                elapsed: Duration::from_millis(elapsed),
+            }
+        }
+
+        mod エフェクトなしの場合 {
+            use super::*;
+
+            #[test]
+            fn love_rowと同じ文字をハートの色で返す() {
+                let config = effect_config(None);
+                for y in 0..=HEART_SIZE_L {
+                    let cells = love_cells(y, &config, &at(0));
+                    assert_eq!(text(&cells), love_row(y, &config));
+                    assert!(cells.iter().all(|cell| cell.fg == Color::Red));
+                }
+            }
+        }
+
+        mod タイプライターの場合 {
+            use super::*;
+
+            #[test]
+            fn 開始直後はカーソルだけを表示する() {
+                let config = effect_config(Some(MessageEffect::Typewriter));
+                let row = text(&love_cells(message_row(&config), &config, &at(0)));
+                assert!(row.contains(" _    "));
+                assert!(!row.contains('L'));
+            }
+
+            #[test]
+            fn 所要時間が過ぎたらメッセージ全体を表示する() {
+                let config = effect_config(Some(MessageEffect::Typewriter));
+                let elapsed = MessageEffect::Typewriter.duration(4).as_millis() as u64;
+                let row = text(&love_cells(message_row(&config), &config, &at(elapsed)));
+                assert!(row.contains(" Love "));
+            }
+        }
+
+        mod フェードの場合 {
+            use super::*;
+
+            #[test]
+            fn メッセージの文字だけを暗い色で表示する() {
+                let config = effect_config(Some(MessageEffect::Fade));
+                let cells = love_cells(message_row(&config), &config, &at(0));
+                let dark = Color::Rgb { r: 0, g: 0, b: 0 };
+                let dimmed: String = cells
+                    .iter()
+                    .filter(|cell| cell.fg == dark)
+                    .map(|cell| cell.ch)
+                    .collect();
+                assert_eq!(dimmed, "Love");
+            }
+        }
+    }
+
+    // ================================================================
+    // message_style: メッセージの色と装飾の仕様
+    // ================================================================
+
+    mod describe_message_style {
+        use super::*;
+        use crossterm::style::Attribute;
+
+        fn styled() -> HeartConfigBuilder {
+            HeartConfig::builder()
+                .message("Love")
+                .color(HeartColor::Red)
+        }
+
+        fn styled_config() -> HeartConfig {
+            styled().build().unwrap()
+        }
+
+        mod 指定がない場合 {
+            use super::*;
+
+            #[test]
+            fn ハートの色で背景も装飾もなし() {
+                let style = message_style(&styled_config());
+                assert_eq!(style.fg, Color::Red);
+                assert_eq!(style.bg, None);
+                assert_eq!(style.attributes, Attributes::default());
+            }
+
+            #[test]
+            fn ブロック文字はハートの補色にする() {
+                let config = styled().font(Font::builtin()).build().unwrap();
+                assert_eq!(message_style(&config).fg, Color::Cyan);
+            }
+        }
+
+        mod 色を指定した場合 {
+            use super::*;
+
+            #[test]
+            fn 文字色と背景色に使う() {
+                let config = styled()
+                    .message_color(HeartColor::Yellow)
+                    .message_bg(HeartColor::Blue)
+                    .build()
+                    .unwrap();
+                let style = message_style(&config);
+                assert_eq!(style.fg, Color::Yellow);
+                assert_eq!(style.bg, Some(Color::Blue));
+            }
+        }
+
+        mod 自動コントラストの場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::暗いハート(HeartColor::Red, Color::White)]
+            #[case::明るいハート(HeartColor::Yellow, Color::Black)]
+            fn ハートの色の背景に黒か白の文字にする(
+                #[case] color: HeartColor,
+                #[case] expected: Color,
+            ) {
+                let config = styled().color(color).auto_contrast(true).build().unwrap();
+                let style = message_style(&config);
+                assert_eq!(style.bg, Some(color.to_color()));
+                assert_eq!(style.fg, expected);
+            }
+
+            #[test]
+            fn 背景色を指定した場合はその色に対して選ぶ() {
+                let config = styled()
+                    .message_bg(HeartColor::White)
+                    .auto_contrast(true)
+                    .build()
+                    .unwrap();
+                assert_eq!(message_style(&config).fg, Color::Black);
+            }
+        }
+
+        mod パレットを指定した場合 {
+            use super::*;
+            use crate::palette::Palette;
+
+            fn palette_styled() -> HeartConfigBuilder {
+                styled().palette(Palette::Deuteranopia)
+            }
+
+            fn palette_config() -> HeartConfig {
+                palette_styled().build().unwrap()
+            }
+
+            #[test]
+            fn ハートとメッセージの色を置き換える() {
+                let config = palette_config();
+                let red = Palette::Deuteranopia.apply(Color::Red);
+                let cells = love_cells(message_row(&config), &config, &MessageState::default());
+                assert!(cells
+                    .iter()
+                    .filter(|cell| cell.ch != ' ')
+                    .all(|cell| cell.fg == red));
+            }
+
+            #[test]
+            fn 補色を決めてから置き換える() {
+                let config = palette_styled().font(Font::builtin()).build().unwrap();
+                assert_eq!(
+                    message_style(&config).fg,
+                    Palette::Deuteranopia.apply(Color::Cyan)
+                );
+            }
+
+            #[test]
+            fn 自動コントラストの黒と白は置き換えた白にする() {
+                let config = palette_styled().auto_contrast(true).build().unwrap();
+                let style = message_style(&config);
+                assert_eq!(style.bg, Some(Palette::Deuteranopia.apply(Color::Red)));
+                assert_eq!(style.fg, Palette::Deuteranopia.apply(Color::White));
+            }
+        }
+
+        mod love_cellsに反映した場合 {
+            use super::*;
+
+            #[test]
+            fn メッセージと前後の空白だけに色と装飾をつける() {
+                let config = styled()
+                    .message_color(HeartColor::Green)
+                    .message_bg(HeartColor::Blue)
+                    .message_attributes(Attribute::Bold.into())
+                    .build()
+                    .unwrap();
+                let cells = love_cells(message_row(&config), &config, &MessageState::default());
+                let styled: String = cells
+                    .iter()
+                    .filter(|cell| cell.bg == Some(Color::Blue))
+                    .map(|cell| cell.ch)
+                    .collect();
+                assert_eq!(styled, " Love ");
+                assert!(cells
+                    .iter()
+                    .filter(|cell| cell.bg.is_none())
+                    .all(|cell| cell.fg == Color::Red && cell.attributes.is_empty()));
+                assert!(cells
+                    .iter()
+                    .filter(|cell| cell.bg.is_some())
+                    .all(|cell| cell.fg == Color::Green && cell.attributes.has(Attribute::Bold)));
+            }
+        }
+    }
+
+    // ================================================================
+    // love_cells: 塗りつぶしたハートの仕様
+    // ================================================================
+
+    mod describe_filled {
+        use super::*;
+
+        fn filled(message: Option<&str>) -> HeartConfigBuilder {
+            let builder = HeartConfig::builder()
+                .color(HeartColor::Yellow)
+                .filled(true);
+            match message {
+                Some(message) => builder.message(message),
+                None => builder,
+            }
+        }
+
+        fn filled_config(message: Option<&str>) -> HeartConfig {
+            filled(message).build().unwrap()
+        }
+
+        #[test]
+        fn ハートの内側を背景色で塗りつぶす() {
+            let config = filled_config(None);
+            for y in 0..=HEART_SIZE_L {
+                let cells = love_cells(y, &config, &MessageState::default());
+                for (cell, ch) in cells.iter().zip(love_row(y, &default_config()).chars()) {
+                    assert_eq!(cell.ch, ' ');
+                    let expected = (ch == 'v').then_some(Color::Yellow);
+                    assert_eq!(cell.bg, expected);
+                }
+            }
+        }
+
+        #[test]
+        fn メッセージはハートの色の上に読みやすい色で描く() {
+            let config = filled_config(Some("Love"));
+            let cells = love_cells(message_row(&config), &config, &MessageState::default());
+            let text: String = cells
+                .iter()
+                .filter(|cell| cell.fg == Color::Black)
+                .map(|cell| cell.ch)
+                .collect();
+            assert_eq!(text, " Love ");
+            assert!(cells
+                .iter()
+                .filter(|cell| cell.fg == Color::Black)
+                .all(|cell| cell.bg == Some(Color::Yellow)));
+        }
+
+        #[test]
+        fn ブロック文字の画素以外は塗りつぶす() {
+            let config = filled(Some("Hi")).font(Font::builtin()).build().unwrap();
+            let cells = love_cells(message_row(&config), &config, &MessageState::default());
+            assert!(cells.iter().all(|cell| cell.ch == ' ' || cell.ch == '#'));
+            assert!(cells
+                .iter()
+                .filter(|cell| cell.ch == '#')
+                .all(|cell| cell.fg == Color::Black && cell.bg == Some(Color::Yellow)));
+        }
+    }
+
+    // ================================================================
+    // love_glyphs: ブロック文字のメッセージの仕様
+    // ================================================================
+
+    mod describe_big_text {
+        use super::*;
+
+        fn big_text(message: &str) -> HeartConfigBuilder {
+            HeartConfig::builder()
+                .message(message)
+                .color(HeartColor::Red)
+                .font(Font::builtin())
+        }
+
+        fn big_text_config(message: &str) -> HeartConfig {
+            big_text(message).build().unwrap()
+        }
+
+        fn pixel_rows(config: &HeartConfig) -> Vec<i32> {
+            (0..=HEART_SIZE_L)
+                .filter(|&y| {
+                    love_glyphs(y, config, 0)
+                        .iter()
+                        .any(|glyph| matches!(glyph, Glyph::BigText { .. }))
+                })
+                .collect()
+        }
+
+        mod 大きいハートの場合 {
+            use super::*;
+
+            #[test]
+            fn フォントの高さぶんの行にまたがって描く() {
+                let config = big_text_config("Hi");
+                assert_eq!(pixel_rows(&config), vec![7, 8, 9, 10, 11]);
+            }
+
+            #[test]
+            fn 画素のない部分はハートを透かして描く() {
+                let config = big_text_config("Hi");
+                assert!(love_row(message_row(&config), &config).contains("v###vv#vv"));
+            }
+
+            #[test]
+            fn 行の長さは通常のハートと変わらない() {
+                let config = big_text_config("Love");
+                for y in 0..=HEART_SIZE_L {
+                    assert_eq!(
+                        love_glyphs(y, &config, 0).len(),
+                        love_glyphs(y, &default_config(), 0).len()
+                    );
+                }
+            }
+
+            #[test]
+            fn ハートの外側には描かない() {
+                let config = big_text_config("I love you so much");
+                for y in 0..=HEART_SIZE_L {
+                    let outside = love_glyphs(y, &default_config(), 0);
+                    let glyphs = love_glyphs(y, &config, 0);
+                    for (plain, glyph) in outside.iter().zip(&glyphs) {
+                        if *plain == Glyph::Blank {
+                            assert_eq!(*glyph, Glyph::Blank);
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn ハートと対照的な色で描く() {
+                let config = big_text_config("Hi");
+                let cells = love_cells(message_row(&config), &config, &MessageState::default());
+                let pixels: Vec<_> = cells.iter().filter(|cell| cell.ch == '#').collect();
+                assert!(!pixels.is_empty());
+                assert!(pixels.iter().all(|cell| cell.fg == Color::Cyan));
+            }
+
+            #[test]
+            fn エフェクトは元の文字ごとに進む() {
+                let config = big_text("Hi")
+                    .message_effect(MessageEffect::Typewriter)
+                    .build()
+                    .unwrap();
+                let state = MessageState {
+                    marquee_offset: 0,
+                    elapsed: Duration::from_millis(100),
+                };
+                let row = love_cells(message_row(&config), &config, &state);
+                let shown: String = row
+                    .iter()
+                    .filter(|cell| cell.fg == Color::Cyan)
+                    .map(|cell| cell.ch)
+                    .collect();
+                assert_eq!(shown, "###");
+            }
+        }
+
+        mod 小さいハートの場合 {
+            use super::*;
+
+            #[test]
+            fn 通常の1行のメッセージで描く() {
+                let config = big_text("Hi").petite(true).build().unwrap();
+                let plain = HeartConfig::builder()
+                    .message("Hi")
+                    .petite(true)
+                    .build()
+                    .unwrap();
+                for y in 0..=HEART_SIZE_S {
+                    assert_eq!(love_row(y, &config), love_row(y, &plain));
+                }
+            }
+        }
+
+        mod メッセージがない場合 {
+            use super::*;
+
+            #[test]
+            fn 通常のハートを描く() {
+                let config = HeartConfig::builder()
+                    .font(Font::builtin())
+                    .build()
+                    .unwrap();
+                for y in 0..=HEART_SIZE_L {
+                    assert_eq!(love_row(y, &config), love_row(y, &default_config()));
+                }
+            }
+        }
+    }
+
     // ================================================================
     // is_in_love: ハート形状判定の仕様
     // ================================================================
@@ -1,116 +1,1569 @@
-use clap::Parser;
+use clap::builder::{
+    FalseyValueParser, PossibleValue, RangedU64ValueParser, StringValueParser, TypedValueParser,
+};
+use clap::{ArgGroup, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+#[cfg(feature = "serde")]
+use crossterm::terminal::{Clear, ClearType};
 use crossterm::{
     cursor::{Hide, MoveTo, RestorePosition, SavePosition, Show},
-    execute,
-    style::{ResetColor, SetForegroundColor},
-    terminal::{size, EnterAlternateScreen, LeaveAlternateScreen},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
+    execute, queue,
+    style::{
+        Attribute, Attributes, Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor,
+    },
+    terminal::{
+        disable_raw_mode, enable_raw_mode, size, EnterAlternateScreen, LeaveAlternateScreen,
+    },
 };
-use std::io::stdout;
+use std::env;
+use std::ffi::OsStr;
+use std::fs::File;
 use std::io::Result;
-use std::sync::atomic::AtomicBool;
-use std::sync::{atomic, Arc};
+use std::io::{stdin, stdout, BufWriter, IsTerminal, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
-use tolove_ru::{heart_sizes, is_in_love, parse_color, HeartConfig, ABOUT_MESSAGE};
+use std::time::{Duration, Instant};
+use tolove_ru::accessibility::{self, REDUCED_MOTION_ENV};
+use tolove_ru::animation::{Animation, Frame, DEFAULT_MARQUEE_SPEED, FADE_TICKS};
+use tolove_ru::bigtext::Font;
+use tolove_ru::config::HeartConfigBuilder;
+#[cfg(unix)]
+use tolove_ru::control;
+use tolove_ru::control::ControlCommand;
+use tolove_ru::controls::{self, Action, HELP_OVERLAY};
+use tolove_ru::effects::MessageEffect;
+use tolove_ru::floating::{FloatingHeart, Motion};
+#[cfg(feature = "serde")]
+use tolove_ru::http::HttpServer;
+use tolove_ru::i18n::Locale;
+use tolove_ru::letter::{self, Letter, LetterServer, DEFAULT_LETTER_PORT};
+use tolove_ru::palette::{self, Palette, PALETTE_NAMES};
+use tolove_ru::playlist::{self, Playlist};
+use tolove_ru::screensaver::{Screensaver, SCREENSAVER_MESSAGES, SCREENSAVER_TICK_MS};
+#[cfg(unix)]
+use tolove_ru::send;
+use tolove_ru::server::Limits;
+use tolove_ru::telnet::{TelnetServer, TCP_LIMITS};
+use tolove_ru::{
+    heart_sizes, message_row, validate_message, Error, HeartColor, HeartConfig, MessageState,
+    Reason, ABOUT_MESSAGE, COLOR_NAMES,
+};
+
+// クリックで同時に出せるハートの上限
+const MAX_SPAWNED_HEARTS: usize = 32;
+
+// ターミナルの広さがわからない場合に使う広さ (列数, 行数)
+const DEFAULT_SCREEN_SIZE: (u16, u16) = (80, 24);
+
+// メッセージのエフェクト中に画面を描き直す間隔
+const EFFECT_FRAME: Duration = Duration::from_millis(40);
+
+// 流し終わるまで待たせておける、届いたハートの数
+const WAITING_LETTERS: usize = 8;
+
+// 反映を待たせておける `love ctl` の操作の数
+const WAITING_COMMANDS: usize = 16;
+
+// --daemon で `love ctl` の操作を確かめる間隔
+const CONTROL_POLL: Duration = Duration::from_millis(100);
+
+// `love ctl burst` で一度に浮かべるハートの数
+const BURST_HEARTS: usize = 8;
 
 #[derive(Parser, Clone)]
 #[clap(author, version)]
-#[command(about = ABOUT_MESSAGE)]
+#[command(name = "love", about = ABOUT_MESSAGE, args_conflicts_with_subcommands = true)]
 struct Options {
-    #[clap(short, long, value_parser = tolove_ru::validate_message)]
+    // サブコマンドなしの `love --message X` は `love play --message X` と同じ
+    #[command(flatten)]
+    play: PlayArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// `love play` (サブコマンドなしの `love`) の引数
+#[derive(Args, Clone)]
+#[command(group(
+    ArgGroup::new("messages_input").args(["messages_file", "messages_from_stdin"])
+))]
+struct PlayArgs {
+    #[command(flatten)]
+    style: StyleArgs,
+
+    /// Float varied hearts endlessly until any key or mouse input
+    #[clap(long)]
+    screensaver: bool,
+
+    /// Read messages (one per line) from a file and show one per heart
+    #[clap(long, value_name = "PATH", conflicts_with = "message")]
+    messages_file: Option<PathBuf>,
+
+    /// Read messages (one per line) from standard input and show one per heart
+    #[clap(long, conflicts_with = "message")]
+    messages_from_stdin: bool,
+
+    /// Show the messages in random order
+    #[clap(long)]
+    shuffle: bool,
+
+    /// Keep one heart looping and rotate its message every SECONDS
+    #[clap(
+        long,
+        value_name = "SECONDS",
+        requires = "messages_input",
+        value_parser = clap::value_parser!(u64).range(1..=3600)
+    )]
+    message_duration: Option<u64>,
+
+    /// Fill the whole screen with a background color
+    #[clap(long, value_name = "COLOR", value_parser = ColorParser)]
+    background: Option<HeartColor>,
+
+    /// Print the effective configuration as json or toml instead of playing
+    #[clap(
+        long,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        default_missing_value = "json"
+    )]
+    dump_config: Option<ConfigFormat>,
+
+    /// Where to send the frames: draw them in the terminal, or write one JSON object per line
+    #[clap(
+        long,
+        value_name = "FORMAT",
+        default_value = "terminal",
+        conflicts_with_all = ["screensaver", "messages_input"]
+    )]
+    output: OutputFormat,
+
+    /// Keep the heart looping and accept `love ctl` commands on a control socket (Unix only)
+    #[clap(long, conflicts_with_all = ["screensaver", "dump_config", "output"])]
+    daemon: bool,
+
+    /// Control socket for --daemon (defaults to $XDG_RUNTIME_DIR/tolove-ru.sock)
+    #[clap(long, value_name = "PATH", requires = "daemon")]
+    control_socket: Option<PathBuf>,
+
+    /// Keep the heart still and fade it in and out instead of floating it up
+    #[clap(long, env = REDUCED_MOTION_ENV, value_parser = FalseyValueParser::new())]
+    reduced_motion: bool,
+
+    /// Print a plain-text description of the heart to stderr before playing
+    #[clap(long, conflicts_with_all = ["screensaver", "messages_input"])]
+    describe: bool,
+}
+
+/// ハートを描くサブコマンドで共通の、ハートの見た目と動き
+#[derive(Args, Clone)]
+struct StyleArgs {
+    #[command(flatten)]
+    heart: HeartArgs,
+
+    /// Scroll messages that do not fit inside the heart
+    #[clap(long)]
+    marquee: bool,
+
+    /// Marquee scrolling speed in characters per second
+    #[clap(
+        long,
+        value_name = "CHARS_PER_SEC",
+        default_value_t = DEFAULT_MARQUEE_SPEED,
+        value_parser = clap::value_parser!(u64).range(1..=50)
+    )]
+    marquee_speed: u64,
+
+    /// Reveal the message over time (typewriter, fade, sparkle)
+    #[clap(long, value_name = "EFFECT", value_parser = MessageEffect::from_str)]
+    message_effect: Option<MessageEffect>,
+
+    /// Draw the message in a large block font inside the heart
+    #[clap(long)]
+    big_text: bool,
+
+    /// Draw the message in big text with a FIGlet font (.flf)
+    #[clap(long, value_name = "PATH")]
+    font: Option<PathBuf>,
+
+    /// Message text color (defaults to the heart color)
+    #[clap(
+        long,
+        value_name = "COLOR",
+        conflicts_with = "auto_contrast",
+        value_parser = ColorParser
+    )]
+    message_color: Option<HeartColor>,
+
+    /// Message background color
+    #[clap(long, value_name = "COLOR", value_parser = ColorParser)]
+    message_bg: Option<HeartColor>,
+
+    /// Draw the message in bold
+    #[clap(long)]
+    bold: bool,
+
+    /// Draw the message in italics
+    #[clap(long)]
+    italic: bool,
+
+    /// Underline the message
+    #[clap(long)]
+    underline: bool,
+
+    /// Make the message blink
+    #[clap(long)]
+    blink: bool,
+
+    /// Draw the message in black or white on the heart color, whichever reads better
+    #[clap(long)]
+    auto_contrast: bool,
+
+    /// Draw the heart as a solid shape of colored cells instead of "v" glyphs
+    #[clap(long)]
+    filled: bool,
+
+    /// Remap every color (deuteranopia, protanopia, tritanopia, high-contrast; see `love palettes`)
+    #[clap(long, value_name = "PALETTE", value_parser = Palette::from_str)]
+    palette: Option<Palette>,
+}
+
+#[derive(Subcommand, Clone)]
+enum Command {
+    /// Float the heart up this terminal (the default when no command is given)
+    Play(PlayArgs),
+
+    /// Print a still heart to stdout, e.g. for a motd or a commit message
+    Print {
+        /// Print a plain-text description of the heart instead of drawing it
+        #[clap(long)]
+        describe: bool,
+
+        #[command(flatten)]
+        style: StyleArgs,
+    },
+
+    /// Write every frame of the animation to a file or stdout without playing it
+    Export {
+        /// File to write (defaults to stdout)
+        #[clap(value_name = "PATH")]
+        file: Option<PathBuf>,
+
+        /// json writes one frame per line; asciicast can be replayed with `asciinema play`
+        #[clap(long, default_value = "json")]
+        format: ExportFormat,
+
+        /// Width of the frames (defaults to the terminal width, or 80 when not a terminal)
+        #[clap(long, value_parser = clap::value_parser!(u16).range(1..=1000))]
+        width: Option<u16>,
+
+        /// Height of the frames (defaults to the terminal height, or 24 when not a terminal)
+        #[clap(long, value_parser = clap::value_parser!(u16).range(1..=1000))]
+        height: Option<u16>,
+
+        /// Keep the heart still and fade it in and out instead of floating it up
+        #[clap(long, env = REDUCED_MOTION_ENV, value_parser = FalseyValueParser::new())]
+        reduced_motion: bool,
+
+        #[command(flatten)]
+        style: StyleArgs,
+    },
+
+    /// Print the effective configuration as json or toml
+    Config {
+        #[clap(long, default_value = "json")]
+        format: ConfigFormat,
+
+        #[command(flatten)]
+        style: StyleArgs,
+    },
+
+    /// Serve the animation to browsers, e.g. http://localhost:8080/?message=Hi&color=red
+    Serve {
+        /// Port to listen on
+        #[clap(long, default_value_t = 8080)]
+        port: u16,
+
+        /// Address to listen on (use 0.0.0.0 to let other machines on the network connect)
+        #[clap(long, default_value = "127.0.0.1")]
+        bind: IpAddr,
+    },
+
+    /// Serve the animation to terminals over TCP, e.g. `nc localhost 2323` or `telnet localhost 2323`
+    ServeTcp {
+        /// Port to listen on
+        #[clap(long, default_value_t = 2323)]
+        port: u16,
+
+        /// Address to listen on (use 0.0.0.0 to let other machines on the network connect)
+        #[clap(long, default_value = "127.0.0.1")]
+        bind: IpAddr,
+
+        #[command(flatten)]
+        heart: HeartArgs,
+
+        /// Number of terminals served at the same time
+        #[clap(
+            long,
+            value_name = "N",
+            default_value_t = TCP_LIMITS.max_clients,
+            value_parser = RangedU64ValueParser::<usize>::new().range(1..=1024)
+        )]
+        max_clients: usize,
+    },
+
+    /// Send a heart to `love listen` on another host, or to another terminal here like write(1)
+    #[command(group(ArgGroup::new("recipient").args(["host", "tty", "user"]).required(true)))]
+    Send {
+        /// Host running `love listen`
+        host: Option<String>,
+
+        /// Port the listener is waiting on
+        #[clap(long, default_value_t = DEFAULT_LETTER_PORT, conflicts_with_all = ["tty", "user"])]
+        port: u16,
+
+        /// Sign the letter with the shared secret in this file
+        #[clap(long, value_name = "PATH", conflicts_with_all = ["tty", "user"])]
+        secret_file: Option<PathBuf>,
+
+        /// Terminal to send to, e.g. /dev/pts/3 (Unix only)
+        #[clap(long, value_name = "PATH")]
+        tty: Option<PathBuf>,
+
+        /// Send to the user's most recently used terminal that accepts messages (Unix only)
+        #[clap(long, value_name = "NAME")]
+        user: Option<String>,
+
+        #[command(flatten)]
+        heart: HeartArgs,
+    },
+
+    /// Wait for hearts sent with `love send <host>` and play each one as it arrives
+    Listen {
+        /// Port to listen on
+        #[clap(long, default_value_t = DEFAULT_LETTER_PORT)]
+        port: u16,
+
+        /// Address to listen on (use 0.0.0.0 to let other machines on the network send hearts)
+        #[clap(long, default_value = "127.0.0.1")]
+        bind: IpAddr,
+
+        /// Only accept letters signed with the shared secret in this file
+        #[clap(long, value_name = "PATH")]
+        secret_file: Option<PathBuf>,
+    },
+
+    /// Change a running `love --daemon` without restarting it (Unix only)
+    Ctl {
+        /// Control socket of the daemon (defaults to the same place as --daemon)
+        #[clap(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
+
+    /// List the color palettes for --palette with a preview swatch of each
+    Palettes,
+
+    /// Print a shell completion script for bash, zsh, fish, powershell or elvish
+    Completions { shell: Shell },
+
+    /// Print the man page in roff format, e.g. `love man > ~/.local/share/man/man1/love.1`
+    Man,
+}
+
+/// `love ctl` で送る操作
+#[derive(Subcommand, Clone)]
+enum CtlCommand {
+    /// Replace the message inside the heart (an empty message removes it)
+    Message { message: String },
+
+    /// Change the heart color
+    Color {
+        #[clap(value_parser = ColorParser)]
+        color: HeartColor,
+    },
+
+    /// Float a burst of hearts across the screen
+    Burst,
+}
+
+/// サブコマンドで選べるハートの見た目
+#[derive(Args, Clone)]
+struct HeartArgs {
+    /// Message shown inside the heart
+    #[clap(short, long)]
     message: Option<String>,
 
+    /// Heart color
+    #[clap(long, default_value = "white", value_parser = ColorParser)]
+    color: HeartColor,
+
+    /// Draw a smaller heart
     #[clap(long)]
     petite: bool,
+}
+
+impl HeartArgs {
+    fn config(&self) -> tolove_ru::Result<HeartConfig> {
+        Ok(self.builder().build()?)
+    }
 
-    #[clap(long, default_value = "white")]
-    color: String,
+    /// 他の見た目を足してから組み立てられるよう、ビルダーのまま返す
+    fn builder(&self) -> HeartConfigBuilder {
+        let builder = HeartConfig::builder().color(self.color).petite(self.petite);
+        match &self.message {
+            Some(message) => builder.message(message),
+            None => builder,
+        }
+    }
+}
+
+/// 色名の引数を読み込む (補完やヘルプには `COLOR_NAMES` を候補として出す)
+#[derive(Clone)]
+struct ColorParser;
+
+impl TypedValueParser for ColorParser {
+    type Value = HeartColor;
+
+    fn parse_ref(
+        &self,
+        command: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> std::result::Result<HeartColor, clap::Error> {
+        StringValueParser::new()
+            .try_map(|name| HeartColor::from_str(&name))
+            .parse_ref(command, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(COLOR_NAMES.iter().map(PossibleValue::new)))
+    }
 }
 
-impl From<&Options> for HeartConfig {
-    fn from(options: &Options) -> Self {
-        HeartConfig {
-            message: options.message.clone(),
-            petite: options.petite,
-            color: options.color.clone(),
+/// --output の出力先
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Terminal,
+    Json,
+}
+
+/// --dump-config の書き出し形式
+#[derive(Clone, Copy, ValueEnum)]
+enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+/// `love export` の書き出し形式
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Json,
+    Asciicast,
+}
+
+impl PlayArgs {
+    /// --messages-file / --messages-from-stdin で指定されたメッセージを読み込む
+    fn load_messages(&self) -> tolove_ru::Result<Option<Vec<String>>> {
+        if let Some(ref path) = self.messages_file {
+            let file = File::open(path).map_err(|e| open_error(path, e))?;
+            return playlist::read_messages(file).map(Some);
         }
+        if self.messages_from_stdin {
+            return playlist::read_messages(stdin().lock()).map(Some);
+        }
+        Ok(None)
     }
 }
 
-fn main() -> Result<()> {
-    // コマンドライン引数の取り扱い
-    let options = Options::parse();
-    let config = HeartConfig::from(&options);
+impl StyleArgs {
+    /// 指定された見た目をバリデーションして、フォントも読み込んだハートの設定にする
+    fn config(&self) -> tolove_ru::Result<HeartConfig> {
+        let mut builder = self
+            .heart
+            .builder()
+            .marquee(self.marquee)
+            .message_attributes(self.message_attributes())
+            .auto_contrast(self.auto_contrast)
+            .filled(self.filled);
+        if let Some(effect) = self.message_effect {
+            builder = builder.message_effect(effect);
+        }
+        if let Some(font) = self.load_font()? {
+            builder = builder.font(font);
+        }
+        if let Some(color) = self.message_color {
+            builder = builder.message_color(color);
+        }
+        if let Some(color) = self.message_bg {
+            builder = builder.message_bg(color);
+        }
+        if let Some(palette) = self.palette {
+            builder = builder.palette(palette);
+        }
+        Ok(builder.build()?)
+    }
+
+    /// --bold などのフラグをメッセージの装飾にまとめる
+    fn message_attributes(&self) -> Attributes {
+        let mut attributes = Attributes::default();
+        for (enabled, attribute) in [
+            (self.bold, Attribute::Bold),
+            (self.italic, Attribute::Italic),
+            (self.underline, Attribute::Underlined),
+            (self.blink, Attribute::SlowBlink),
+        ] {
+            if enabled {
+                attributes.set(attribute);
+            }
+        }
+        attributes
+    }
 
-    execute!(stdout(), EnterAlternateScreen, Hide, SavePosition)?;
+    /// --font で指定されたフォントを読み込む (--big-text のみなら組み込みフォント)
+    fn load_font(&self) -> tolove_ru::Result<Option<Font>> {
+        if let Some(ref path) = self.font {
+            let file = File::open(path).map_err(|e| open_error(path, e))?;
+            return Font::read_flf(file).map(Some).map_err(|e| {
+                Error::Config(Reason::InFile {
+                    path: path.clone(),
+                    error: Box::new(e),
+                })
+            });
+        }
+        Ok(self.big_text.then(Font::builtin))
+    }
+}
 
-    // Ctrl-Cを受け付けるハンドラの設定
-    let running = Arc::new(AtomicBool::new(true));
-    let state = Arc::clone(&running);
-    ctrlc::set_handler(move || {
-        state.store(false, atomic::Ordering::Relaxed);
+/// 指定されたファイルを開けなかった場合のエラー
+fn open_error(path: &Path, source: std::io::Error) -> Error {
+    Error::Config(Reason::OpenFailed {
+        path: path.to_path_buf(),
+        source,
     })
-    .expect("Setting Ctrl-C handler failed.");
+}
+
+/// 描画先の画面の広さと背景色
+#[derive(Clone, Copy)]
+struct Screen {
+    cols: u16,
+    rows: u16,
+    background: Option<Color>,
+}
+
+impl Screen {
+    fn current(background: Option<Color>) -> Result<Self> {
+        let (cols, rows) = size()?;
+        Ok(Screen {
+            cols,
+            rows,
+            background,
+        })
+    }
+
+    /// 画面と同じ広さの空のフレーム
+    fn frame(&self) -> Frame {
+        Frame::new(self.cols, self.rows, Duration::ZERO)
+    }
+
+    /// フレームを画面全体に描く (背景色のない文字は画面の背景色で描く)
+    fn print(&self, frame: &Frame) -> Result<()> {
+        frame.write_ansi(&mut stdout(), self.background)
+    }
+}
+
+/// rawモードと代替スクリーンを有効にし、Drop時に必ず元に戻す
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        execute!(
+            stdout(),
+            EnterAlternateScreen,
+            Hide,
+            SavePosition,
+            EnableMouseCapture
+        )?;
+        enable_raw_mode()?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(
+            stdout(),
+            DisableMouseCapture,
+            RestorePosition,
+            Show,
+            LeaveAlternateScreen
+        );
+    }
+}
+
+/// 再生中の状態
+struct Player {
+    config: HeartConfig,
+    y: i32,
+    tick: Duration,
+    paused: bool,
+    show_help: bool,
+    spawned: Vec<FloatingHeart>,
+    playlist: Option<Playlist>,
+    // 指定があればハートを繰り返し浮かべ、この間隔でメッセージを入れ替える
+    rotation: Option<Duration>,
+    rotated_at: Instant,
+    // 浮かびきったら同じハートをまた浮かべる (--daemon)
+    looping: bool,
+    // 浮かべる代わりに中央に置いたまま、現れる時と消える時だけフェードする
+    reduced_motion: bool,
+    // マーキー表示とエフェクトは浮かぶ速さとは別の時間軸で進める
+    message: MessageState,
+    marquee_step: Duration,
+    clock: Instant,
+}
+
+impl Player {
+    fn new(config: HeartConfig) -> Self {
+        Player {
+            config,
+            y: 0,
+            tick: Duration::from_millis(controls::DEFAULT_TICK_MS),
+            paused: false,
+            show_help: false,
+            spawned: Vec::new(),
+            playlist: None,
+            rotation: None,
+            rotated_at: Instant::now(),
+            looping: false,
+            reduced_motion: false,
+            message: MessageState::default(),
+            marquee_step: Duration::from_millis(1000 / DEFAULT_MARQUEE_SPEED),
+            clock: Instant::now(),
+        }
+    }
+
+    /// マーキー表示を1秒あたり `speed` 文字の速さで流す
+    fn with_marquee_speed(mut self, speed: u64) -> Self {
+        self.marquee_step = Duration::from_millis(1000 / speed);
+        self
+    }
+
+    /// 浮かびきったハートを最下段から浮かべ直し続ける
+    fn with_loop(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// ハートを動かさずに表示する (クリックや `love ctl burst` のハートも出さない)
+    fn with_reduced_motion(mut self, reduced_motion: bool) -> Self {
+        self.reduced_motion = reduced_motion;
+        self
+    }
+
+    /// プレイリストのメッセージを順に表示する
+    fn with_playlist(mut self, mut playlist: Playlist, rotation: Option<Duration>) -> Self {
+        self.set_message(playlist.next_message());
+        self.playlist = Some(playlist);
+        self.rotation = rotation;
+        self
+    }
+
+    /// 入れ替えの間隔が過ぎていれば、ハート内のメッセージを次に進める
+    fn rotate_message(&mut self, now: Instant) {
+        let Some(rotation) = self.rotation else {
+            return;
+        };
+        if now.duration_since(self.rotated_at) < rotation {
+            return;
+        }
+        if let Some(ref mut playlist) = self.playlist {
+            let message = playlist.next_message();
+            self.set_message(message);
+        }
+        self.rotated_at = now;
+    }
+
+    /// メッセージを差し替え、エフェクトを最初からやり直す
+    fn set_message(&mut self, message: Option<String>) {
+        // プレイリストも `love ctl` もメッセージをバリデーション済みのため失敗しない
+        // (万一失敗しても、元のメッセージのまま続ける)
+        let _ = self.config.set_message(message.as_deref());
+        self.message = MessageState::default();
+    }
+
+    /// メッセージ行が画面に見えている間だけ、エフェクトの時間を進める
+    fn update_clock(&mut self, now: Instant) {
+        let delta = now.duration_since(self.clock);
+        self.clock = now;
+        if !self.paused && (self.reduced_motion || self.y >= message_row(&self.config)) {
+            self.message.elapsed += delta;
+        }
+    }
+
+    /// エフェクトの途中で、tickを待たずに描き直す必要があるか
+    fn is_revealing(&self) -> bool {
+        match (self.config.message_effect(), self.config.message()) {
+            (Some(effect), Some(message)) => self.message.elapsed < effect.duration(message.len()),
+            _ => false,
+        }
+    }
 
-    let (_, rows) = size()?;
-    execute!(stdout(), MoveTo(0, rows))?;
+    /// メインのハートが浮かびきった後、次のハートを最下段から浮かべる (続きがなければfalse)
+    fn next_heart(&mut self) -> bool {
+        if self.rotation.is_none() {
+            match self.playlist {
+                Some(ref mut playlist) if playlist.remaining() > 0 => {
+                    let message = playlist.next_message();
+                    self.set_message(message);
+                }
+                _ if self.looping => {}
+                _ => return false,
+            }
+        }
+        self.y = 0;
+        true
+    }
+
+    /// 操作を反映し、終了する場合はfalseを返す
+    fn apply(&mut self, action: Action) -> bool {
+        match action {
+            Action::TogglePause => self.paused = !self.paused,
+            Action::SpeedUp => self.tick = controls::faster(self.tick),
+            Action::SlowDown => self.tick = controls::slower(self.tick),
+            Action::CycleColor => self.config.set_color(self.config.color().next()),
+            Action::ToggleSize => self.config.set_petite(!self.config.petite()),
+            Action::ToggleHelp => self.show_help = !self.show_help,
+            Action::SpawnHeart { .. } if self.reduced_motion => {}
+            Action::SpawnHeart { column, row } => self.spawn(column as i32, row as i32),
+            Action::Quit => return false,
+        }
+        true
+    }
 
-    let mut y = 0;
-    while running.load(atomic::Ordering::Relaxed) {
-        if draw_love_row_with_message(y, &config)? {
-            break;
+    /// `love ctl` で届いた操作を反映する
+    fn control(&mut self, command: ControlCommand, (cols, rows): (u16, u16)) {
+        match command {
+            ControlCommand::Message(message) => self.set_message(message),
+            ControlCommand::Color(color) => self.config.set_color(color),
+            ControlCommand::Burst if self.reduced_motion => {}
+            ControlCommand::Burst => {
+                // 画面の下半分のあちこちからまとめて浮かべる
+                for _ in 0..BURST_HEARTS {
+                    let column = fastrand::i32(0..cols.max(1) as i32);
+                    let row = fastrand::i32(rows as i32 / 2..rows.max(1) as i32);
+                    self.spawn(column, row);
+                }
+            }
         }
-        thread::sleep(Duration::from_millis(300));
-        y += 1;
     }
 
-    execute!(stdout(), RestorePosition, Show, LeaveAlternateScreen)?;
+    /// クリック位置を中心に、直前のハートの次の色でハートを出す
+    fn spawn(&mut self, column: i32, row: i32) {
+        if self.spawned.len() >= MAX_SPAWNED_HEARTS {
+            return;
+        }
+
+        let last_color = match self.spawned.last() {
+            Some(heart) => heart.config.color(),
+            None => self.config.color(),
+        };
+        let mut builder = HeartConfig::builder()
+            .petite(self.config.petite())
+            .color(last_color.next())
+            .filled(self.config.filled());
+        if let Some(palette) = self.config.palette() {
+            builder = builder.palette(palette);
+        }
+        // メッセージのないハートはバリデーションで失敗しない
+        let Ok(config) = builder.build() else {
+            return;
+        };
+        self.spawned.push(FloatingHeart::centered_at(
+            config,
+            column,
+            row,
+            Motion::Rise,
+        ));
+    }
+
+    /// 全てのハートを1行浮かせ、画面の上端を抜けきったものを片付ける
+    fn advance(&mut self) {
+        self.y += 1;
+        for heart in &mut self.spawned {
+            heart.advance();
+        }
+        self.spawned.retain(|heart| !heart.is_gone());
+    }
+
+    /// メインのハートが画面の上端を抜けきったか
+    ///
+    /// 動きを控える場合も、浮かびきるのと同じtick数だけ表示する。
+    fn is_floated_away(&self, rows: i32) -> bool {
+        let (heart_size, _) = heart_sizes(&self.config);
+        self.y >= rows + heart_size
+    }
+
+    /// 動きを控える場合のメインのハートの明るさ (表示の始めと終わりの `FADE_TICKS` で変える)
+    fn brightness(&self, rows: i32) -> f32 {
+        if !self.reduced_motion {
+            return 1.0;
+        }
+        let (heart_size, _) = heart_sizes(&self.config);
+        let remaining = rows + heart_size - self.y;
+        (self.y.min(remaining) as f32 / FADE_TICKS as f32).clamp(0.0, 1.0)
+    }
+}
+
+fn main() {
+    // ヘルプとエラーは LANG / LC_MESSAGES の言語で表示する
+    let locale = Locale::from_env();
+
+    // コマンドライン引数の取り扱い (サブコマンドがなければ `love play`)
+    let matches = localize(Options::command().about(locale.about()), locale).get_matches();
+    let options = Options::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let command = options.command.unwrap_or(Command::Play(options.play));
+
+    // 端末を元に戻してから、パニックではなく読みやすいエラーとして表示する
+    if let Err(e) = dispatch(&command, locale) {
+        eprintln!("{}: {}", locale.translate("error"), e.localized(locale));
+        process::exit(e.exit_code());
+    }
+}
+
+/// ヘルプの説明と見出しを `locale` の言語にする
+fn localize(mut command: clap::Command, locale: Locale) -> clap::Command {
+    if locale == Locale::En {
+        return command;
+    }
+    // 組み込みの --help / --version と help サブコマンドも訳せるよう、先に組み立てる
+    command.build();
+    localize_built(command, locale)
+}
+
+fn localize_built(mut command: clap::Command, locale: Locale) -> clap::Command {
+    if let Some(about) = command.get_about().map(ToString::to_string) {
+        command = command.about(locale.translate(&about).to_string());
+    }
+
+    let args: Vec<_> = command
+        .get_arguments()
+        .map(|arg| {
+            let help = arg.get_help().map(ToString::to_string);
+            (arg.get_id().clone(), help, arg.is_positional())
+        })
+        .collect();
+    for (id, help, positional) in args {
+        let heading = match positional {
+            true => locale.translate("Arguments"),
+            false => locale.translate("Options"),
+        };
+        command = command.mut_arg(id, |arg| {
+            let arg = arg.help_heading(heading.to_string());
+            match help {
+                Some(help) => arg.help(locale.translate(&help).to_string()),
+                None => arg,
+            }
+        });
+    }
+
+    let subcommands: Vec<_> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+    for name in subcommands {
+        command = command.mut_subcommand(name, |subcommand| localize_built(subcommand, locale));
+    }
+
+    let template = format!(
+        "{{before-help}}{{about-with-newline}}\n{} {{usage}}\n\n{{all-args}}{{after-help}}",
+        locale.translate("Usage:")
+    );
+    command
+        .subcommand_help_heading(locale.translate("Commands").to_string())
+        .help_template(template)
+}
+
+/// サブコマンドごとの処理に振り分ける
+fn dispatch(command: &Command, locale: Locale) -> tolove_ru::Result<()> {
+    match command {
+        Command::Play(args) => play(args, locale),
+        Command::Print { describe, style } => print(style, *describe, locale),
+        Command::Export {
+            file,
+            format,
+            width,
+            height,
+            reduced_motion,
+            style,
+        } => {
+            let (cols, rows) = output_size();
+            let size = (width.unwrap_or(cols), height.unwrap_or(rows));
+            export(file.as_deref(), *format, size, *reduced_motion, style)
+        }
+        Command::Config { format, style } => dump_config(&style.config()?, *format),
+        Command::Serve { port, bind } => serve(*bind, *port, locale),
+        Command::ServeTcp {
+            port,
+            bind,
+            heart,
+            max_clients,
+        } => serve_tcp(*bind, *port, heart.config()?, *max_clients, locale),
+        Command::Send {
+            host: Some(host),
+            port,
+            secret_file,
+            heart,
+            ..
+        } => send_letter(host, *port, secret_file.as_deref(), heart.config()?, locale),
+        Command::Send {
+            tty, user, heart, ..
+        } => send_heart(tty.as_deref(), user.as_deref(), heart.config()?, locale),
+        Command::Listen {
+            port,
+            bind,
+            secret_file,
+        } => listen(*bind, *port, secret_file.as_deref(), locale),
+        Command::Ctl { socket, command } => ctl(socket.as_deref(), command),
+        Command::Palettes => palettes(locale),
+        Command::Completions { shell } => completions(*shell),
+        Command::Man => man(),
+    }
+}
+
+/// 設定と入力を読み込んでハートを浮かべる
+fn play(args: &PlayArgs, locale: Locale) -> tolove_ru::Result<()> {
+    let config = args.style.config()?;
+    if let Some(format) = args.dump_config {
+        return dump_config(&config, format);
+    }
+    // 代替スクリーンに切り替える前に書き、スクリーンリーダーがすぐ読めるようにする
+    if args.describe {
+        eprintln!("{}", accessibility::describe(&config, locale));
+    }
+
+    if args.output == OutputFormat::Json {
+        let (cols, rows) = output_size();
+        let animation = Animation::new(config, cols, rows)
+            .with_marquee_speed(args.style.marquee_speed)
+            .with_reduced_motion(args.reduced_motion);
+        return write_frames(&mut stdout().lock(), animation, ExportFormat::Json);
+    }
+
+    // 標準入力は rawモードに入る前に読み切る
+    let messages = args.load_messages()?;
+    let playlist = messages.map(|messages| Playlist::new(messages, args.shuffle));
+
+    let background = args
+        .background
+        .map(|color| palette::paint(config.palette(), color.into()));
+
+    // ソケットを作れない場合は、画面を切り替える前にエラーにする
+    let (sender, commands) = mpsc::sync_channel(WAITING_COMMANDS);
+    let _control = match args.daemon {
+        true => Some(bind_control_socket(args, sender)?),
+        false => None,
+    };
+
+    let _terminal = TerminalGuard::enter()?;
+    if args.screensaver {
+        run_screensaver(&config, playlist, args, background)?;
+        return Ok(());
+    }
+
+    let player = Player::new(config)
+        .with_marquee_speed(args.style.marquee_speed)
+        .with_loop(args.daemon)
+        .with_reduced_motion(args.reduced_motion);
+    let player = match playlist {
+        Some(playlist) => {
+            let rotation = args.message_duration.map(Duration::from_secs);
+            player.with_playlist(playlist, rotation)
+        }
+        None => player,
+    };
+    run(player, background, args.daemon.then_some(&commands))?;
+    Ok(())
+}
+
+/// ハートの静止画を標準出力に書き出す (ターミナルには色つきで、それ以外には文字だけで)
+///
+/// `describe` なら絵の代わりに、エスケープシーケンスを含まない説明文を書き出す。
+fn print(style: &StyleArgs, describe: bool, locale: Locale) -> tolove_ru::Result<()> {
+    let mut config = style.config()?;
+    if describe {
+        let text = accessibility::describe(&config, locale);
+        return writeln!(stdout(), "{}", text).map_err(Error::Export);
+    }
+    // 静止画では時間が進まないため、エフェクトをかけずにメッセージを見せる
+    config.set_message_effect(None);
+    let frame = Frame::still(&config);
+
+    let mut out = stdout().lock();
+    if out.is_terminal() {
+        frame.write_ansi_lines(&mut out, None)?;
+    } else {
+        for line in frame.text().lines() {
+            writeln!(out, "{}", line.trim_end()).map_err(Error::Export)?;
+        }
+    }
+    out.flush().map_err(Error::Export)
+}
+
+/// アニメーションの全てのフレームをファイル (指定がなければ標準出力) に書き出す
+fn export(
+    file: Option<&Path>,
+    format: ExportFormat,
+    (cols, rows): (u16, u16),
+    reduced_motion: bool,
+    style: &StyleArgs,
+) -> tolove_ru::Result<()> {
+    let animation = Animation::new(style.config()?, cols, rows)
+        .with_marquee_speed(style.marquee_speed)
+        .with_reduced_motion(reduced_motion);
+    match file {
+        Some(path) => {
+            let file = File::create(path).map_err(|e| {
+                Error::Export(std::io::Error::new(
+                    e.kind(),
+                    Reason::CannotCreate {
+                        path: path.to_path_buf(),
+                        source: e,
+                    },
+                ))
+            })?;
+            write_frames(&mut BufWriter::new(file), animation, format)
+        }
+        None => write_frames(&mut stdout().lock(), animation, format),
+    }
+}
+
+/// パレットの一覧を、置き換え先の7色の見本つきで標準出力に書き出す
+///
+/// ターミナルには色の見本を、それ以外には色を `#rrggbb` で書き出す。
+fn palettes(locale: Locale) -> tolove_ru::Result<()> {
+    let mut out = stdout().lock();
+    let is_terminal = out.is_terminal();
+    let width = PALETTE_NAMES
+        .iter()
+        .map(|name| name.len())
+        .max()
+        .unwrap_or(0);
+
+    for palette in Palette::ALL {
+        let mut line = Vec::new();
+        queue!(line, Print(format!("{:width$}  ", palette.name())))?;
+        for color in HeartColor::ALL {
+            let (r, g, b) = palette.rgb(color);
+            if is_terminal {
+                queue!(
+                    line,
+                    SetForegroundColor(Color::Rgb { r, g, b }),
+                    Print("██"),
+                    ResetColor,
+                    Print(' ')
+                )?;
+            } else {
+                queue!(line, Print(format!("#{:02x}{:02x}{:02x} ", r, g, b)))?;
+            }
+        }
+        queue!(
+            line,
+            Print(format!(" {}\n", locale.translate(palette.description())))
+        )?;
+        out.write_all(&line).map_err(Error::Export)?;
+    }
+    out.flush().map_err(Error::Export)
+}
+
+/// シェルの補完スクリプトを標準出力に書き出す
+fn completions(shell: Shell) -> tolove_ru::Result<()> {
+    let mut command = Options::command();
+    clap_complete::generate(shell, &mut command, "love", &mut stdout());
     Ok(())
 }
 
-fn draw_love_row_with_message(y: i32, config: &HeartConfig) -> Result<bool> {
-    let (heart_size, half_size) = heart_sizes(config);
+/// man ページをroff形式で標準出力に書き出す
+fn man() -> tolove_ru::Result<()> {
+    let mut out = stdout().lock();
+    // NAME の節は1行の説明にする (ヘルプのアスキーアートはmanでは崩れる)
+    let command = Options::command().about(env!("CARGO_PKG_DESCRIPTION"));
+    clap_mangen::Man::new(command)
+        .render(&mut out)
+        .map_err(Error::Export)?;
+    out.flush().map_err(Error::Export)
+}
+
+/// `--daemon` の操作を受け付けるソケットを作る
+#[cfg(unix)]
+fn bind_control_socket(
+    args: &PlayArgs,
+    sender: mpsc::SyncSender<ControlCommand>,
+) -> tolove_ru::Result<control::ControlSocket> {
+    let path = match &args.control_socket {
+        Some(path) => path.clone(),
+        None => control::default_socket_path()?,
+    };
+    control::ControlSocket::bind(&path, sender)
+}
+
+#[cfg(not(unix))]
+fn bind_control_socket(_: &PlayArgs, _: mpsc::SyncSender<ControlCommand>) -> tolove_ru::Result<()> {
+    Err(Error::Config(Reason::UnixOnly("--daemon")))
+}
+
+/// 動いている `love --daemon` に操作を送る
+#[cfg(unix)]
+fn ctl(socket: Option<&Path>, command: &CtlCommand) -> tolove_ru::Result<()> {
+    let command = match command {
+        CtlCommand::Message { message } => {
+            let message = validate_message(message)?;
+            ControlCommand::Message((!message.is_empty()).then_some(message))
+        }
+        CtlCommand::Color { color } => ControlCommand::Color(*color),
+        CtlCommand::Burst => ControlCommand::Burst,
+    };
+    let path = match socket {
+        Some(path) => path.to_path_buf(),
+        None => control::default_socket_path()?,
+    };
+    control::send_command(&path, &command)
+}
+
+#[cfg(not(unix))]
+fn ctl(_: Option<&Path>, _: &CtlCommand) -> tolove_ru::Result<()> {
+    Err(Error::Config(Reason::UnixOnly("love ctl")))
+}
+
+/// 待ち受けや送り届けた状況として、標準エラー出力に知らせること
+enum Notice<'a> {
+    /// `scheme://addr/` で配信を始めた
+    Serving {
+        scheme: &'static str,
+        addr: SocketAddr,
+    },
+    /// 別のターミナルにハートを送る
+    Sending(&'a Path),
+    /// `love listen` にハートを届けた
+    Delivered { host: &'a str, port: u16 },
+    /// ハートを待ち受け始めた
+    Waiting(SocketAddr),
+}
 
-    let message = match config.message {
-        Some(ref string) => format!(" {} ", string),
-        None => "".to_string(),
+impl Notice<'_> {
+    /// `locale` の言語で知らせる文言
+    fn localized(&self, locale: Locale) -> String {
+        match (self, locale) {
+            (Notice::Serving { scheme, addr }, Locale::En) => format!(
+                "Serving hearts on {}://{}/ (press Ctrl-C to stop)",
+                scheme, addr
+            ),
+            (Notice::Serving { scheme, addr }, Locale::Ja) => format!(
+                "{}://{}/ でハートを配信しています (Ctrl-C で止めます)",
+                scheme, addr
+            ),
+            (Notice::Sending(path), Locale::En) => {
+                format!("Sending a heart to {}", path.display())
+            }
+            (Notice::Sending(path), Locale::Ja) => {
+                format!("{} にハートを送ります", path.display())
+            }
+            (Notice::Delivered { host, port }, Locale::En) => {
+                format!("Delivered a heart to {}:{}", host, port)
+            }
+            (Notice::Delivered { host, port }, Locale::Ja) => {
+                format!("{}:{} にハートを届けました", host, port)
+            }
+            (Notice::Waiting(addr), Locale::En) => format!(
+                "Waiting for love letters on {} (press Ctrl-C to stop)",
+                addr
+            ),
+            (Notice::Waiting(addr), Locale::Ja) => {
+                format!("{} でハートを待っています (Ctrl-C で止めます)", addr)
+            }
+        }
+    }
+}
+
+/// ブラウザ向けにアニメーションを配信し続ける
+#[cfg(feature = "serde")]
+fn serve(bind: IpAddr, port: u16, locale: Locale) -> tolove_ru::Result<()> {
+    let server = HttpServer::bind((bind, port))?;
+    let notice = Notice::Serving {
+        scheme: "http",
+        addr: server.local_addr()?,
     };
-    let message_indent = (half_size - (message.len() / 4) as i32) - 1;
+    eprintln!("{}", notice.localized(locale));
+    server.run()
+}
 
-    let (cols, rows) = size()?;
-    let rows = rows as i32;
-    let cols = cols as i32;
+#[cfg(not(feature = "serde"))]
+fn serve(_: IpAddr, _: u16, _: Locale) -> tolove_ru::Result<()> {
+    Err(Error::Config(Reason::NeedsSerde("love serve")))
+}
 
-    let mut x = 0;
+/// `nc` や `telnet` で接続したターミナル向けにアニメーションを配信し続ける
+fn serve_tcp(
+    bind: IpAddr,
+    port: u16,
+    config: HeartConfig,
+    max_clients: usize,
+    locale: Locale,
+) -> tolove_ru::Result<()> {
+    let server = TelnetServer::bind((bind, port), config)?.with_limits(Limits {
+        max_clients,
+        ..TCP_LIMITS
+    });
+    let notice = Notice::Serving {
+        scheme: "telnet",
+        addr: server.local_addr()?,
+    };
+    eprintln!("{}", notice.localized(locale));
+    server.run()
+}
+
+/// 同じマシンの別のターミナルにハートを送る
+#[cfg(unix)]
+fn send_heart(
+    tty: Option<&Path>,
+    user: Option<&str>,
+    config: HeartConfig,
+    locale: Locale,
+) -> tolove_ru::Result<()> {
+    let path = match (tty, user) {
+        (Some(tty), _) => tty.to_path_buf(),
+        (None, Some(user)) => send::find_user_tty(user)?,
+        (None, None) => unreachable!("clap requires --tty or --user"),
+    };
+    let mut out = BufWriter::new(send::open_tty(&path)?);
+    let letter = Letter::new(&sender_name(), config);
+
+    eprintln!("{}", Notice::Sending(&path).localized(locale));
+    letter::write_inline(&mut out, &letter)?;
+    Ok(())
+}
 
-    let indent = ((cols / 2) - half_size) - 10;
-    for _ in 0..indent {
-        print!(" ");
+#[cfg(not(unix))]
+fn send_heart(
+    _: Option<&Path>,
+    _: Option<&str>,
+    _: HeartConfig,
+    _: Locale,
+) -> tolove_ru::Result<()> {
+    Err(Error::Config(Reason::UnixOnly("love send --tty/--user")))
+}
+
+/// 別のマシンで待ち受ける `love listen` にハートを届ける
+fn send_letter(
+    host: &str,
+    port: u16,
+    secret_file: Option<&Path>,
+    config: HeartConfig,
+    locale: Locale,
+) -> tolove_ru::Result<()> {
+    let secret = secret_file.map(letter::read_secret).transpose()?;
+    let letter = Letter::new(&sender_name(), config);
+    letter::send_letter((host, port), &letter, secret.as_deref())?;
+    eprintln!("{}", Notice::Delivered { host, port }.localized(locale));
+    Ok(())
+}
+
+/// `love send <host>` で届いたハートを、届いた順に1つずつ流す
+fn listen(
+    bind: IpAddr,
+    port: u16,
+    secret_file: Option<&Path>,
+    locale: Locale,
+) -> tolove_ru::Result<()> {
+    let secret = secret_file.map(letter::read_secret).transpose()?;
+    let mut server = LetterServer::bind((bind, port))?;
+    if let Some(secret) = secret {
+        server = server.with_secret(secret);
+    }
+    eprintln!(
+        "{}",
+        Notice::Waiting(server.local_addr()?).localized(locale)
+    );
+
+    let (sender, letters) = mpsc::sync_channel(WAITING_LETTERS);
+    thread::spawn(move || server.run(sender));
+    for letter in letters {
+        letter::play(&mut stdout().lock(), &letter, output_size(), thread::sleep)?;
+    }
+    Ok(())
+}
+
+/// 送り主として名乗る名前 (実uidのログイン名)
+///
+/// `$USER` を書き換えて別の人になりすませないよう、環境変数は使わない。
+#[cfg(unix)]
+fn sender_name() -> String {
+    send::login_name().unwrap_or_else(|| "someone".to_string())
+}
+
+#[cfg(not(unix))]
+fn sender_name() -> String {
+    env::var("USERNAME").unwrap_or_else(|_| "someone".to_string())
+}
+
+/// 実際に使う設定を標準出力に書き出す
+#[cfg(feature = "serde")]
+fn dump_config(config: &HeartConfig, format: ConfigFormat) -> tolove_ru::Result<()> {
+    let text = match format {
+        ConfigFormat::Json => serde_json::to_string_pretty(config).map_err(std::io::Error::other),
+        ConfigFormat::Toml => toml::to_string(config).map_err(std::io::Error::other),
     }
+    .map_err(Error::Export)?;
+    writeln!(stdout(), "{}", text.trim_end()).map_err(Error::Export)
+}
+
+#[cfg(not(feature = "serde"))]
+fn dump_config(_: &HeartConfig, _: ConfigFormat) -> tolove_ru::Result<()> {
+    Err(Error::Config(Reason::NeedsSerde(
+        "--dump-config / love config",
+    )))
+}
 
-    // 色を設定
-    execute!(stdout(), SetForegroundColor(parse_color(&config.color)))?;
+/// 標準出力に書き出すフレームの広さ
+///
+/// パイプなどターミナル以外に書き出す場合は、どこでも同じフレームになるよう既定の広さにする。
+fn output_size() -> (u16, u16) {
+    if stdout().is_terminal() {
+        size().unwrap_or(DEFAULT_SCREEN_SIZE)
+    } else {
+        DEFAULT_SCREEN_SIZE
+    }
+}
+
+/// ターミナルに描く代わりに、フレームを書き出す
+///
+/// 待たずに書き出し、表示の間隔は json なら各フレームの `delay_ms`、asciicast なら各イベントの時刻に任せる。
+#[cfg(feature = "serde")]
+fn write_frames(
+    out: &mut impl Write,
+    animation: Animation,
+    format: ExportFormat,
+) -> tolove_ru::Result<()> {
+    match format {
+        ExportFormat::Json => {
+            for frame in animation {
+                serde_json::to_writer(&mut *out, &frame).map_err(|e| Error::Export(e.into()))?;
+                writeln!(out).map_err(Error::Export)?;
+            }
+        }
+        ExportFormat::Asciicast => write_asciicast(out, animation).map_err(Error::Export)?,
+    }
+    out.flush().map_err(Error::Export)
+}
+
+#[cfg(not(feature = "serde"))]
+fn write_frames(_: &mut impl Write, _: Animation, _: ExportFormat) -> tolove_ru::Result<()> {
+    Err(Error::Config(Reason::NeedsSerde(
+        "--output json / love export",
+    )))
+}
+
+/// asciinema の asciicast v2 形式 (1行目がヘッダ、以降が `[秒, "o", 出力]` の行) で書き出す
+#[cfg(feature = "serde")]
+fn write_asciicast(out: &mut impl Write, animation: Animation) -> Result<()> {
+    let mut frames = animation.peekable();
+    let (width, height) = frames
+        .peek()
+        .map_or((0, 0), |frame| (frame.width(), frame.height()));
+    writeln!(
+        out,
+        r#"{{"version": 2, "width": {}, "height": {}}}"#,
+        width, height
+    )?;
+
+    // 画面を消してカーソルを隠してから描き始め、最後のフレームを見せ終えたらカーソルを戻す
+    let mut data = Vec::new();
+    queue!(data, Clear(ClearType::All), Hide)?;
+    let mut time = Duration::ZERO;
+    for frame in frames {
+        frame.write_ansi(&mut data, None)?;
+        write_asciicast_event(out, time, &data)?;
+        data.clear();
+        time += frame.delay;
+    }
+    queue!(data, Show)?;
+    write_asciicast_event(out, time, &data)
+}
+
+#[cfg(feature = "serde")]
+fn write_asciicast_event(out: &mut impl Write, time: Duration, data: &[u8]) -> Result<()> {
+    let event = serde_json::json!([time.as_secs_f64(), "o", String::from_utf8_lossy(data)]);
+    writeln!(out, "{}", event)
+}
+
+/// タイマーのtickとキー入力をまとめて処理するイベントループ
+fn run(
+    mut player: Player,
+    background: Option<Color>,
+    control: Option<&mpsc::Receiver<ControlCommand>>,
+) -> Result<()> {
+    let mut next_tick = Instant::now() + player.tick;
+    let mut next_scroll = Instant::now() + player.marquee_step;
 
     loop {
-        print!("{}", if is_in_love(x, y, config) { "vv" } else { "  " });
-        if y == half_size - 1 && x == message_indent {
-            print!("{}", message);
-            x += (message.len() / 2) as i32;
+        player.update_clock(Instant::now());
+        draw_frame(&player, background)?;
+
+        let mut deadline = next_tick;
+        if player.config.marquee() {
+            deadline = deadline.min(next_scroll);
+        }
+        if player.is_revealing() {
+            deadline = deadline.min(Instant::now() + EFFECT_FRAME);
+        }
+        let mut timeout = deadline.saturating_duration_since(Instant::now());
+        if control.is_some() {
+            timeout = timeout.min(CONTROL_POLL);
+        }
+        if event::poll(timeout)? {
+            let action = match event::read()? {
+                Event::Key(key) => controls::action_for_key(&key),
+                Event::Mouse(mouse) => controls::action_for_mouse(&mouse),
+                _ => None,
+            };
+            if let Some(action) = action {
+                if !player.apply(action) {
+                    return Ok(());
+                }
+            }
+            // 入力は即座に画面へ反映し、tickの時刻はそのまま待つ
+            continue;
+        }
+        if let Some(control) = control {
+            let commands: Vec<_> = control.try_iter().collect();
+            if !commands.is_empty() {
+                let screen = size()?;
+                for command in commands {
+                    player.control(command, screen);
+                }
+                continue;
+            }
         }
 
-        if x >= heart_size {
-            break;
+        let now = Instant::now();
+        if now >= next_scroll {
+            if !player.paused {
+                player.message.marquee_offset += 1;
+            }
+            next_scroll = now + player.marquee_step;
+        }
+        if now < next_tick {
+            continue;
         }
 
-        x += 1;
+        if !player.paused {
+            let (_, rows) = size()?;
+            if player.is_floated_away(rows as i32) && !player.next_heart() {
+                // クリックで出したハートが浮かびきるまでは待つ
+                if player.spawned.is_empty() {
+                    return Ok(());
+                }
+            }
+            player.rotate_message(Instant::now());
+            player.advance();
+        }
+        next_tick = Instant::now() + player.tick;
     }
+}
 
-    execute!(stdout(), ResetColor)?;
-    println!();
+/// 何か入力があるまでハートを浮かべ続ける
+fn run_screensaver(
+    config: &HeartConfig,
+    playlist: Option<Playlist>,
+    args: &PlayArgs,
+    background: Option<Color>,
+) -> Result<()> {
+    let playlist = playlist.unwrap_or_else(|| {
+        let messages = match config.message() {
+            Some(message) => vec![message.to_string()],
+            None => SCREENSAVER_MESSAGES.iter().map(|m| m.to_string()).collect(),
+        };
+        Playlist::new(messages, args.shuffle)
+    });
+    let mut saver = Screensaver::new(playlist)
+        .with_config(config.clone())
+        .with_reduced_motion(args.reduced_motion);
+    let tick = Duration::from_millis(SCREENSAVER_TICK_MS);
 
-    if y < rows + heart_size {
-        Ok(false)
-    } else {
-        Ok(true)
+    loop {
+        let screen = Screen::current(background)?;
+        saver.tick(screen.cols, screen.rows);
+
+        // マーキー表示やエフェクトは、ハートごとに浮かび始めてからの時間で進める
+        let mut frame = screen.frame();
+        for heart in saver.hearts() {
+            let state = heart.message_state(tick, args.style.marquee_speed);
+            frame.draw_heart(heart.left(), heart.top(), &heart.config, &state);
+        }
+        screen.print(&frame)?;
+        stdout().flush()?;
+
+        // キー入力とマウス操作 (移動を含む) はどれでも終了の合図とする
+        if event::poll(tick)? {
+            if let Event::Key(_) | Event::Mouse(_) = event::read()? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn draw_frame(player: &Player, background: Option<Color>) -> Result<()> {
+    let screen = Screen::current(background)?;
+    let mut frame = screen.frame();
+
+    // メインのハートは中央寄せで、最新の行が最下段に来る位置から浮かせる
+    // (動きを控える場合は画面の上下の中央に置いたままにする)
+    let (heart_size, half_size) = heart_sizes(&player.config);
+    let left = ((screen.cols as i32 / 2) - half_size) - 10;
+    let top = match player.reduced_motion {
+        true => (screen.rows as i32 - heart_size - 1) / 2,
+        false => screen.rows as i32 - 1 - player.y,
+    };
+    let brightness = player.brightness(screen.rows as i32);
+    if brightness > 0.0 {
+        frame.draw_heart(left, top, &player.config, &player.message);
+        frame.dim(brightness);
+    }
+
+    // クリックで出したハートにはメッセージがないため、状態は既定のままでよい
+    let state = MessageState::default();
+    for heart in &player.spawned {
+        frame.draw_heart(heart.left(), heart.top(), &heart.config, &state);
+    }
+    screen.print(&frame)?;
+
+    if player.show_help {
+        draw_help_overlay(&screen)?;
+    }
+
+    stdout().flush()
+}
+
+fn draw_help_overlay(screen: &Screen) -> Result<()> {
+    let width = HELP_OVERLAY[0].chars().count() as u16;
+    let height = HELP_OVERLAY.len() as u16;
+    let left = screen.cols.saturating_sub(width) / 2;
+    let top = screen.rows.saturating_sub(height) / 2;
+
+    let mut out = stdout();
+    if let Some(background) = screen.background {
+        queue!(out, SetBackgroundColor(background))?;
+    }
+    for (i, line) in HELP_OVERLAY.iter().enumerate() {
+        queue!(out, MoveTo(left, top + i as u16), Print(line))?;
     }
+    queue!(out, ResetColor)
 }
@@ -0,0 +1,228 @@
+//! 色覚の多様性や見やすさに合わせて、描く色を置き換えるパレット
+//!
+//! ハートやメッセージの色は、補色や自動コントラストを決めた後で最後にパレットの色に置き換える。
+
+use crate::config::ConfigError;
+use crate::HeartColor;
+use crossterm::style::Color;
+use std::str::FromStr;
+
+/// `--palette` で指定できる名前の一覧
+pub const PALETTE_NAMES: [&str; 4] = ["deuteranopia", "protanopia", "tritanopia", "high-contrast"];
+
+/// 7色それぞれを置き換える色のパレット
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Palette {
+    /// 緑を感じにくい色覚向け (Okabe-Ito の配色)
+    Deuteranopia,
+    /// 赤を感じにくい色覚向け (赤を明るい橙にする)
+    Protanopia,
+    /// 青と黄を見分けにくい色覚向け (明るさの差で見分ける)
+    Tritanopia,
+    /// 暗い背景の上で見やすい、明るく鮮やかな色
+    HighContrast,
+}
+
+// `COLOR_NAMES` と同じ並び (red, green, blue, yellow, magenta, cyan, white) の置き換え先
+const DEUTERANOPIA: [(u8, u8, u8); 7] = [
+    (213, 94, 0),
+    (0, 158, 115),
+    (0, 114, 178),
+    (240, 228, 66),
+    (204, 121, 167),
+    (86, 180, 233),
+    (255, 255, 255),
+];
+const PROTANOPIA: [(u8, u8, u8); 7] = [
+    (230, 159, 0),
+    (0, 158, 115),
+    (0, 114, 178),
+    (240, 228, 66),
+    (204, 121, 167),
+    (86, 180, 233),
+    (255, 255, 255),
+];
+const TRITANOPIA: [(u8, u8, u8); 7] = [
+    (221, 40, 40),
+    (40, 160, 60),
+    (40, 40, 160),
+    (255, 200, 200),
+    (140, 0, 100),
+    (150, 230, 230),
+    (255, 255, 255),
+];
+const HIGH_CONTRAST: [(u8, u8, u8); 7] = [
+    (255, 85, 85),
+    (0, 255, 0),
+    (110, 160, 255),
+    (255, 255, 0),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+impl Palette {
+    /// `PALETTE_NAMES` と同じ並びの全てのパレット
+    pub const ALL: [Palette; 4] = [
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+        Palette::HighContrast,
+    ];
+
+    /// パレット名 (`PALETTE_NAMES` の1つ)
+    pub fn name(self) -> &'static str {
+        PALETTE_NAMES[self as usize]
+    }
+
+    /// `love palettes` に出す1行の説明
+    pub fn description(self) -> &'static str {
+        match self {
+            Palette::Deuteranopia => "For green-weak color vision (Okabe-Ito colors)",
+            Palette::Protanopia => "For red-weak color vision (red becomes a bright orange)",
+            Palette::Tritanopia => "For blue-yellow color blindness (told apart by brightness)",
+            Palette::HighContrast => "Bright, saturated colors that stand out on a dark background",
+        }
+    }
+
+    /// 7色の1つを置き換えた色
+    pub fn rgb(self, color: HeartColor) -> (u8, u8, u8) {
+        let table = match self {
+            Palette::Deuteranopia => DEUTERANOPIA,
+            Palette::Protanopia => PROTANOPIA,
+            Palette::Tritanopia => TRITANOPIA,
+            Palette::HighContrast => HIGH_CONTRAST,
+        };
+        table[color as usize]
+    }
+
+    /// 描く色を置き換える (7色以外の黒や既定の色、truecolorはそのまま)
+    pub fn apply(self, color: Color) -> Color {
+        let Some(heart_color) = HeartColor::ALL
+            .into_iter()
+            .find(|heart_color| heart_color.to_color() == color)
+        else {
+            return color;
+        };
+        let (r, g, b) = self.rgb(heart_color);
+        Color::Rgb { r, g, b }
+    }
+}
+
+impl FromStr for Palette {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Palette::ALL
+            .into_iter()
+            .find(|palette| palette.name() == s)
+            .ok_or_else(|| ConfigError::UnknownPalette(s.to_string()))
+    }
+}
+
+/// パレットの指定があれば色を置き換える
+pub fn paint(palette: Option<Palette>, color: Color) -> Color {
+    match palette {
+        Some(palette) => palette.apply(color),
+        None => color,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::collections::HashSet;
+
+    // ================================================================
+    // Palette::from_str: パレット名のパースの仕様
+    // ================================================================
+
+    mod describe_from_str {
+        use super::*;
+
+        #[rstest]
+        #[case::緑を感じにくい色覚("deuteranopia", Palette::Deuteranopia)]
+        #[case::赤を感じにくい色覚("protanopia", Palette::Protanopia)]
+        #[case::青と黄を見分けにくい色覚("tritanopia", Palette::Tritanopia)]
+        #[case::ハイコントラスト("high-contrast", Palette::HighContrast)]
+        fn 有効な名前は対応するパレットを返す(
+            #[case] input: &str,
+            #[case] expected: Palette,
+        ) {
+            assert_eq!(input.parse::<Palette>().unwrap(), expected);
+            assert_eq!(expected.name(), input);
+        }
+
+        #[test]
+        fn 知らない名前は候補つきのエラーを返す() {
+            let error = "sepia".parse::<Palette>().unwrap_err();
+            assert_eq!(error, ConfigError::UnknownPalette("sepia".to_string()));
+            assert!(error.to_string().contains("high-contrast"));
+        }
+    }
+
+    // ================================================================
+    // Palette::apply: 色の置き換えの仕様
+    // ================================================================
+
+    mod describe_apply {
+        use super::*;
+
+        #[rstest]
+        fn 七色を全て見分けられる色に置き換える(
+            #[values(
+                Palette::Deuteranopia,
+                Palette::Protanopia,
+                Palette::Tritanopia,
+                Palette::HighContrast
+            )]
+            palette: Palette,
+        ) {
+            let colors: Vec<Color> = HeartColor::ALL
+                .iter()
+                .map(|&color| palette.apply(color.to_color()))
+                .collect();
+            assert!(colors
+                .iter()
+                .all(|color| matches!(color, Color::Rgb { .. })));
+            let distinct: HashSet<Color> = colors.into_iter().collect();
+            assert_eq!(distinct.len(), HeartColor::ALL.len());
+        }
+
+        #[test]
+        fn 赤と緑の明るさに差をつける() {
+            // 赤と緑を見分けにくい色覚でも、明るさの違いで見分けられるようにする
+            let luminance =
+                |(r, g, b): (u8, u8, u8)| 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+            for palette in [Palette::Deuteranopia, Palette::Protanopia] {
+                let red = luminance(palette.rgb(HeartColor::Red));
+                let green = luminance(palette.rgb(HeartColor::Green));
+                assert!((red - green).abs() > 10.0, "{}", palette.name());
+            }
+        }
+
+        #[rstest]
+        #[case::黒(Color::Black)]
+        #[case::既定の色(Color::Reset)]
+        #[case::truecolor(Color::Rgb { r: 1, g: 2, b: 3 })]
+        fn 七色以外はそのまま返す(#[case] color: Color) {
+            assert_eq!(Palette::HighContrast.apply(color), color);
+        }
+
+        #[test]
+        fn 指定がなければ置き換えない() {
+            assert_eq!(paint(None, Color::Red), Color::Red);
+            assert_eq!(
+                paint(Some(Palette::Deuteranopia), Color::Red),
+                Color::Rgb {
+                    r: 213,
+                    g: 94,
+                    b: 0
+                }
+            );
+        }
+    }
+}
@@ -0,0 +1,322 @@
+use crate::config::check_message;
+use crate::error::{Error, Reason, Result};
+use std::io::Read;
+
+// ファイルや標準入力から読み込む量の上限 (リソース枯渇対策)
+pub const MAX_PLAYLIST_BYTES: u64 = 64 * 1024;
+pub const MAX_PLAYLIST_MESSAGES: usize = 1000;
+
+/// 1行1メッセージのテキストを読み込み、各行をバリデーションする (空行は読み飛ばす)
+pub fn read_messages<R: Read>(reader: R) -> Result<Vec<String>> {
+    let mut bytes = Vec::new();
+    reader
+        .take(MAX_PLAYLIST_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| Error::Config(Reason::MessagesUnreadable(e)))?;
+
+    if bytes.len() as u64 > MAX_PLAYLIST_BYTES {
+        return Err(Error::Validation(Reason::MessagesTooLarge {
+            max: MAX_PLAYLIST_BYTES,
+        }));
+    }
+
+    let text = String::from_utf8(bytes).map_err(|_| Error::Validation(Reason::MessagesNotUtf8))?;
+    parse_messages(&text)
+}
+
+/// 1行1メッセージのテキストを分割し、各行をバリデーションする (空行や制御文字だけの行は読み飛ばす)
+pub fn parse_messages(text: &str) -> Result<Vec<String>> {
+    let mut messages = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let message = check_message(line)
+            .map_err(|error| Error::Validation(Reason::Line { line: i + 1, error }))?;
+        // 制御文字だけの行も、除いた後は空行と同じく読み飛ばす
+        if message.trim().is_empty() {
+            continue;
+        }
+        if messages.len() >= MAX_PLAYLIST_MESSAGES {
+            return Err(Error::Validation(Reason::TooManyMessages {
+                max: MAX_PLAYLIST_MESSAGES,
+            }));
+        }
+        messages.push(message);
+    }
+
+    if messages.is_empty() {
+        return Err(Error::Validation(Reason::NoMessages));
+    }
+    Ok(messages)
+}
+
+/// 順番に (またはシャッフルして) メッセージを取り出すプレイリスト
+pub struct Playlist {
+    messages: Vec<String>,
+    order: Vec<usize>,
+    position: usize,
+    shuffle: bool,
+    rng: fastrand::Rng,
+}
+
+impl Playlist {
+    pub fn new(messages: Vec<String>, shuffle: bool) -> Self {
+        Playlist::with_rng(messages, shuffle, fastrand::Rng::new())
+    }
+
+    /// シードを固定した乱数でシャッフルする
+    pub fn with_seed(messages: Vec<String>, shuffle: bool, seed: u64) -> Self {
+        Playlist::with_rng(messages, shuffle, fastrand::Rng::with_seed(seed))
+    }
+
+    fn with_rng(messages: Vec<String>, shuffle: bool, rng: fastrand::Rng) -> Self {
+        let mut playlist = Playlist {
+            order: (0..messages.len()).collect(),
+            messages,
+            position: 0,
+            shuffle,
+            rng,
+        };
+        playlist.reorder();
+        playlist
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// 今の一巡でまだ取り出していないメッセージの数
+    pub fn remaining(&self) -> usize {
+        self.messages.len() - self.position
+    }
+
+    /// 次のメッセージを返す (一巡したら先頭に戻り、シャッフル時は並べ直す)
+    pub fn next_message(&mut self) -> Option<String> {
+        if self.messages.is_empty() {
+            return None;
+        }
+        if self.position >= self.messages.len() {
+            self.position = 0;
+            self.reorder();
+        }
+
+        let message = self.messages[self.order[self.position]].clone();
+        self.position += 1;
+        Some(message)
+    }
+
+    fn reorder(&mut self) {
+        if self.shuffle {
+            self.rng.shuffle(&mut self.order);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn messages(items: &[&str]) -> Vec<String> {
+        items.iter().map(|m| m.to_string()).collect()
+    }
+
+    // ================================================================
+    // parse_messages: メッセージ一覧の読み込みの仕様
+    // ================================================================
+
+    mod describe_parse_messages {
+        use super::*;
+
+        mod 正常なテキストの場合 {
+            use super::*;
+
+            #[test]
+            fn 一行ずつメッセージとして返す() {
+                assert_eq!(
+                    parse_messages("Thanks Alice\nGreat job Bob\n").unwrap(),
+                    messages(&["Thanks Alice", "Great job Bob"])
+                );
+            }
+
+            #[rstest]
+            #[case::空行("A\n\nB")]
+            #[case::空白のみの行("A\n   \nB")]
+            #[case::crlf改行("A\r\nB\r\n")]
+            fn 空行と改行コードを除いて返す(#[case] input: &str) {
+                assert_eq!(parse_messages(input).unwrap(), messages(&["A", "B"]));
+            }
+        }
+
+        mod 制御文字を含む行の場合 {
+            use super::*;
+
+            #[test]
+            fn サニタイズして返す() {
+                assert_eq!(
+                    parse_messages("Hello\x1b[31mWorld").unwrap(),
+                    messages(&["Hello[31mWorld"])
+                );
+            }
+
+            #[test]
+            fn 制御文字だけの行は読み飛ばす() {
+                assert_eq!(
+                    parse_messages("A\n\x1b\x07\nB").unwrap(),
+                    messages(&["A", "B"])
+                );
+            }
+        }
+
+        mod 長すぎる行がある場合 {
+            use super::*;
+
+            #[test]
+            fn 行番号つきのエラーを返す() {
+                let input = format!("ok\n{}\n", "a".repeat(101));
+                let err = parse_messages(&input).unwrap_err().to_string();
+                assert!(err.contains("Line 2"));
+                assert!(err.contains("Message too long"));
+            }
+        }
+
+        mod メッセージが多すぎる場合 {
+            use super::*;
+
+            #[test]
+            fn エラーを返す() {
+                let input = "a\n".repeat(MAX_PLAYLIST_MESSAGES + 1);
+                assert!(parse_messages(&input)
+                    .unwrap_err()
+                    .to_string()
+                    .contains("Too many messages"));
+            }
+
+            #[test]
+            fn 上限ちょうどは受け付ける() {
+                let input = "a\n".repeat(MAX_PLAYLIST_MESSAGES);
+                assert_eq!(parse_messages(&input).unwrap().len(), MAX_PLAYLIST_MESSAGES);
+            }
+        }
+
+        mod メッセージがない場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::空文字列("")]
+            #[case::空行のみ("\n\n  \n")]
+            #[case::制御文字のみ("\x1b\n\x07\x08\n")]
+            fn エラーを返す(#[case] input: &str) {
+                assert!(parse_messages(input)
+                    .unwrap_err()
+                    .to_string()
+                    .contains("No messages found"));
+            }
+        }
+    }
+
+    // ================================================================
+    // read_messages: 入力ストリームからの読み込みの仕様
+    // ================================================================
+
+    mod describe_read_messages {
+        use super::*;
+
+        #[test]
+        fn 入力を読み込んで分割する() {
+            let input = "Thanks\nKudos\n".as_bytes();
+            assert_eq!(
+                read_messages(input).unwrap(),
+                messages(&["Thanks", "Kudos"])
+            );
+        }
+
+        #[test]
+        fn 上限を超える入力はエラーを返す() {
+            let input = vec![b'a'; MAX_PLAYLIST_BYTES as usize + 1];
+            assert!(read_messages(input.as_slice())
+                .unwrap_err()
+                .to_string()
+                .contains("too large"));
+        }
+
+        #[test]
+        fn utf8でない入力はエラーを返す() {
+            let input: &[u8] = &[0xff, 0xfe, b'\n'];
+            assert!(read_messages(input)
+                .unwrap_err()
+                .to_string()
+                .contains("UTF-8"));
+        }
+    }
+
+    // ================================================================
+    // Playlist: プレイリストの仕様
+    // ================================================================
+
+    mod describe_playlist {
+        use super::*;
+
+        mod シャッフルしない場合 {
+            use super::*;
+
+            #[test]
+            fn 順番に返して一巡したら先頭に戻る() {
+                let mut playlist = Playlist::new(messages(&["A", "B"]), false);
+                let taken: Vec<_> = (0..5).map(|_| playlist.next_message().unwrap()).collect();
+                assert_eq!(taken, messages(&["A", "B", "A", "B", "A"]));
+            }
+
+            #[test]
+            fn 残りの数を返す() {
+                let mut playlist = Playlist::new(messages(&["A", "B"]), false);
+                assert_eq!(playlist.remaining(), 2);
+                playlist.next_message();
+                assert_eq!(playlist.remaining(), 1);
+                playlist.next_message();
+                assert_eq!(playlist.remaining(), 0);
+            }
+        }
+
+        mod シャッフルする場合 {
+            use super::*;
+
+            #[test]
+            fn 一巡で全てのメッセージを一度ずつ返す() {
+                let items = messages(&["A", "B", "C", "D", "E"]);
+                let mut playlist = Playlist::with_seed(items.clone(), true, 7);
+                for _ in 0..3 {
+                    let mut round: Vec<_> = (0..items.len())
+                        .map(|_| playlist.next_message().unwrap())
+                        .collect();
+                    round.sort();
+                    assert_eq!(round, items);
+                }
+            }
+
+            #[test]
+            fn 並び順が変わる() {
+                let items: Vec<String> = (0..20).map(|i| i.to_string()).collect();
+                let mut playlist = Playlist::with_seed(items.clone(), true, 7);
+                let round: Vec<_> = (0..items.len())
+                    .map(|_| playlist.next_message().unwrap())
+                    .collect();
+                assert_ne!(round, items);
+            }
+        }
+
+        mod 空の場合 {
+            use super::*;
+
+            #[test]
+            fn noneを返す() {
+                let mut playlist = Playlist::new(Vec::new(), true);
+                assert!(playlist.is_empty());
+                assert_eq!(playlist.next_message(), None);
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use crate::floating::{FloatingHeart, Motion};
+use crate::playlist::Playlist;
+use crate::{heart_sizes, HeartColor, HeartConfig};
+
+// スクリーンセーバーの1行ぶん浮き上がる間隔 (ミリ秒)
+pub const SCREENSAVER_TICK_MS: u64 = 150;
+
+// 画面の幅に対して同時に浮かべるハートの数 (この列数ごとに1つ)
+const COLUMNS_PER_HEART: u16 = 30;
+
+/// メッセージの指定がない場合に使う一覧
+pub const SCREENSAVER_MESSAGES: [&str; 6] =
+    ["I love you", "Be mine", "xoxo", "Hugs", "You rock", "Love"];
+
+/// サイズ・色・浮かび方・メッセージを毎回変えながらハートを浮かべ続ける
+pub struct Screensaver {
+    rng: fastrand::Rng,
+    messages: Playlist,
+    config: HeartConfig,
+    reduced_motion: bool,
+    hearts: Vec<FloatingHeart>,
+}
+
+impl Screensaver {
+    /// プレイリストから1つずつメッセージを取り出して浮かべる (空の場合はメッセージなし)
+    pub fn new(messages: Playlist) -> Self {
+        Screensaver::with_rng(messages, fastrand::Rng::new())
+    }
+
+    /// シードを固定した乱数で浮かべる
+    pub fn with_seed(messages: Playlist, seed: u64) -> Self {
+        Screensaver::with_rng(messages, fastrand::Rng::with_seed(seed))
+    }
+
+    fn with_rng(messages: Playlist, rng: fastrand::Rng) -> Self {
+        Screensaver {
+            rng,
+            messages,
+            config: HeartConfig::default(),
+            reduced_motion: false,
+            hearts: Vec::new(),
+        }
+    }
+
+    /// 各ハートの元にする設定 (メッセージ・大きさ・色だけをハートごとに変える)
+    pub fn with_config(mut self, config: HeartConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 浮かべる代わりに、画面内のあちこちに動かないハートを置く
+    pub fn with_reduced_motion(mut self, reduced_motion: bool) -> Self {
+        self.reduced_motion = reduced_motion;
+        self
+    }
+
+    pub fn hearts(&self) -> &[FloatingHeart] {
+        &self.hearts
+    }
+
+    /// 全てのハートを1行浮かせ、画面の広さに応じて新しいハートを足す
+    pub fn tick(&mut self, cols: u16, rows: u16) {
+        for heart in &mut self.hearts {
+            heart.advance();
+        }
+        self.hearts.retain(|heart| !heart.is_gone());
+
+        let capacity = (cols / COLUMNS_PER_HEART).max(1) as usize;
+        // 一斉に出揃わないよう、空いていても毎tickは足さない
+        if self.hearts.len() < capacity && (self.hearts.is_empty() || self.rng.u8(0..4) == 0) {
+            let heart = self.random_heart(cols, rows);
+            self.hearts.push(heart);
+        }
+    }
+
+    /// 画面の下端のすぐ外 (動きを控える場合は画面内) に、ランダムなハートを1つ作る
+    fn random_heart(&mut self, cols: u16, rows: u16) -> FloatingHeart {
+        let mut config = self.config.clone();
+        // プレイリストのメッセージはバリデーション済みのため失敗しない
+        let _ = config.set_message(self.messages.next_message().as_deref());
+        config.set_petite(self.rng.bool());
+        config.set_color(HeartColor::ALL[self.rng.usize(..HeartColor::ALL.len())]);
+
+        let (heart_size, _) = heart_sizes(&config);
+        let width = (heart_size + 1) * 2;
+        let left = self.rng.i32(0..=(cols as i32 - width).max(0));
+        if self.reduced_motion {
+            let top = self.rng.i32(0..=(rows as i32 - heart_size - 1).max(0));
+            return FloatingHeart::new(config, left, top, Motion::Still);
+        }
+
+        let motion = match self.rng.u8(0..3) {
+            0 => Motion::Rise,
+            1 => Motion::Sway,
+            _ => Motion::Drift(if self.rng.bool() { 1 } else { -1 }),
+        };
+
+        FloatingHeart::new(config, left, rows as i32, motion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screensaver() -> Screensaver {
+        let messages = SCREENSAVER_MESSAGES.iter().map(|m| m.to_string()).collect();
+        Screensaver::with_seed(Playlist::with_seed(messages, true, 42), 42)
+    }
+
+    // ================================================================
+    // Screensaver: スクリーンセーバーの仕様
+    // ================================================================
+
+    mod describe_screensaver {
+        use super::*;
+
+        mod 最初のtickの場合 {
+            use super::*;
+
+            #[test]
+            fn 画面の下端のすぐ外にハートを1つ出す() {
+                let mut saver = screensaver();
+                saver.tick(80, 24);
+                assert_eq!(saver.hearts().len(), 1);
+                assert_eq!(saver.hearts()[0].top(), 24);
+            }
+        }
+
+        mod tickを繰り返した場合 {
+            use super::*;
+
+            #[test]
+            fn 画面の幅に応じた数を超えない() {
+                let mut saver = screensaver();
+                for _ in 0..500 {
+                    saver.tick(90, 24);
+                    assert!(saver.hearts().len() <= 3);
+                }
+            }
+
+            #[test]
+            fn 途切れずにハートを出し続ける() {
+                let mut saver = screensaver();
+                for _ in 0..500 {
+                    saver.tick(80, 24);
+                }
+                assert!(!saver.hearts().is_empty());
+            }
+
+            #[test]
+            fn 色とサイズと浮かび方がばらつく() {
+                let mut saver = screensaver();
+                let mut colors = Vec::new();
+                let mut sizes = Vec::new();
+                let mut motions = Vec::new();
+                for _ in 0..100 {
+                    let heart = saver.random_heart(80, 24);
+                    colors.push(heart.config.color());
+                    sizes.push(heart.config.petite());
+                    motions.push(std::mem::discriminant(&heart.motion));
+                }
+                colors.dedup();
+                sizes.dedup();
+                motions.dedup();
+                assert!(colors.len() > 1);
+                assert!(sizes.len() > 1);
+                assert!(motions.len() > 1);
+            }
+
+            #[test]
+            fn メッセージはプレイリストから選ぶ() {
+                let mut saver = screensaver();
+                for _ in 0..50 {
+                    let message = saver
+                        .random_heart(80, 24)
+                        .config
+                        .message()
+                        .unwrap()
+                        .to_string();
+                    assert!(SCREENSAVER_MESSAGES.contains(&message.as_str()));
+                }
+            }
+        }
+
+        mod プレイリストが空の場合 {
+            use super::*;
+
+            #[test]
+            fn メッセージなしのハートを出す() {
+                let mut saver = Screensaver::with_seed(Playlist::new(Vec::new(), false), 42);
+                assert!(saver.random_heart(80, 24).config.message().is_none());
+            }
+        }
+
+        mod 設定を指定した場合 {
+            use super::*;
+            use crate::effects::MessageEffect;
+            use crate::palette::Palette;
+            use crossterm::style::{Attribute, Attributes};
+
+            #[test]
+            fn メッセージの見た目の指定を全てのハートに引き継ぐ() {
+                let config = HeartConfig::builder()
+                    .message_color(HeartColor::White)
+                    .message_bg(HeartColor::Blue)
+                    .message_attributes(Attributes::from(Attribute::Bold))
+                    .message_effect(MessageEffect::Typewriter)
+                    .marquee(true)
+                    .filled(true)
+                    .palette(Palette::HighContrast)
+                    .build()
+                    .unwrap();
+                let mut saver = screensaver().with_config(config.clone());
+                for _ in 0..20 {
+                    let heart = saver.random_heart(80, 24);
+                    assert_eq!(heart.config.message_color(), config.message_color());
+                    assert_eq!(heart.config.message_bg(), config.message_bg());
+                    assert_eq!(
+                        heart.config.message_attributes(),
+                        config.message_attributes()
+                    );
+                    assert_eq!(heart.config.message_effect(), config.message_effect());
+                    assert!(heart.config.marquee() && heart.config.filled());
+                    assert_eq!(heart.config.palette(), config.palette());
+                }
+            }
+
+            #[test]
+            fn 自動コントラストを引き継ぐ() {
+                let config = HeartConfig::builder().auto_contrast(true).build().unwrap();
+                let mut saver = screensaver().with_config(config);
+                assert!(saver.random_heart(80, 24).config.auto_contrast());
+            }
+        }
+
+        mod 動きを控える場合 {
+            use super::*;
+
+            #[test]
+            fn 画面内に動かないハートを置く() {
+                let mut saver = screensaver().with_reduced_motion(true);
+                for _ in 0..50 {
+                    let heart = saver.random_heart(80, 40);
+                    assert_eq!(heart.motion, Motion::Still);
+                    let (heart_size, _) = heart_sizes(&heart.config);
+                    assert!((0..=40 - heart_size - 1).contains(&heart.top()));
+                }
+            }
+        }
+
+        mod 画面がハートより狭い場合 {
+            use super::*;
+
+            #[test]
+            fn 左端に寄せて出す() {
+                let mut saver = screensaver();
+                for _ in 0..20 {
+                    assert_eq!(saver.random_heart(10, 24).left(), 0);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! 同じマシンの別のターミナルにハートを送る (`love send`)
+//!
+//! `write(1)` と同じく、受け取る側が `mesg n` にしているターミナルには書き込まない。
+//! 届けたハートは `letter::write_inline` で相手の画面を消さずに書き足す。
+
+use crate::error::{Error, Reason, Result};
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, IsTerminal};
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
+
+const PASSWD: &str = "/etc/passwd";
+
+// ログイン中のターミナルを探す場所 (Linux は /dev/pts/N、macOS は /dev/ttysNNN)
+const TTY_DIRS: [&str; 2] = ["/dev/pts", "/dev"];
+
+// `mesg y` で立つ、グループの書き込み許可のビット
+const MESG_BIT: u32 = 0o020;
+
+/// `mesg y` にしているターミナルか (ファイルのモードで判断する)
+pub fn accepts_messages(mode: u32) -> bool {
+    mode & MESG_BIT != 0
+}
+
+/// ハートを送ってよい端末のパスか (/dev/pts/N、/dev/ttyN、/dev/ttysNNN だけを許す)
+///
+/// シリアルポート (/dev/ttyS0 や /dev/ttyUSB0) のような、人がログインしていない端末には送らない。
+fn is_tty_path(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    match path.parent() {
+        Some(dir) if dir == Path::new("/dev/pts") => is_number(name),
+        Some(dir) if dir == Path::new("/dev") => match name.strip_prefix("tty") {
+            Some(rest) => is_number(rest.strip_prefix('s').unwrap_or(rest)),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// 実行しているユーザーのログイン名 (実uidで /etc/passwd を引く)
+///
+/// `$USER` などの環境変数は書き換えられるため、送り主の名前には使わない。
+pub fn login_name() -> Option<String> {
+    let passwd = fs::read_to_string(PASSWD).ok()?;
+    find_user(&passwd, rustix::process::getuid().as_raw())
+}
+
+/// /etc/passwd の形式のテキストからユーザーのuidを探す
+fn find_uid(passwd: &str, user: &str) -> Option<u32> {
+    passwd
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .find_map(|line| {
+            let mut fields = line.split(':');
+            if fields.next()? != user {
+                return None;
+            }
+            fields.nth(1)?.parse().ok()
+        })
+}
+
+/// /etc/passwd の形式のテキストからuidのユーザー名を探す
+fn find_user(passwd: &str, uid: u32) -> Option<String> {
+    passwd
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .find_map(|line| {
+            let mut fields = line.split(':');
+            let user = fields.next()?;
+            (fields.nth(1)?.parse() == Ok(uid)).then(|| user.to_string())
+        })
+}
+
+fn not_a_terminal(path: &Path) -> Error {
+    Error::Validation(Reason::NotATerminal(path.to_path_buf()))
+}
+
+fn messages_disabled(whom: impl std::fmt::Display) -> Error {
+    Error::Terminal(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        Reason::MessagesDisabled(whom.to_string()),
+    ))
+}
+
+/// 書き込みを許されたターミナルを開く
+pub fn open_tty(path: &Path) -> Result<File> {
+    let canonical = fs::canonicalize(path).map_err(|_| not_a_terminal(path))?;
+    let metadata = fs::metadata(&canonical).map_err(Error::Terminal)?;
+    if !is_tty_path(&canonical) || !metadata.file_type().is_char_device() {
+        return Err(not_a_terminal(path));
+    }
+    if !accepts_messages(metadata.mode()) {
+        return Err(messages_disabled(path.display()));
+    }
+
+    let file = OpenOptions::new()
+        .write(true)
+        .open(&canonical)
+        .map_err(|e| {
+            Error::Terminal(io::Error::new(
+                e.kind(),
+                Reason::CannotWrite {
+                    path: path.to_path_buf(),
+                    source: e,
+                },
+            ))
+        })?;
+    if !file.is_terminal() {
+        return Err(not_a_terminal(path));
+    }
+    Ok(file)
+}
+
+/// ユーザーのターミナルのうち、メッセージを受け付けていて最近使われたものを探す
+pub fn find_user_tty(user: &str) -> Result<PathBuf> {
+    let passwd = fs::read_to_string(PASSWD).map_err(|source| {
+        Error::Config(Reason::ReadFailed {
+            path: PASSWD.into(),
+            source,
+        })
+    })?;
+    let uid = find_uid(&passwd, user)
+        .ok_or_else(|| Error::Validation(Reason::UnknownUser(user.to_string())))?;
+
+    let ttys = user_ttys(uid);
+    if ttys.is_empty() {
+        return Err(Error::Validation(Reason::NotLoggedIn(user.to_string())));
+    }
+    ttys.into_iter()
+        .filter(|(_, metadata)| accepts_messages(metadata.mode()))
+        .max_by_key(|(_, metadata)| metadata.atime())
+        .map(|(path, _)| path)
+        .ok_or_else(|| messages_disabled(user))
+}
+
+/// uid が持ち主のターミナルの一覧
+fn user_ttys(uid: u32) -> Vec<(PathBuf, Metadata)> {
+    TTY_DIRS
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let metadata = fs::metadata(&path).ok()?;
+            let owned = is_tty_path(&path)
+                && metadata.file_type().is_char_device()
+                && metadata.uid() == uid;
+            owned.then_some((path, metadata))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const PASSWD_TEXT: &str = "\
+# comment
+root:x:0:0:root:/root:/bin/sh
+alice:x:1000:1000:Alice:/home/alice:/bin/bash
+";
+
+    // ================================================================
+    // accepts_messages / is_tty_path: 送り先の判定の仕様
+    // ================================================================
+
+    mod describe_accepts_messages {
+        use super::*;
+
+        #[rstest]
+        #[case::mesg_y(0o20620, true)]
+        #[case::mesg_n(0o20600, false)]
+        fn グループの書き込み許可で判断する(
+            #[case] mode: u32,
+            #[case] expected: bool,
+        ) {
+            assert_eq!(accepts_messages(mode), expected);
+        }
+    }
+
+    mod describe_is_tty_path {
+        use super::*;
+
+        #[rstest]
+        #[case::疑似端末("/dev/pts/3", true)]
+        #[case::仮想コンソール("/dev/tty2", true)]
+        #[case::macosの端末("/dev/ttys003", true)]
+        #[case::疑似端末のマスター("/dev/pts/ptmx", false)]
+        #[case::制御端末("/dev/tty", false)]
+        #[case::シリアルポート("/dev/ttyS0", false)]
+        #[case::usbシリアル("/dev/ttyUSB0", false)]
+        #[case::usbモデム("/dev/ttyACM0", false)]
+        #[case::番号のないmacosの端末("/dev/ttys", false)]
+        #[case::端末でないデバイス("/dev/null", false)]
+        #[case::通常のファイル("/etc/passwd", false)]
+        fn 端末のパスだけを許す(#[case] path: &str, #[case] expected: bool) {
+            assert_eq!(is_tty_path(Path::new(path)), expected);
+        }
+    }
+
+    // ================================================================
+    // find_uid: ユーザーの検索の仕様
+    // ================================================================
+
+    mod describe_find_uid {
+        use super::*;
+
+        #[rstest]
+        #[case::いるユーザー("alice", Some(1000))]
+        #[case::root("root", Some(0))]
+        #[case::いないユーザー("bob", None)]
+        #[case::名前の一部("ali", None)]
+        fn 名前が一致する行のuidを返す(
+            #[case] user: &str,
+            #[case] expected: Option<u32>,
+        ) {
+            assert_eq!(find_uid(PASSWD_TEXT, user), expected);
+        }
+    }
+
+    // ================================================================
+    // find_user: 送り主の名前の検索の仕様
+    // ================================================================
+
+    mod describe_find_user {
+        use super::*;
+
+        #[rstest]
+        #[case::いるユーザー(1000, Some("alice"))]
+        #[case::root(0, Some("root"))]
+        #[case::いないuid(1001, None)]
+        fn uidが一致する行の名前を返す(
+            #[case] uid: u32,
+            #[case] expected: Option<&str>,
+        ) {
+            assert_eq!(find_user(PASSWD_TEXT, uid).as_deref(), expected);
+        }
+    }
+
+    // ================================================================
+    // open_tty: 送り先を開く仕様
+    // ================================================================
+
+    mod describe_open_tty {
+        use super::*;
+
+        #[rstest]
+        #[case::通常のファイル("/etc/passwd")]
+        #[case::端末でないデバイス("/dev/null")]
+        #[case::存在しないパス("/dev/pts/999999")]
+        fn 端末でなければ書き込まずにエラーを返す(#[case] path: &str) {
+            let err = open_tty(Path::new(path)).unwrap_err();
+            assert_eq!(err.exit_code(), Error::EXIT_VALIDATION);
+            assert!(err.to_string().contains("is not a terminal"));
+        }
+    }
+}
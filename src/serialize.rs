@@ -0,0 +1,273 @@
+//! serde で書き出す際の、外部クレートの型やバリデーションつきの項目の変換
+
+use crate::bigtext::Font;
+use crate::config::ConfigError;
+use crate::effects::MessageEffect;
+use crate::palette::Palette;
+use crate::{HeartColor, HeartConfig};
+use crossterm::style::Attributes;
+
+/// `Attributes` を装飾名の一覧 (例: `["Bold", "Italic"]`) として扱う
+pub(crate) mod attributes {
+    use crossterm::style::{Attribute, Attributes};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        attributes: &Attributes,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Attribute::iterator()
+            .filter(|&attribute| attributes.has(attribute))
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Attributes, D::Error> {
+        let list = Vec::<Attribute>::deserialize(deserializer)?;
+        Ok(Attributes::from(list.as_slice()))
+    }
+}
+
+/// `Duration` をミリ秒の整数として扱う
+pub(crate) mod millis {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        (duration.as_millis() as u64).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+/// 読み込んだ設定を `HeartConfig::builder()` に通す前の内容
+///
+/// 読み込みでもビルダーと同じバリデーション (メッセージの長さや項目の組み合わせ) をかける。
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct HeartConfigData {
+    message: Option<String>,
+    petite: bool,
+    color: HeartColor,
+    marquee: bool,
+    message_effect: Option<MessageEffect>,
+    font: Option<Font>,
+    message_color: Option<HeartColor>,
+    message_bg: Option<HeartColor>,
+    #[serde(deserialize_with = "attributes::deserialize")]
+    message_attributes: Attributes,
+    auto_contrast: bool,
+    filled: bool,
+    palette: Option<Palette>,
+}
+
+impl TryFrom<HeartConfigData> for HeartConfig {
+    type Error = ConfigError;
+
+    fn try_from(data: HeartConfigData) -> Result<Self, Self::Error> {
+        let mut builder = HeartConfig::builder()
+            .petite(data.petite)
+            .color(data.color)
+            .marquee(data.marquee)
+            .message_attributes(data.message_attributes)
+            .auto_contrast(data.auto_contrast)
+            .filled(data.filled);
+        if let Some(message) = data.message {
+            builder = builder.message(message);
+        }
+        if let Some(effect) = data.message_effect {
+            builder = builder.message_effect(effect);
+        }
+        if let Some(font) = data.font {
+            builder = builder.font(font);
+        }
+        if let Some(color) = data.message_color {
+            builder = builder.message_color(color);
+        }
+        if let Some(color) = data.message_bg {
+            builder = builder.message_bg(color);
+        }
+        if let Some(palette) = data.palette {
+            builder = builder.palette(palette);
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::animation::{Animation, Frame};
+    use crate::bigtext::Font;
+    use crate::config::HeartConfigBuilder;
+    use crate::controls::DEFAULT_TICK_MS;
+    use crate::effects::MessageEffect;
+    use crate::{HeartColor, HeartConfig, MessageState};
+    use crossterm::style::Attribute;
+    use std::time::Duration;
+
+    fn love_card_builder() -> HeartConfigBuilder {
+        HeartConfig::builder()
+            .message("Thank you")
+            .color(HeartColor::Magenta)
+            .message_effect(MessageEffect::Sparkle)
+            .message_bg(HeartColor::White)
+            .message_attributes([Attribute::Bold, Attribute::Italic].as_slice().into())
+            .filled(true)
+    }
+
+    fn love_card() -> HeartConfig {
+        love_card_builder().build().unwrap()
+    }
+
+    // ================================================================
+    // HeartConfig: 設定の読み書きの仕様
+    // ================================================================
+
+    mod describe_heart_config {
+        use super::*;
+
+        mod 書き出して読み込んだ場合 {
+            use super::*;
+
+            #[test]
+            fn jsonで元の設定に戻る() {
+                let json = serde_json::to_string(&love_card()).unwrap();
+                assert_eq!(
+                    serde_json::from_str::<HeartConfig>(&json).unwrap(),
+                    love_card()
+                );
+            }
+
+            #[test]
+            fn tomlで元の設定に戻る() {
+                let toml = toml::to_string(&love_card()).unwrap();
+                assert_eq!(toml::from_str::<HeartConfig>(&toml).unwrap(), love_card());
+            }
+
+            #[test]
+            fn フォントも含めて元に戻る() {
+                let config = love_card_builder().font(Font::builtin()).build().unwrap();
+                let toml = toml::to_string(&config).unwrap();
+                assert_eq!(toml::from_str::<HeartConfig>(&toml).unwrap(), config);
+            }
+        }
+
+        mod 書き出した場合 {
+            use super::*;
+
+            #[test]
+            fn 色や装飾は名前で書き出す() {
+                let json = serde_json::to_value(love_card()).unwrap();
+                assert_eq!(json["color"], "magenta");
+                assert_eq!(json["message_effect"], "sparkle");
+                assert_eq!(
+                    json["message_attributes"],
+                    serde_json::json!(["Bold", "Italic"])
+                );
+            }
+        }
+
+        mod 一部の項目だけ読み込んだ場合 {
+            use super::*;
+
+            #[test]
+            fn 残りは既定値にする() {
+                let config: HeartConfig = toml::from_str("color = \"red\"").unwrap();
+                assert_eq!(
+                    config,
+                    HeartConfig::builder()
+                        .color(HeartColor::Red)
+                        .build()
+                        .unwrap()
+                );
+            }
+        }
+
+        mod 不正なメッセージを読み込んだ場合 {
+            use super::*;
+
+            #[test]
+            fn 長すぎるメッセージはエラーを返す() {
+                let json = format!(r#"{{"message": "{}"}}"#, "a".repeat(101));
+                let err = serde_json::from_str::<HeartConfig>(&json).unwrap_err();
+                assert!(err.to_string().contains("Message too long"));
+            }
+
+            #[test]
+            fn 制御文字を除く() {
+                let json = r#"{"message": "Hello\u001b[31mWorld"}"#;
+                let config: HeartConfig = serde_json::from_str(json).unwrap();
+                assert_eq!(config.message.as_deref(), Some("Hello[31mWorld"));
+            }
+
+            #[test]
+            fn 知らない色名はエラーを返す() {
+                assert!(serde_json::from_str::<HeartConfig>(r#"{"color": "purple"}"#).is_err());
+            }
+        }
+
+        mod 組み合わせられない項目を読み込んだ場合 {
+            use super::*;
+
+            #[test]
+            fn tomlはエラーを返す() {
+                let toml = "message_color = \"white\"\nauto_contrast = true";
+                let err = toml::from_str::<HeartConfig>(toml).unwrap_err();
+                assert!(err.to_string().contains("cannot be used with"), "{}", err);
+            }
+
+            #[test]
+            fn jsonはエラーを返す() {
+                let json = r#"{"message_color": "white", "auto_contrast": true}"#;
+                let err = serde_json::from_str::<HeartConfig>(json).unwrap_err();
+                assert!(err.to_string().contains("cannot be used with"), "{}", err);
+            }
+        }
+    }
+
+    // ================================================================
+    // Frame / Animation: フレームとアニメーションの読み書きの仕様
+    // ================================================================
+
+    mod describe_frame {
+        use super::*;
+
+        #[test]
+        fn フレームを書き出して読み込むと元に戻る() {
+            let frame = Animation::new(love_card(), 40, 30).nth(25).unwrap();
+            let json = serde_json::to_string(&frame).unwrap();
+            assert_eq!(serde_json::from_str::<Frame>(&json).unwrap(), frame);
+        }
+
+        #[test]
+        fn 待ち時間はミリ秒で書き出す() {
+            let frame = Frame::new(1, 1, Duration::from_millis(150));
+            let json = serde_json::to_value(&frame).unwrap();
+            assert_eq!(json["delay_ms"], 150);
+            assert_eq!(json["cells"][0]["fg"], "reset");
+        }
+
+        #[test]
+        fn 途中のアニメーションを書き出すと設定と進み具合が入る() {
+            let mut animation = Animation::new(love_card(), 40, 30);
+            animation.nth(9);
+            let json = serde_json::to_value(&animation).unwrap();
+            assert_eq!(json["config"], serde_json::to_value(love_card()).unwrap());
+            assert_eq!(json["tick_ms"], DEFAULT_TICK_MS);
+            assert_eq!(json["y"], 10);
+        }
+
+        #[test]
+        fn メッセージの状態を書き出して読み込むと元に戻る() {
+            let state = MessageState {
+                marquee_offset: 3,
+                elapsed: Duration::from_millis(480),
+            };
+            let json = serde_json::to_string(&state).unwrap();
+            assert_eq!(json, r#"{"marquee_offset":3,"elapsed_ms":480}"#);
+            assert_eq!(serde_json::from_str::<MessageState>(&json).unwrap(), state);
+        }
+    }
+}
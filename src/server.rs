@@ -0,0 +1,245 @@
+//! ネットワーク越しにハートを配信するサーバー (`love serve` / `love serve-tcp`) で共通の接続の制限
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+// 接続の頻度を覚えておくアドレスの数の上限 (これ以上は新しいアドレスを断る)
+const MAX_TRACKED_ADDRESSES: usize = 4096;
+
+// 配信するフレームの広さ (クライアントが広さを知らせない場合は既定の広さにする)
+pub const DEFAULT_SCREEN_SIZE: (u16, u16) = (80, 24);
+pub const MAX_SCREEN_COLS: u16 = 200;
+pub const MAX_SCREEN_ROWS: u16 = 100;
+
+// 断る返事を送る待ち時間 (待ち受けのスレッドで送るため、相手が読まなくても止まらないよう短くする)
+pub const REFUSAL_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// 同時接続数と接続の頻度の上限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// サーバー全体で同時に配信するクライアントの数
+    pub max_clients: usize,
+    /// 同じアドレスから同時に受け付ける接続の数
+    pub max_clients_per_address: usize,
+    /// 同じアドレスから `window` の間に受け付ける接続の数
+    pub max_connects_per_address: usize,
+    pub window: Duration,
+}
+
+/// 接続を断った理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refusal {
+    TooManyClients,
+    TooManyFromAddress,
+    TooFrequent,
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Refusal::TooManyClients => "Too many clients, please try again later",
+            Refusal::TooManyFromAddress => "Too many connections from your address",
+            Refusal::TooFrequent => "Connecting too often, please slow down",
+        })
+    }
+}
+
+#[derive(Default)]
+struct State {
+    clients: usize,
+    per_address: HashMap<IpAddr, usize>,
+    recent: HashMap<IpAddr, VecDeque<Instant>>,
+}
+
+/// 同時接続数と、アドレスごとの同時接続数・接続の頻度を制限する
+pub struct ConnectionLimiter {
+    limits: Limits,
+    state: Mutex<State>,
+}
+
+impl ConnectionLimiter {
+    pub fn new(limits: Limits) -> Arc<Self> {
+        Arc::new(ConnectionLimiter {
+            limits,
+            state: Mutex::new(State::default()),
+        })
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// 接続を受け付けてよければ、切断時に数から外す `Connection` を返す
+    pub fn acquire(self: &Arc<Self>, address: IpAddr, now: Instant) -> Result<Connection, Refusal> {
+        let limits = self.limits;
+        // 配信中のスレッドがpanicしても、数え方は壊れていないので使い続ける
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // 満員で断った接続も頻度に数え、繰り返し接続し続けるクライアントを締め出す
+        if state.recent.len() >= MAX_TRACKED_ADDRESSES && !state.recent.contains_key(&address) {
+            state.recent.retain(|_, times| {
+                times.retain(|&time| now.duration_since(time) < limits.window);
+                !times.is_empty()
+            });
+            if state.recent.len() >= MAX_TRACKED_ADDRESSES {
+                return Err(Refusal::TooFrequent);
+            }
+        }
+        let times = state.recent.entry(address).or_default();
+        times.retain(|&time| now.duration_since(time) < limits.window);
+        let too_frequent = times.len() >= limits.max_connects_per_address;
+        if !too_frequent {
+            times.push_back(now);
+        }
+
+        if state.clients >= limits.max_clients {
+            return Err(Refusal::TooManyClients);
+        }
+        if state.per_address.get(&address).copied().unwrap_or(0) >= limits.max_clients_per_address {
+            return Err(Refusal::TooManyFromAddress);
+        }
+        if too_frequent {
+            return Err(Refusal::TooFrequent);
+        }
+
+        state.clients += 1;
+        *state.per_address.entry(address).or_default() += 1;
+        Ok(Connection {
+            limiter: Arc::clone(self),
+            address,
+        })
+    }
+
+    /// 配信中のクライアントの数
+    pub fn clients(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clients
+    }
+
+    fn release(&self, address: IpAddr) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.clients -= 1;
+        if let Some(count) = state.per_address.get_mut(&address) {
+            *count -= 1;
+            if *count == 0 {
+                state.per_address.remove(&address);
+            }
+        }
+    }
+}
+
+/// 受け付けた接続 (Dropで同時接続数から外す)
+pub struct Connection {
+    limiter: Arc<ConnectionLimiter>,
+    address: IpAddr,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.limiter.release(self.address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    const LIMITS: Limits = Limits {
+        max_clients: 3,
+        max_clients_per_address: 2,
+        max_connects_per_address: 4,
+        window: Duration::from_secs(10),
+    };
+
+    fn address(n: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(192, 168, 0, n))
+    }
+
+    // ================================================================
+    // ConnectionLimiter: 接続の制限の仕様
+    // ================================================================
+
+    mod describe_connection_limiter {
+        use super::*;
+
+        mod 上限に達していない場合 {
+            use super::*;
+
+            #[test]
+            fn 受け付けて切断すると数から外す() {
+                let limiter = ConnectionLimiter::new(LIMITS);
+                let connection = limiter.acquire(address(1), Instant::now()).unwrap();
+                assert_eq!(limiter.clients(), 1);
+                drop(connection);
+                assert_eq!(limiter.clients(), 0);
+            }
+        }
+
+        mod 全体の上限に達した場合 {
+            use super::*;
+
+            #[test]
+            fn 新しい接続を断る() {
+                let limiter = ConnectionLimiter::new(LIMITS);
+                let now = Instant::now();
+                let _held: Vec<_> = (1..=3)
+                    .map(|n| limiter.acquire(address(n), now).unwrap())
+                    .collect();
+                assert_eq!(
+                    limiter.acquire(address(4), now).err(),
+                    Some(Refusal::TooManyClients)
+                );
+            }
+        }
+
+        mod 同じアドレスから同時に接続した場合 {
+            use super::*;
+
+            #[test]
+            fn アドレスごとの上限を超えると断る() {
+                let limiter = ConnectionLimiter::new(LIMITS);
+                let now = Instant::now();
+                let _first = limiter.acquire(address(1), now).unwrap();
+                let _second = limiter.acquire(address(1), now).unwrap();
+                assert_eq!(
+                    limiter.acquire(address(1), now).err(),
+                    Some(Refusal::TooManyFromAddress)
+                );
+                assert!(limiter.acquire(address(2), now).is_ok());
+            }
+        }
+
+        mod 同じアドレスから繰り返し接続した場合 {
+            use super::*;
+
+            #[test]
+            fn 時間内の回数を超えると断る() {
+                let limiter = ConnectionLimiter::new(LIMITS);
+                let now = Instant::now();
+                for _ in 0..4 {
+                    drop(limiter.acquire(address(1), now).unwrap());
+                }
+                assert_eq!(
+                    limiter.acquire(address(1), now).err(),
+                    Some(Refusal::TooFrequent)
+                );
+            }
+
+            #[test]
+            fn 時間が経てばまた受け付ける() {
+                let limiter = ConnectionLimiter::new(LIMITS);
+                let now = Instant::now();
+                for _ in 0..4 {
+                    drop(limiter.acquire(address(1), now).unwrap());
+                }
+                assert!(limiter.acquire(address(1), now + LIMITS.window).is_ok());
+            }
+        }
+    }
+}
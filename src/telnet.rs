@@ -0,0 +1,492 @@
+//! `nc` や `telnet` で接続したターミナルにアニメーションを流すTCPサーバー (`love serve-tcp`)
+//!
+//! 接続ごとにスレッドを立て、ウィンドウの広さは telnet の NAWS (RFC 1073) で受け取る。
+//! NAWS に答えないクライアント (`nc` など) には既定の広さで描く。
+
+use crate::animation::Animation;
+use crate::error::{Error, Result};
+use crate::server::{
+    ConnectionLimiter, Limits, DEFAULT_SCREEN_SIZE, MAX_SCREEN_COLS, MAX_SCREEN_ROWS,
+    REFUSAL_TIMEOUT,
+};
+use crate::HeartConfig;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::queue;
+use crossterm::style::{Attribute, ResetColor, SetAttribute};
+use crossterm::terminal::{Clear, ClearType};
+use std::io::{self, BufWriter, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// telnet のコマンドとオプション (RFC 854 / 857 / 858 / 1073)
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const OPTION_ECHO: u8 = 1;
+const OPTION_SUPPRESS_GO_AHEAD: u8 = 3;
+const OPTION_NAWS: u8 = 31;
+
+/// NAWS を頼み、クライアント側のエコーと行バッファを止めてもらう
+const GREETING: [u8; 9] = [
+    IAC,
+    DO,
+    OPTION_NAWS,
+    IAC,
+    WILL,
+    OPTION_ECHO,
+    IAC,
+    WILL,
+    OPTION_SUPPRESS_GO_AHEAD,
+];
+
+// 同時接続数と接続の頻度の上限 (超えた分は理由を1行送って切る)
+pub const TCP_LIMITS: Limits = Limits {
+    max_clients: 64,
+    max_clients_per_address: 4,
+    max_connects_per_address: 10,
+    window: Duration::from_secs(60),
+};
+
+// 最初の NAWS の返事を待つ時間 (答えないクライアントは既定の広さにする)
+const NAWS_WAIT: Duration = Duration::from_millis(500);
+
+// 1回の送信の待ち時間 (読まないクライアントで詰まらないように)
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// サブネゴシエーションとして受け取る長さの上限 (NAWS は5バイト)
+const MAX_SUBNEGOTIATION: usize = 16;
+
+// 受信してまだ処理していないイベントの上限 (超えたら読むのを待たせる)
+const EVENT_BUFFER: usize = 64;
+
+/// クライアントから届いたもの
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelnetEvent {
+    /// NAWS で知らされたウィンドウの広さ
+    WindowSize { cols: u16, rows: u16 },
+    /// telnet のコマンド以外の1バイト
+    Data(u8),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum ParserState {
+    #[default]
+    Data,
+    Command,
+    Negotiation,
+    Subnegotiation,
+    SubnegotiationCommand,
+}
+
+/// 受信したバイト列から telnet のコマンドを取り除き、NAWS を読み取る
+#[derive(Debug, Clone, Default)]
+pub struct TelnetParser {
+    state: ParserState,
+    subnegotiation: Vec<u8>,
+}
+
+impl TelnetParser {
+    /// 1バイト読み進め、まとまったものがあれば返す
+    pub fn feed(&mut self, byte: u8) -> Option<TelnetEvent> {
+        use ParserState::*;
+
+        match (self.state, byte) {
+            (Data, IAC) => self.state = Command,
+            (Data, _) => return Some(TelnetEvent::Data(byte)),
+            (Command, IAC) => {
+                self.state = Data;
+                return Some(TelnetEvent::Data(IAC));
+            }
+            (Command, WILL | WONT | DO | DONT) => self.state = Negotiation,
+            (Command, SB) => {
+                self.subnegotiation.clear();
+                self.state = Subnegotiation;
+            }
+            // 返事は待たないので、ネゴシエーションやその他のコマンドは読み捨てる
+            (Command | Negotiation, _) => self.state = Data,
+            (Subnegotiation, IAC) => self.state = SubnegotiationCommand,
+            (Subnegotiation, _) => self.push_subnegotiation(byte),
+            (SubnegotiationCommand, IAC) => {
+                self.push_subnegotiation(IAC);
+                self.state = Subnegotiation;
+            }
+            (SubnegotiationCommand, SE) => {
+                self.state = Data;
+                return self.window_size();
+            }
+            (SubnegotiationCommand, _) => self.state = Subnegotiation,
+        }
+        None
+    }
+
+    fn push_subnegotiation(&mut self, byte: u8) {
+        // 長すぎるものは NAWS ではないので、溜め込まずに捨てる
+        if self.subnegotiation.len() <= MAX_SUBNEGOTIATION {
+            self.subnegotiation.push(byte);
+        }
+    }
+
+    fn window_size(&self) -> Option<TelnetEvent> {
+        match self.subnegotiation.as_slice() {
+            &[OPTION_NAWS, c1, c0, r1, r0] => Some(TelnetEvent::WindowSize {
+                cols: u16::from_be_bytes([c1, c0]),
+                rows: u16::from_be_bytes([r1, r0]),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// NAWS の広さを描ける範囲に収める (0 は「不明」なので既定の広さにする)
+fn screen_size(cols: u16, rows: u16) -> (u16, u16) {
+    let (default_cols, default_rows) = DEFAULT_SCREEN_SIZE;
+    let cols = if cols == 0 { default_cols } else { cols };
+    let rows = if rows == 0 { default_rows } else { rows };
+    (cols.min(MAX_SCREEN_COLS), rows.min(MAX_SCREEN_ROWS))
+}
+
+/// ターミナルにアニメーションを流すTCPサーバー
+pub struct TelnetServer {
+    listener: TcpListener,
+    config: Arc<HeartConfig>,
+    limiter: Arc<ConnectionLimiter>,
+}
+
+impl TelnetServer {
+    pub fn bind(addr: impl ToSocketAddrs, config: HeartConfig) -> Result<Self> {
+        Ok(TelnetServer {
+            listener: TcpListener::bind(addr).map_err(Error::Network)?,
+            config: Arc::new(config),
+            limiter: ConnectionLimiter::new(TCP_LIMITS),
+        })
+    }
+
+    /// 同時接続数と接続の頻度の上限を変える
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limiter = ConnectionLimiter::new(limits);
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener.local_addr().map_err(Error::Network)
+    }
+
+    /// 接続を待ち受け、1接続につき1スレッドでアニメーションを流し続ける
+    pub fn run(self) -> Result<()> {
+        for stream in self.listener.incoming() {
+            // 接続の途中で切られただけなら、待ち受けは続ける
+            let Ok(mut stream) = stream else { continue };
+            let Ok(peer) = stream.peer_addr() else {
+                continue;
+            };
+
+            match self.limiter.acquire(peer.ip(), Instant::now()) {
+                Ok(connection) => {
+                    let config = Arc::clone(&self.config);
+                    thread::spawn(move || {
+                        let _connection = connection;
+                        // 送れなくなったクライアントは黙って切る
+                        let _ = serve_client(&stream, &config);
+                        let _ = stream.shutdown(Shutdown::Both);
+                    });
+                }
+                Err(refusal) => {
+                    let _ = stream.set_write_timeout(Some(REFUSAL_TIMEOUT));
+                    let _ = write!(stream, "{}\r\n", refusal);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 次のフレームまで待つ間に起きたこと
+enum Wait {
+    Elapsed,
+    Resized,
+    Quit,
+}
+
+/// 1つの接続にアニメーションを流す ('q' か Ctrl-C / Ctrl-D、または切断で終わる)
+fn serve_client(stream: &TcpStream, config: &HeartConfig) -> io::Result<()> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let events = spawn_reader(stream.try_clone()?);
+    let mut out = BufWriter::new(stream);
+    out.write_all(&GREETING)?;
+    out.flush()?;
+
+    let mut size = DEFAULT_SCREEN_SIZE;
+    if let Wait::Quit = wait(&events, NAWS_WAIT, &mut size, true) {
+        return Ok(());
+    }
+
+    'session: loop {
+        queue!(
+            out,
+            Hide,
+            SetAttribute(Attribute::Reset),
+            Clear(ClearType::All)
+        )?;
+        let (cols, rows) = size;
+        for frame in Animation::new(config.clone(), cols, rows) {
+            frame.write_ansi(&mut out, None)?;
+            out.flush()?;
+            match wait(&events, frame.delay, &mut size, false) {
+                Wait::Elapsed => {}
+                // 広さが変わったら、新しい広さで下から浮かべ直す
+                Wait::Resized => continue 'session,
+                Wait::Quit => break 'session,
+            }
+        }
+    }
+
+    queue!(out, ResetColor, MoveTo(0, size.1), Show)?;
+    write!(out, "\r\n")?;
+    out.flush()
+}
+
+/// 受信したバイト列を読み、イベントを送るスレッドを立てる (切断するとチャンネルが閉じる)
+fn spawn_reader(mut stream: TcpStream) -> Receiver<TelnetEvent> {
+    let (sender, receiver) = mpsc::sync_channel(EVENT_BUFFER);
+    thread::spawn(move || {
+        let mut parser = TelnetParser::default();
+        let mut buffer = [0; 256];
+        while let Ok(n @ 1..) = stream.read(&mut buffer) {
+            for &byte in &buffer[..n] {
+                if let Some(event) = parser.feed(byte) {
+                    if sender.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    });
+    receiver
+}
+
+/// `delay` だけ待ちながら、届いたイベントを反映する
+///
+/// `until_resized` なら広さが届いた時点で待つのをやめる (最初の NAWS の返事待ち)。
+fn wait(
+    events: &Receiver<TelnetEvent>,
+    delay: Duration,
+    size: &mut (u16, u16),
+    until_resized: bool,
+) -> Wait {
+    let deadline = Instant::now() + delay;
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match events.recv_timeout(timeout) {
+            Ok(TelnetEvent::WindowSize { cols, rows }) => {
+                let resized = screen_size(cols, rows);
+                if until_resized {
+                    *size = resized;
+                    return Wait::Elapsed;
+                }
+                if resized != *size {
+                    *size = resized;
+                    return Wait::Resized;
+                }
+            }
+            Ok(TelnetEvent::Data(b'q' | b'Q' | 0x03 | 0x04)) => return Wait::Quit,
+            Ok(TelnetEvent::Data(_)) => {}
+            Err(RecvTimeoutError::Timeout) => return Wait::Elapsed,
+            Err(RecvTimeoutError::Disconnected) => return Wait::Quit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn feed_all(bytes: &[u8]) -> Vec<TelnetEvent> {
+        let mut parser = TelnetParser::default();
+        bytes.iter().filter_map(|&b| parser.feed(b)).collect()
+    }
+
+    // ================================================================
+    // TelnetParser: telnet の読み取りの仕様
+    // ================================================================
+
+    mod describe_telnet_parser {
+        use super::*;
+
+        mod コマンドを含まない場合 {
+            use super::*;
+
+            #[test]
+            fn そのままのバイトを返す() {
+                assert_eq!(
+                    feed_all(b"hi"),
+                    vec![TelnetEvent::Data(b'h'), TelnetEvent::Data(b'i')]
+                );
+            }
+        }
+
+        mod nawsを受け取った場合 {
+            use super::*;
+
+            #[test]
+            fn ウィンドウの広さを返す() {
+                let bytes = [IAC, SB, OPTION_NAWS, 0, 100, 0, 40, IAC, SE];
+                assert_eq!(
+                    feed_all(&bytes),
+                    vec![TelnetEvent::WindowSize {
+                        cols: 100,
+                        rows: 40
+                    }]
+                );
+            }
+
+            #[test]
+            fn エスケープされた255を戻す() {
+                let bytes = [IAC, SB, OPTION_NAWS, 0, IAC, IAC, 1, 0, IAC, SE];
+                assert_eq!(
+                    feed_all(&bytes),
+                    vec![TelnetEvent::WindowSize {
+                        cols: 255,
+                        rows: 256
+                    }]
+                );
+            }
+        }
+
+        mod その他のコマンドの場合 {
+            use super::*;
+
+            #[rstest]
+            #[case::ネゴシエーション(&[IAC, WILL, OPTION_NAWS, b'a'])]
+            #[case::ネゴシエーションの拒否(&[IAC, DONT, OPTION_ECHO, b'a'])]
+            #[case::nawsでないサブネゴシエーション(&[IAC, SB, 24, 0, b'x', IAC, SE, b'a'])]
+            #[case::長すぎるサブネゴシエーション(&[&[IAC, SB, OPTION_NAWS][..], &[1; 64], &[IAC, SE, b'a']].concat())]
+            fn 読み捨てて続きを読む(#[case] bytes: &[u8]) {
+                assert_eq!(feed_all(bytes), vec![TelnetEvent::Data(b'a')]);
+            }
+
+            #[test]
+            fn エスケープされた255はデータとして返す() {
+                assert_eq!(feed_all(&[IAC, IAC]), vec![TelnetEvent::Data(255)]);
+            }
+        }
+    }
+
+    // ================================================================
+    // screen_size: 広さの仕様
+    // ================================================================
+
+    mod describe_screen_size {
+        use super::*;
+
+        #[rstest]
+        #[case::そのまま(100, 40, (100, 40))]
+        #[case::不明(0, 0, DEFAULT_SCREEN_SIZE)]
+        #[case::広すぎる(60000, 60000, (MAX_SCREEN_COLS, MAX_SCREEN_ROWS))]
+        fn 描ける範囲に収める(
+            #[case] cols: u16,
+            #[case] rows: u16,
+            #[case] expected: (u16, u16),
+        ) {
+            assert_eq!(screen_size(cols, rows), expected);
+        }
+    }
+
+    // ================================================================
+    // TelnetServer: 待ち受けの仕様
+    // ================================================================
+
+    mod describe_telnet_server {
+        use super::*;
+
+        fn start(limits: Limits) -> SocketAddr {
+            let config = HeartConfig::builder().message("Hi").build().unwrap();
+            let server = TelnetServer::bind("127.0.0.1:0", config)
+                .unwrap()
+                .with_limits(limits);
+            let addr = server.local_addr().unwrap();
+            thread::spawn(move || server.run());
+            addr
+        }
+
+        /// 受信したものに `needle` が現れるまで読む
+        fn read_until(stream: &mut TcpStream, needle: &[u8]) -> Vec<u8> {
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let mut received = Vec::new();
+            let mut buffer = [0; 4096];
+            while !received.windows(needle.len()).any(|w| w == needle) {
+                let n = stream.read(&mut buffer).expect("受信が途切れた");
+                assert!(n > 0, "接続が切れた");
+                received.extend_from_slice(&buffer[..n]);
+            }
+            received
+        }
+
+        #[test]
+        fn nawsの広さで描く() {
+            let mut stream = TcpStream::connect(start(TCP_LIMITS)).unwrap();
+            let greeting = read_until(&mut stream, &GREETING);
+            assert!(greeting.starts_with(&[IAC, DO, OPTION_NAWS]));
+
+            stream
+                .write_all(&[
+                    IAC,
+                    WILL,
+                    OPTION_NAWS,
+                    IAC,
+                    SB,
+                    OPTION_NAWS,
+                    0,
+                    40,
+                    0,
+                    30,
+                    IAC,
+                    SE,
+                ])
+                .unwrap();
+            // 30行目 (ESC[30;1H) まで描き、31行目には描かない
+            let received = read_until(&mut stream, b"\x1b[30;1H");
+            assert!(!received.windows(8).any(|w| w == b"\x1b[31;1H"));
+        }
+
+        #[test]
+        fn nawsに答えないクライアントには既定の広さで描く() {
+            let mut stream = TcpStream::connect(start(TCP_LIMITS)).unwrap();
+            read_until(&mut stream, b"\x1b[24;1H");
+        }
+
+        #[test]
+        fn qで終わる() {
+            let mut stream = TcpStream::connect(start(TCP_LIMITS)).unwrap();
+            read_until(&mut stream, b"\x1b[24;1H");
+            stream.write_all(b"q").unwrap();
+
+            let mut rest = Vec::new();
+            stream.read_to_end(&mut rest).unwrap();
+        }
+
+        #[test]
+        fn 上限を超える接続には理由を送って切る() {
+            let addr = start(Limits {
+                max_clients: 1,
+                ..TCP_LIMITS
+            });
+            let mut first = TcpStream::connect(addr).unwrap();
+            read_until(&mut first, &GREETING);
+
+            let mut second = TcpStream::connect(addr).unwrap();
+            let mut refusal = String::new();
+            second.read_to_string(&mut refusal).unwrap();
+            assert!(refusal.contains("Too many clients"));
+        }
+    }
+}
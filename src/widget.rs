@@ -0,0 +1,385 @@
+use crate::{
+    heart_cell, heart_sizes, is_in_love, message_cells, message_row, Cell, HeartConfig,
+    MessageState,
+};
+use crossterm::style::{Attribute, Color};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color as TuiColor, Modifier};
+use ratatui::widgets::{StatefulWidget, Widget};
+use std::time::Duration;
+
+/// `HeartConfig` のハートを、描画先の領域に収まる大きさで描くウィジェット
+///
+/// 形は `is_in_love` を拡大・縮小して描き、メッセージは中央の行に1行で重ねてハートの形で切り抜く。
+/// ブロック文字とマーキー表示には対応しない。
+pub struct HeartWidget<'a> {
+    config: &'a HeartConfig,
+}
+
+impl<'a> HeartWidget<'a> {
+    pub fn new(config: &'a HeartConfig) -> Self {
+        HeartWidget { config }
+    }
+
+    /// 領域に収まる拡大率と、描くハートの幅と高さ
+    fn layout(&self, area: Rect) -> Option<(f64, u16, u16)> {
+        let (heart_size, _) = heart_sizes(self.config);
+        let cells = (heart_size + 1) as f64;
+        // 1セルを横2文字で描くため、幅は高さの2倍ぶん使う
+        let scale = (area.width as f64 / (2.0 * cells)).min(area.height as f64 / cells);
+        let width = (2.0 * cells * scale).floor() as u16;
+        let height = (cells * scale).floor() as u16;
+        (width > 0 && height > 0).then_some((scale, width, height))
+    }
+
+    /// 左上を (left, top) として、領域からはみ出さないようにハートを描く
+    fn draw(&self, area: Rect, buf: &mut Buffer, left: i32, top: i32, state: &MessageState) {
+        let Some((scale, width, height)) = self.layout(area) else {
+            return;
+        };
+        let (heart_size, _) = heart_sizes(self.config);
+        let contains = |column: u16, row: u16| {
+            let x = ((column as f64 / (2.0 * scale)) as i32).min(heart_size);
+            let y = ((row as f64 / scale) as i32).min(heart_size);
+            is_in_love(x, y, self.config)
+        };
+        let mut put = |column: u16, row: u16, cell: &Cell| {
+            let (x, y) = (left + column as i32, top + row as i32);
+            let inside = (area.left() as i32..area.right() as i32).contains(&x)
+                && (area.top() as i32..area.bottom() as i32).contains(&y);
+            if inside {
+                if let Some(target) = buf.cell_mut((x as u16, y as u16)) {
+                    set_cell(target, cell);
+                }
+            }
+        };
+
+        let heart = heart_cell(self.config, 'v');
+        for row in 0..height {
+            for column in 0..width {
+                if contains(column, row) {
+                    put(column, row, &heart);
+                }
+            }
+        }
+
+        // メッセージは拡大・縮小せず、メッセージ行にあたる行の中央に重ねる
+        let message = message_cells(self.config, state);
+        let row = (((message_row(self.config) as f64 + 0.5) * scale) as u16).min(height - 1);
+        let start = (width / 2) as i32 - (message.len() / 2) as i32;
+        for (i, cell) in message.iter().enumerate() {
+            let column = start + i as i32;
+            if column >= 0 && column < width as i32 && contains(column as u16, row) {
+                put(column as u16, row, cell);
+            }
+        }
+    }
+
+    /// ハートが領域の上端を抜けきったか
+    pub fn is_floated_away(&self, area: Rect, state: &HeartState) -> bool {
+        match self.layout(area) {
+            Some((_, _, height)) => state.rise >= area.height + height,
+            None => true,
+        }
+    }
+}
+
+/// 領域の中央に止まったハートを描く
+impl Widget for HeartWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Some((_, width, height)) = self.layout(area) else {
+            return;
+        };
+        let left = area.x + (area.width - width) / 2;
+        let top = area.y + (area.height - height) / 2;
+        self.draw(area, buf, left as i32, top as i32, &MessageState::default());
+    }
+}
+
+/// 領域の下端から浮かび上がる途中のハートを描く
+impl StatefulWidget for HeartWidget<'_> {
+    type State = HeartState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut HeartState) {
+        let Some((_, width, _)) = self.layout(area) else {
+            return;
+        };
+        let left = area.x + (area.width - width) / 2;
+        let top = area.bottom() as i32 - state.rise as i32;
+        self.draw(area, buf, left as i32, top, &state.message);
+    }
+}
+
+/// 浮かび上がるハートのアニメーションの状態
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeartState {
+    /// 領域の下端から浮かんだ行数
+    pub rise: u16,
+    /// マーキー表示とエフェクトの進み具合
+    pub message: MessageState,
+}
+
+impl HeartState {
+    /// 1行浮かせ、メッセージのエフェクトを `elapsed` だけ進める
+    pub fn advance(&mut self, elapsed: Duration) {
+        self.rise = self.rise.saturating_add(1);
+        self.message.elapsed += elapsed;
+    }
+}
+
+fn set_cell(target: &mut ratatui::buffer::Cell, cell: &Cell) {
+    target.set_char(cell.ch);
+    target.fg = to_tui_color(cell.fg);
+    if let Some(bg) = cell.bg {
+        target.bg = to_tui_color(bg);
+    }
+    target.modifier = [
+        (Attribute::Bold, Modifier::BOLD),
+        (Attribute::Italic, Modifier::ITALIC),
+        (Attribute::Underlined, Modifier::UNDERLINED),
+        (Attribute::SlowBlink, Modifier::SLOW_BLINK),
+    ]
+    .into_iter()
+    .filter(|(attribute, _)| cell.attributes.has(*attribute))
+    .fold(Modifier::empty(), |modifiers, (_, modifier)| {
+        modifiers | modifier
+    });
+}
+
+/// crosstermの色をratatuiの色に変換する (crosstermは明るい方の色が無印)
+fn to_tui_color(color: Color) -> TuiColor {
+    match color {
+        Color::Reset => TuiColor::Reset,
+        Color::Black => TuiColor::Black,
+        Color::DarkGrey => TuiColor::DarkGray,
+        Color::Red => TuiColor::LightRed,
+        Color::DarkRed => TuiColor::Red,
+        Color::Green => TuiColor::LightGreen,
+        Color::DarkGreen => TuiColor::Green,
+        Color::Yellow => TuiColor::LightYellow,
+        Color::DarkYellow => TuiColor::Yellow,
+        Color::Blue => TuiColor::LightBlue,
+        Color::DarkBlue => TuiColor::Blue,
+        Color::Magenta => TuiColor::LightMagenta,
+        Color::DarkMagenta => TuiColor::Magenta,
+        Color::Cyan => TuiColor::LightCyan,
+        Color::DarkCyan => TuiColor::Cyan,
+        Color::White => TuiColor::White,
+        Color::Grey => TuiColor::Gray,
+        Color::Rgb { r, g, b } => TuiColor::Rgb(r, g, b),
+        Color::AnsiValue(value) => TuiColor::Indexed(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{love_row, HeartColor};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn config(message: Option<&str>) -> HeartConfig {
+        let builder = HeartConfig::builder().color(HeartColor::Red);
+        match message {
+            Some(message) => builder.message(message),
+            None => builder,
+        }
+        .build()
+        .unwrap()
+    }
+
+    fn render(config: &HeartConfig, width: u16, height: u16) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(HeartWidget::new(config), frame.area()))
+            .unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    fn render_stateful(
+        config: &HeartConfig,
+        width: u16,
+        height: u16,
+        state: &mut HeartState,
+    ) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| {
+                frame.render_stateful_widget(HeartWidget::new(config), frame.area(), state)
+            })
+            .unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    fn lines(buf: &Buffer) -> Vec<String> {
+        (0..buf.area.height)
+            .map(|y| {
+                (0..buf.area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    // ================================================================
+    // HeartWidget: ウィジェットの仕様
+    // ================================================================
+
+    mod describe_heart_widget {
+        use super::*;
+
+        mod 本来の大きさの領域に描いた場合 {
+            use super::*;
+
+            #[test]
+            fn love_rowと同じ形になる() {
+                let config = config(None);
+                let buf = render(&config, 42, 21);
+                for (y, line) in lines(&buf).iter().enumerate() {
+                    assert_eq!(line, &love_row(y as i32, &config));
+                }
+            }
+
+            #[test]
+            fn ハートの色で描く() {
+                let buf = render(&config(None), 42, 21);
+                assert!(buf
+                    .content()
+                    .iter()
+                    .filter(|cell| cell.symbol() == "v")
+                    .all(|cell| cell.fg == TuiColor::LightRed));
+            }
+
+            #[test]
+            fn メッセージを中央の行に重ねる() {
+                let config = config(Some("Hello"));
+                let line = &lines(&render(&config, 42, 21))[message_row(&config) as usize];
+                assert!(line.contains("v Hello v"));
+            }
+        }
+
+        mod 小さい領域に描いた場合 {
+            use super::*;
+
+            #[test]
+            fn 縮小して収める() {
+                let buf = render(&config(None), 21, 10);
+                let drawn = buf
+                    .content()
+                    .iter()
+                    .filter(|cell| cell.symbol() == "v")
+                    .count();
+                assert!(drawn > 0);
+                assert!(drawn < 42 * 21 / 2);
+            }
+
+            #[test]
+            fn 収まらないメッセージはハートの形で切り抜く() {
+                let config = config(Some("A message that is far too long"));
+                let buf = render(&config, 21, 10);
+                for line in lines(&buf) {
+                    assert!(!line.contains("far too long"));
+                }
+            }
+        }
+
+        mod 大きい領域に描いた場合 {
+            use super::*;
+
+            #[test]
+            fn 縦横比を保って中央に拡大する() {
+                let buf = render(&config(None), 120, 42);
+                let lines = lines(&buf);
+                let drawn: Vec<usize> = lines
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, line)| line.contains('v'))
+                    .map(|(y, _)| y)
+                    .collect();
+                // 本来は16行ぶんの形が2倍になる
+                assert_eq!(drawn.len(), 32);
+                let widest = lines.iter().map(|line| line.matches('v').count()).max();
+                assert_eq!(widest, Some(76));
+                assert!(lines.iter().all(|line| line[..18].trim().is_empty()));
+            }
+        }
+
+        mod 領域が空の場合 {
+            use super::*;
+
+            #[test]
+            fn 何も描かない() {
+                let mut buf = Buffer::empty(Rect::new(0, 0, 0, 0));
+                Widget::render(
+                    HeartWidget::new(&config(None)),
+                    Rect::new(0, 0, 0, 0),
+                    &mut buf,
+                );
+                assert!(buf.content().is_empty());
+            }
+        }
+
+        mod 状態つきで描いた場合 {
+            use super::*;
+
+            #[test]
+            fn 浮かぶ前は何も見えない() {
+                let mut state = HeartState::default();
+                let buf = render_stateful(&config(None), 42, 21, &mut state);
+                assert!(lines(&buf).iter().all(|line| line.trim().is_empty()));
+            }
+
+            #[test]
+            fn 浮かんだ行数ぶんだけ下端から見える() {
+                let config = config(None);
+                let mut state = HeartState::default();
+                for _ in 0..5 {
+                    state.advance(Duration::from_millis(100));
+                }
+                let buf = render_stateful(&config, 42, 21, &mut state);
+                let lines = lines(&buf);
+                assert!(lines[..16].iter().all(|line| line.trim().is_empty()));
+                assert_eq!(lines[19], love_row(3, &config));
+                assert_eq!(state.message.elapsed, Duration::from_millis(500));
+            }
+
+            #[test]
+            fn 上端を抜けきったら浮かびきったと判定する() {
+                let config = config(None);
+                let widget = HeartWidget::new(&config);
+                let area = Rect::new(0, 0, 42, 21);
+                let state = HeartState {
+                    rise: 41,
+                    ..HeartState::default()
+                };
+                assert!(!widget.is_floated_away(area, &state));
+                let state = HeartState {
+                    rise: 42,
+                    ..HeartState::default()
+                };
+                assert!(widget.is_floated_away(area, &state));
+            }
+        }
+    }
+
+    // ================================================================
+    // to_tui_color: 色の変換の仕様
+    // ================================================================
+
+    mod describe_to_tui_color {
+        use super::*;
+        use rstest::rstest;
+
+        #[rstest]
+        #[case::明るい赤(Color::Red, TuiColor::LightRed)]
+        #[case::暗い赤(Color::DarkRed, TuiColor::Red)]
+        #[case::白(Color::White, TuiColor::White)]
+        #[case::rgb(Color::Rgb { r: 1, g: 2, b: 3 }, TuiColor::Rgb(1, 2, 3))]
+        #[case::既定(Color::Reset, TuiColor::Reset)]
+        fn 同じ見た目の色に変換する(#[case] color: Color, #[case] expected: TuiColor) {
+            assert_eq!(to_tui_color(color), expected);
+        }
+    }
+}
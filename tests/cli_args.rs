@@ -3,6 +3,13 @@ use predicates::prelude::*;
 use rstest::rstest;
 use std::time::Duration;
 
+/// 英語の表示を確かめるため、ロケールを固定して love を起動する
+fn love() -> Command {
+    let mut command = Command::cargo_bin("love").unwrap();
+    command.env("LC_ALL", "C");
+    command
+}
+
 mod describe_cli {
     use super::*;
 
@@ -11,8 +18,7 @@ mod describe_cli {
 
         #[test]
         fn helpフラグでヘルプメッセージを表示する() {
-            Command::cargo_bin("love")
-                .unwrap()
+            love()
                 .arg("--help")
                 .assert()
                 .success()
@@ -23,8 +29,7 @@ mod describe_cli {
 
         #[test]
         fn versionフラグでバージョンを表示する() {
-            Command::cargo_bin("love")
-                .unwrap()
+            love()
                 .arg("--version")
                 .assert()
                 .success()
@@ -32,16 +37,57 @@ mod describe_cli {
         }
     }
 
+    mod 言語を切り替える場合 {
+        use super::*;
+
+        fn help(lang: &str) -> String {
+            let output = Command::cargo_bin("love")
+                .unwrap()
+                .env_remove("LC_ALL")
+                .env_remove("LC_MESSAGES")
+                .env("LANG", lang)
+                .arg("--help")
+                .output()
+                .expect("プロセスの実行に失敗");
+            assert!(output.status.success());
+            String::from_utf8(output.stdout).unwrap()
+        }
+
+        #[test]
+        fn langが日本語なら日本語のヘルプを表示する() {
+            let help = help("ja_JP.UTF-8");
+            assert!(help.contains("ターミナルに浮かぶ、かわいいハート"));
+            assert!(help.contains("使い方: love"));
+            assert!(help.contains("ハートの中に表示するメッセージ"));
+        }
+
+        #[rstest]
+        #[case::英語("en_US.UTF-8")]
+        #[case::訳のない言語("fr_FR.UTF-8")]
+        fn それ以外は英語のヘルプを表示する(#[case] lang: &str) {
+            let help = help(lang);
+            assert!(help.contains("A lovely terminal heart animation"));
+            assert!(help.contains("Usage: love"));
+        }
+
+        #[test]
+        fn サブコマンドのヘルプも訳す() {
+            love()
+                .env("LC_ALL", "ja_JP.UTF-8")
+                .args(["serve", "--help"])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("待ち受けるポート"))
+                .stdout(predicate::str::contains("ヘルプを表示する"));
+        }
+    }
+
     mod 不正なオプションの場合 {
         use super::*;
 
         #[test]
         fn エラーで終了する() {
-            Command::cargo_bin("love")
-                .unwrap()
-                .arg("--invalid")
-                .assert()
-                .failure();
+            love().arg("--invalid").assert().failure();
         }
     }
 
@@ -51,8 +97,7 @@ mod describe_cli {
         #[test]
         fn エラーメッセージを表示して終了する() {
             let long_message = "a".repeat(101);
-            Command::cargo_bin("love")
-                .unwrap()
+            love()
                 .arg("--message")
                 .arg(&long_message)
                 .assert()
@@ -61,6 +106,446 @@ mod describe_cli {
         }
     }
 
+    #[cfg(feature = "serde")]
+    mod 設定を書き出す場合 {
+        use super::*;
+
+        #[test]
+        fn 形式を省略するとjsonで書き出す() {
+            love()
+                .args(["-m", "Hi", "--color", "magenta", "--bold", "--dump-config"])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains(r#""message": "Hi""#))
+                .stdout(predicate::str::contains(r#""color": "magenta""#))
+                .stdout(predicate::str::contains(r#""Bold""#));
+        }
+
+        #[test]
+        fn tomlを指定するとtomlで書き出す() {
+            love()
+                .args(["--petite", "--dump-config", "toml"])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("petite = true"));
+        }
+
+        #[test]
+        fn 制御文字を除いたメッセージを書き出す() {
+            love()
+                .args(["-m", "Hello\x1b[31mWorld", "--dump-config"])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains(r#""message": "Hello[31mWorld""#));
+        }
+
+        #[test]
+        fn 知らない形式はエラーで終了する() {
+            love()
+                .args(["--dump-config", "yaml"])
+                .assert()
+                .failure()
+                .code(2);
+        }
+
+        #[rstest]
+        #[case::json(&["config", "-m", "Hi"], r#""message": "Hi""#)]
+        #[case::toml(&["config", "--format", "toml", "--petite"], "petite = true")]
+        fn configサブコマンドでも書き出す(
+            #[case] args: &[&str],
+            #[case] expected: &str,
+        ) {
+            love()
+                .args(args)
+                .assert()
+                .success()
+                .stdout(predicate::str::contains(expected));
+        }
+    }
+
+    mod 静止画を書き出す場合 {
+        use super::*;
+
+        fn print(args: &[&str]) -> String {
+            let output = love()
+                .arg("print")
+                .args(args)
+                .output()
+                .expect("プロセスの実行に失敗");
+            assert!(output.status.success());
+            String::from_utf8(output.stdout).unwrap()
+        }
+
+        #[test]
+        fn メッセージ入りのハートを1枚だけ書き出す() {
+            let text = print(&["-m", "Hi", "--color", "red"]);
+            assert!(text.contains("Hi"));
+            assert!(text.lines().all(|line| line.contains('v')));
+            assert!(text.lines().all(|line| !line.ends_with(' ')));
+        }
+
+        #[test]
+        fn パイプには色をつけずに書き出す() {
+            assert!(!print(&["--color", "red"]).contains('\x1b'));
+        }
+
+        #[test]
+        fn petiteで小さいハートを書き出す() {
+            assert!(print(&["--petite"]).lines().count() < print(&[]).lines().count());
+        }
+    }
+
+    mod ハートを説明する場合 {
+        use super::*;
+
+        #[test]
+        fn 静止画の代わりに説明文を書き出す() {
+            love()
+                .args(["print", "--describe", "-m", "Hi", "--color", "red"])
+                .assert()
+                .success()
+                .stdout(
+                    "A large red heart made of the letter \"v\", with the message \"Hi\" in the middle.\n",
+                );
+        }
+
+        #[test]
+        fn メッセージのエスケープシーケンスは書き出さない() {
+            love()
+                .args(["print", "--describe", "-m", "\x1b[31mHi\x07"])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains('\x1b').not())
+                .stdout(predicate::str::contains('\x07').not());
+        }
+
+        #[test]
+        fn 日本語でも説明する() {
+            love()
+                .env("LC_ALL", "ja_JP.UTF-8")
+                .args(["print", "--describe", "--petite", "--filled"])
+                .assert()
+                .success()
+                .stdout("塗りつぶした白の小さなハート。メッセージはありません。\n");
+        }
+
+        #[test]
+        #[cfg(feature = "serde")]
+        fn 再生する場合は標準エラー出力に書き出す() {
+            love()
+                .args(["--describe", "--output", "json", "-m", "Hi"])
+                .assert()
+                .success()
+                .stderr(predicate::str::contains("with the message \"Hi\""))
+                .stdout(predicate::str::contains("A large").not());
+        }
+
+        #[rstest]
+        #[case::有効("1")]
+        #[case::無効("0")]
+        fn 動きを控える環境変数はサブコマンドの邪魔をしない(
+            #[case] value: &str,
+        ) {
+            love()
+                .env("LOVE_REDUCED_MOTION", value)
+                .args(["print", "-m", "Hi"])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("Hi"));
+        }
+    }
+
+    mod パレットを使う場合 {
+        use super::*;
+
+        #[test]
+        fn 一覧に全てのパレットと置き換え先の色を書き出す() {
+            let output = love()
+                .arg("palettes")
+                .output()
+                .expect("プロセスの実行に失敗");
+            assert!(output.status.success());
+            let text = String::from_utf8(output.stdout).unwrap();
+
+            let names: Vec<&str> = text
+                .lines()
+                .filter_map(|line| line.split_whitespace().next())
+                .collect();
+            assert_eq!(
+                names,
+                ["deuteranopia", "protanopia", "tritanopia", "high-contrast"]
+            );
+            // パイプには色の見本の代わりに色の値を書き出す
+            assert!(text.lines().all(|line| line.matches('#').count() == 7));
+            assert!(!text.contains('\x1b'));
+        }
+
+        #[test]
+        fn 一覧の説明も訳す() {
+            love()
+                .env("LC_ALL", "ja_JP.UTF-8")
+                .arg("palettes")
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("暗い背景の上で見やすい"));
+        }
+
+        #[test]
+        #[cfg(feature = "serde")]
+        fn 設定にパレットを含める() {
+            love()
+                .args(["config", "--format", "toml", "--palette", "high-contrast"])
+                .assert()
+                .success()
+                .stdout(predicate::str::contains(r#"palette = "high-contrast""#));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod アニメーションを書き出す場合 {
+        use super::*;
+
+        fn export(args: &[&str]) -> Vec<serde_json::Value> {
+            let output = love()
+                .arg("export")
+                .args(args)
+                .output()
+                .expect("プロセスの実行に失敗");
+            assert!(output.status.success());
+            String::from_utf8(output.stdout)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).expect("1行に1つのJSON"))
+                .collect()
+        }
+
+        #[test]
+        fn 指定した広さのフレームをjsonで書き出す() {
+            let frames = export(&["--width", "40", "--height", "12", "-m", "Hi"]);
+            assert_eq!(frames.len(), 12 + 20);
+            assert!(frames.iter().all(|frame| frame["width"] == 40));
+        }
+
+        #[test]
+        fn asciicastはヘッダと時刻つきのイベントを書き出す() {
+            let lines = export(&["--format", "asciicast", "--width", "40", "--height", "12"]);
+            assert_eq!(lines[0]["version"], 2);
+            assert_eq!(lines[0]["width"], 40);
+            assert_eq!(lines[0]["height"], 12);
+            // フレームごとのイベントと、最後にカーソルを戻すイベント
+            assert_eq!(lines.len(), 1 + 12 + 20 + 1);
+            assert_eq!(lines[1][0], 0.0);
+            assert_eq!(lines[2][0], 0.3);
+            assert!(lines[1..].iter().all(|event| event[1] == "o"));
+        }
+
+        #[test]
+        fn ファイルを指定するとファイルに書き出す() {
+            let path =
+                std::env::temp_dir().join(format!("love-export-{}.json", std::process::id()));
+            love()
+                .arg("export")
+                .arg(&path)
+                .args(["--width", "30", "--height", "10"])
+                .assert()
+                .success()
+                .stdout("");
+            let written = std::fs::read_to_string(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(written.lines().count(), 10 + 20);
+        }
+
+        #[test]
+        fn 動きを控えるとハートを中央に置いたままにする() {
+            let frames = export(&["--width", "40", "--height", "12", "--reduced-motion"]);
+            assert_eq!(frames.len(), 12 + 20);
+            assert_eq!(frames[5]["cells"], frames[20]["cells"]);
+            assert_ne!(frames[0]["cells"], frames[5]["cells"]);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod フレームをjsonで書き出す場合 {
+        use super::*;
+
+        fn frames(args: &[&str]) -> Vec<serde_json::Value> {
+            let output = love()
+                .args(args)
+                .args(["--output", "json"])
+                .output()
+                .expect("プロセスの実行に失敗");
+            assert!(output.status.success());
+            String::from_utf8(output.stdout)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).expect("1行に1つのJSON"))
+                .collect()
+        }
+
+        #[test]
+        fn ハートが抜けきるまで1行に1フレームずつ書き出す() {
+            let frames = frames(&[]);
+            // パイプに書き出す場合は80x24で、画面の高さとハートの大きさぶん
+            assert_eq!(frames.len(), 24 + 20);
+            for frame in &frames {
+                assert_eq!(frame["width"], 80);
+                assert_eq!(frame["height"], 24);
+                assert_eq!(frame["cells"].as_array().unwrap().len(), 80 * 24);
+                assert_eq!(frame["delay_ms"], 300);
+            }
+        }
+
+        #[test]
+        fn 文字と色を書き出す() {
+            let frames = frames(&["-m", "Hi", "--color", "red"]);
+            let heart: Vec<&serde_json::Value> = frames[30]["cells"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|cell| cell["ch"] == "v")
+                .collect();
+            assert!(!heart.is_empty());
+            assert!(heart.iter().all(|cell| cell["fg"] == "red"));
+        }
+
+        #[rstest]
+        #[case::オプション(&["--reduced-motion"], "")]
+        #[case::環境変数(&[], "1")]
+        fn 動きを控えるとハートを中央に置いたままにする(
+            #[case] args: &[&str],
+            #[case] env: &str,
+        ) {
+            let output = love()
+                .args(args)
+                .args(["--output", "json"])
+                .env("LOVE_REDUCED_MOTION", env)
+                .output()
+                .expect("プロセスの実行に失敗");
+            assert!(output.status.success());
+            let frames: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect();
+            assert_eq!(frames.len(), 24 + 20);
+            assert_eq!(frames[10]["cells"], frames[30]["cells"]);
+        }
+
+        #[rstest]
+        #[case::スクリーンセーバー(&["--screensaver"])]
+        #[case::プレイリスト(&["--messages-from-stdin"])]
+        fn 続けて再生するモードとは併用できない(#[case] args: &[&str]) {
+            love()
+                .args(args)
+                .args(["--output", "json"])
+                .assert()
+                .failure()
+                .code(2);
+        }
+    }
+
+    mod 補完スクリプトとmanページを書き出す場合 {
+        use super::*;
+
+        fn stdout(args: &[&str]) -> String {
+            let output = love().args(args).output().expect("プロセスの実行に失敗");
+            assert!(output.status.success());
+            String::from_utf8(output.stdout).unwrap()
+        }
+
+        // fish は `-l message` の形でオプションを補完する
+        #[rstest]
+        #[case::bash("bash", "--message")]
+        #[case::zsh("zsh", "--message")]
+        #[case::fish("fish", "-l message")]
+        #[case::powershell("powershell", "--message")]
+        #[case::elvish("elvish", "--message")]
+        fn 各シェルの補完スクリプトにオプションを含める(
+            #[case] shell: &str,
+            #[case] option: &str,
+        ) {
+            let script = stdout(&["completions", shell]);
+            assert!(!script.is_empty());
+            assert!(script.contains(option), "{} に {} がない", shell, option);
+        }
+
+        #[rstest]
+        #[case::bash("bash")]
+        #[case::zsh("zsh")]
+        #[case::fish("fish")]
+        fn 色名を補完の候補に含める(#[case] shell: &str) {
+            let script = stdout(&["completions", shell]);
+            for color in tolove_ru::COLOR_NAMES {
+                assert!(script.contains(color), "{} に {} がない", shell, color);
+            }
+        }
+
+        #[test]
+        fn manページをroff形式で書き出す() {
+            let page = stdout(&["man"]);
+            assert!(page.contains(".TH love 1"));
+            assert!(page.contains("love \\- A lovely terminal heart animation."));
+            assert!(page.contains("\\-\\-message"));
+        }
+    }
+
+    mod ラブレターを送る場合 {
+        use super::*;
+        use std::io::{BufRead, BufReader, Read};
+        use std::process::{Command as Process, Stdio};
+
+        #[test]
+        fn 待ち受けている相手にハートが届く() {
+            let secret = std::env::temp_dir().join(format!("love-secret-{}", std::process::id()));
+            std::fs::write(&secret, "s3cret\n").unwrap();
+
+            let mut listener = Process::new(assert_cmd::cargo::cargo_bin("love"))
+                .args(["listen", "--port", "0", "--secret-file"])
+                .arg(&secret)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .unwrap();
+            // 待ち受けたアドレスは標準エラー出力の1行目に出る
+            let mut started = String::new();
+            BufReader::new(listener.stderr.take().unwrap())
+                .read_line(&mut started)
+                .unwrap();
+            let addr = started.split_whitespace().nth(5).unwrap().to_string();
+            let (host, port) = addr.rsplit_once(':').unwrap();
+
+            let sent = love()
+                .args([
+                    "send",
+                    host,
+                    "--port",
+                    port,
+                    "-m",
+                    "Great demo!",
+                    "--secret-file",
+                ])
+                .arg(&secret)
+                .env("USER", "bob")
+                .timeout(Duration::from_secs(5))
+                .assert();
+            // 届いたハートを流し始めたら止める (流し終わりの表示はユニットテストで確かめている)
+            let mut stdout = listener.stdout.take().unwrap();
+            let mut played = Vec::new();
+            let mut buffer = [0; 4096];
+            while !played.windows(8).any(|w| w == b"\x1b[?1049h") {
+                let n = stdout.read(&mut buffer).unwrap();
+                assert!(n > 0, "ハートが届かない");
+                played.extend_from_slice(&buffer[..n]);
+            }
+            listener.kill().unwrap();
+            listener.wait().unwrap();
+            std::fs::remove_file(&secret).unwrap();
+
+            sent.success()
+                .stderr(predicate::str::contains("Delivered a heart"));
+        }
+    }
+
     mod 有効なオプションで起動した場合 {
         use super::*;
 
@@ -70,9 +555,24 @@ mod describe_cli {
         #[case::短縮メッセージ(&["-m", "Test"])]
         #[case::長形式メッセージ(&["--message", "Test"])]
         #[case::スペース含むメッセージ(&["-m", "Hello World"])]
+        #[case::スクリーンセーバー(&["--screensaver"])]
+        #[case::マーキー(&["-m", "A long message that scrolls", "--marquee", "--marquee-speed", "12"])]
+        #[case::タイプライター(&["-m", "Hi", "--message-effect", "typewriter"])]
+        #[case::フェード(&["-m", "Hi", "--message-effect", "fade"])]
+        #[case::スパークル(&["-m", "Hi", "--message-effect", "sparkle"])]
+        #[case::ビッグテキスト(&["-m", "Love", "--big-text"])]
+        #[case::メッセージの色と装飾(&["-m", "Hi", "--message-color", "yellow", "--message-bg", "blue", "--bold", "--italic", "--underline", "--blink"])]
+        #[case::自動コントラスト(&["-m", "Hi", "--color", "yellow", "--auto-contrast"])]
+        #[case::背景色(&["--background", "blue"])]
+        #[case::塗りつぶし(&["-m", "Hi", "--filled"])]
         #[case::全オプション組み合わせ(&["-m", "Love", "--petite", "--color", "magenta"])]
+        #[case::playサブコマンド(&["play", "-m", "Love", "--petite", "--color", "magenta"])]
+        #[case::playサブコマンドのスクリーンセーバー(&["play", "--screensaver"])]
+        #[case::動きを控える(&["-m", "Hi", "--reduced-motion"])]
+        #[case::パレット(&["-m", "Hi", "--color", "red", "--palette", "deuteranopia"])]
+        #[case::動きを控えるスクリーンセーバー(&["--screensaver", "--reduced-motion"])]
         fn 引数パースエラーなく起動する(#[case] args: &[&str]) {
-            let mut cmd = Command::cargo_bin("love").unwrap();
+            let mut cmd = love();
             for arg in args {
                 cmd.arg(arg);
             }
@@ -90,4 +590,80 @@ mod describe_cli {
             );
         }
     }
+
+    mod メッセージのプレイリストを指定した場合 {
+        use super::*;
+        use std::fs;
+
+        #[test]
+        fn ファイルから読み込んで引数パースエラーなく起動する() {
+            let path =
+                std::env::temp_dir().join(format!("love-playlist-{}.txt", std::process::id()));
+            fs::write(&path, "Thanks Alice\nGreat job Bob\n").unwrap();
+
+            let mut cmd = love();
+            cmd.arg("--messages-file").arg(&path).arg("--shuffle");
+            cmd.timeout(Duration::from_millis(500));
+            let output = cmd.output().expect("プロセスの実行に失敗");
+            fs::remove_file(&path).unwrap();
+
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert_ne!(
+                output.status.code(),
+                Some(2),
+                "引数パースエラー: {}",
+                stderr
+            );
+        }
+
+        #[test]
+        fn 標準入力から読み込んで引数パースエラーなく起動する() {
+            let mut cmd = love();
+            cmd.args(["--messages-from-stdin", "--message-duration", "2"]);
+            cmd.write_stdin("Kudos\nThanks\n");
+            cmd.timeout(Duration::from_millis(500));
+            let output = cmd.output().expect("プロセスの実行に失敗");
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert_ne!(
+                output.status.code(),
+                Some(2),
+                "引数パースエラー: {}",
+                stderr
+            );
+        }
+
+        #[rstest]
+        #[case::messageと併用(&["--message", "Hi", "--messages-from-stdin"])]
+        #[case::入力元を両方指定(&["--messages-file", "a.txt", "--messages-from-stdin"])]
+        #[case::入力元なしで表示時間を指定(&["--message-duration", "3"])]
+        #[case::表示時間がゼロ(&["--messages-from-stdin", "--message-duration", "0"])]
+        #[case::文字色と自動コントラストを併用(&["--message-color", "red", "--auto-contrast"])]
+        #[case::マーキーの速さがゼロ(&["--marquee", "--marquee-speed", "0"])]
+        #[case::知らない色名(&["--color", "purple"])]
+        #[case::サーバーとオプションを併用(&["--message", "Hi", "serve"])]
+        #[case::ポートが数値でない(&["serve", "--port", "http"])]
+        #[case::tcpサーバーの同時接続数がゼロ(&["serve-tcp", "--max-clients", "0"])]
+        #[case::tcpサーバーに使えないオプション(&["serve-tcp", "--screensaver"])]
+        #[case::送り先がない(&["send", "--message", "Hi"])]
+        #[case::送り先が2つ(&["send", "--tty", "/dev/pts/0", "--user", "alice"])]
+        #[case::ホストと端末を併用(&["send", "localhost", "--tty", "/dev/pts/0"])]
+        #[case::端末に合言葉を指定(&["send", "--user", "alice", "--secret-file", "secret.txt"])]
+        #[case::待ち受けに送り先を指定(&["listen", "localhost"])]
+        #[case::デーモンでないのにソケットを指定(&["--control-socket", "/tmp/love.sock"])]
+        #[case::デーモンとスクリーンセーバーを併用(&["--daemon", "--screensaver"])]
+        #[case::操作がない(&["ctl"])]
+        #[case::知らない操作(&["ctl", "reboot"])]
+        #[case::操作に知らない色名(&["ctl", "color", "purple"])]
+        #[case::オプションの後にサブコマンド(&["--petite", "print"])]
+        #[case::静止画に使えないオプション(&["print", "--screensaver"])]
+        #[case::知らない書き出し形式(&["export", "--format", "gif"])]
+        #[case::書き出す広さがゼロ(&["export", "--width", "0"])]
+        #[case::知らないシェル(&["completions", "tcsh"])]
+        #[case::シェルがない(&["completions"])]
+        #[case::スクリーンセーバーを説明する(&["--describe", "--screensaver"])]
+        #[case::知らないパレット(&["print", "--palette", "sepia"])]
+        fn 不正な組み合わせはエラーで終了する(#[case] args: &[&str]) {
+            love().args(args).assert().failure().code(2);
+        }
+    }
 }
@@ -3,6 +3,19 @@ use predicates::prelude::*;
 use rstest::rstest;
 use std::time::Duration;
 
+// 失敗の種類ごとの終了コード (tolove_ru::Error::exit_code)
+const EXIT_VALIDATION: i32 = 65;
+const EXIT_CONFIG: i32 = 78;
+const EXIT_NETWORK: i32 = 69;
+const EXIT_EXPORT: i32 = 73;
+
+/// 英語の表示を確かめるため、ロケールを固定して love を起動する
+fn love() -> Command {
+    let mut command = Command::cargo_bin("love").unwrap();
+    command.env("LC_ALL", "C");
+    command
+}
+
 mod describe_エラーハンドリング {
     use super::*;
 
@@ -14,15 +27,55 @@ mod describe_エラーハンドリング {
         #[case::dos攻撃(1000)]
         fn エラーメッセージを表示して終了する(#[case] length: usize) {
             let msg = "a".repeat(length);
-            Command::cargo_bin("love")
-                .unwrap()
+            love()
                 .arg("--message")
                 .arg(&msg)
                 .assert()
                 .failure()
+                .code(EXIT_VALIDATION)
                 .stderr(predicate::str::contains("Message too long"))
                 .stderr(predicate::str::contains("max 100 characters"));
         }
+
+        #[test]
+        fn 日本語のロケールでは日本語で表示する() {
+            love()
+                .env("LC_ALL", "ja_JP.UTF-8")
+                .arg("--message")
+                .arg("a".repeat(101))
+                .assert()
+                .failure()
+                .code(EXIT_VALIDATION)
+                .stderr("エラー: メッセージが長すぎます (最大100文字)\n");
+        }
+
+        #[rstest]
+        #[case::play(&["play"])]
+        #[case::print(&["print"])]
+        #[case::export(&["export"])]
+        #[case::config(&["config"])]
+        fn サブコマンドでもエラーメッセージを表示して終了する(
+            #[case] args: &[&str],
+        ) {
+            love()
+                .args(args)
+                .args(["--message", &"a".repeat(101)])
+                .assert()
+                .failure()
+                .code(EXIT_VALIDATION)
+                .stderr(predicate::str::contains("Message too long"));
+        }
+
+        #[test]
+        fn tcpサーバーも待ち受けずに終了する() {
+            love()
+                .args(["serve-tcp", "--port", "0", "--message", &"a".repeat(101)])
+                .timeout(Duration::from_secs(5))
+                .assert()
+                .failure()
+                .code(EXIT_VALIDATION)
+                .stderr(predicate::str::contains("Message too long"));
+        }
     }
 
     mod エスケープシーケンスを含むメッセージの場合 {
@@ -32,7 +85,7 @@ mod describe_エラーハンドリング {
         fn サニタイズされて引数パースエラーなく起動する() {
             // ヌルバイトはOSレベルでCLI引数に含められないため、
             // ユニットテスト（lib.rs）でカバーしている
-            let mut cmd = Command::cargo_bin("love").unwrap();
+            let mut cmd = love();
             cmd.arg("--message").arg("Hello\x1b[31mWorld");
             cmd.timeout(Duration::from_millis(500));
             let output = cmd.output().expect("プロセスの実行に失敗");
@@ -46,4 +99,269 @@ mod describe_エラーハンドリング {
             );
         }
     }
+
+    mod メッセージのプレイリストが不正な場合 {
+        use super::*;
+        use std::fs;
+
+        #[test]
+        fn 長すぎる行は行番号つきのエラーを表示して終了する() {
+            let path = std::env::temp_dir().join(format!("love-long-{}.txt", std::process::id()));
+            fs::write(&path, format!("ok\n{}\n", "a".repeat(101))).unwrap();
+
+            let assert = love().arg("--messages-file").arg(&path).assert();
+            fs::remove_file(&path).unwrap();
+
+            assert
+                .failure()
+                .code(EXIT_VALIDATION)
+                .stderr(predicate::str::contains("Line 2"))
+                .stderr(predicate::str::contains("Message too long"));
+        }
+
+        #[test]
+        fn 存在しないファイルはエラーを表示して終了する() {
+            love()
+                .arg("--messages-file")
+                .arg("/nonexistent/love-messages.txt")
+                .assert()
+                .failure()
+                .code(EXIT_CONFIG)
+                .stderr(predicate::str::contains("Failed to open"));
+        }
+
+        #[test]
+        fn 空の標準入力はエラーを表示して終了する() {
+            love()
+                .arg("--messages-from-stdin")
+                .write_stdin("\n\n")
+                .assert()
+                .failure()
+                .code(EXIT_VALIDATION)
+                .stderr(predicate::str::contains("No messages found"));
+        }
+    }
+
+    mod 不明なエフェクト名の場合 {
+        use super::*;
+
+        #[test]
+        fn 選択肢を表示して終了する() {
+            love()
+                .args(["--message-effect", "blink"])
+                .assert()
+                .failure()
+                .code(2)
+                .stderr(predicate::str::contains("typewriter, fade, sparkle"));
+        }
+    }
+
+    mod 不明な色名の場合 {
+        use super::*;
+
+        #[test]
+        fn 引数のエラーとして選択肢を表示して終了する() {
+            love()
+                .args(["--color", "purple"])
+                .assert()
+                .failure()
+                .code(2)
+                .stderr(predicate::str::contains("Unknown color 'purple'"));
+        }
+    }
+
+    mod 待ち受けられない場合 {
+        use super::*;
+        use std::net::TcpListener;
+
+        #[rstest]
+        #[cfg_attr(feature = "serde", case::http("serve"))]
+        #[case::tcp("serve-tcp")]
+        #[case::ラブレター("listen")]
+        fn 使用中のポートはエラーを表示して終了する(#[case] command: &str) {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = listener.local_addr().unwrap().port().to_string();
+
+            love()
+                .args([command, "--port", &port])
+                .timeout(Duration::from_secs(5))
+                .assert()
+                .failure()
+                .code(EXIT_NETWORK)
+                .stderr(predicate::str::contains("Network I/O failed"));
+        }
+    }
+
+    #[cfg(unix)]
+    mod ハートを送れない場合 {
+        use super::*;
+
+        #[rstest]
+        #[case::端末でないファイル(&["send", "--tty", "/etc/passwd"], "is not a terminal")]
+        #[case::端末でないデバイス(&["send", "--tty", "/dev/null"], "is not a terminal")]
+        #[case::いないユーザー(&["send", "--user", "no-such-user-love"], "Unknown user")]
+        fn 書き込まずにエラーを表示して終了する(
+            #[case] args: &[&str],
+            #[case] expected: &str,
+        ) {
+            love()
+                .args(args)
+                .assert()
+                .failure()
+                .code(EXIT_VALIDATION)
+                .stdout(predicate::str::is_empty())
+                .stderr(predicate::str::contains(expected));
+        }
+    }
+
+    mod ラブレターを届けられない場合 {
+        use super::*;
+        use std::net::TcpListener;
+
+        #[test]
+        fn 待ち受けていない相手にはエラーを表示して終了する() {
+            // 一度使って閉じたポートには誰も待ち受けていない
+            let port = TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+                .port()
+                .to_string();
+
+            love()
+                .args(["send", "127.0.0.1", "--port", &port, "-m", "Hi"])
+                .timeout(Duration::from_secs(10))
+                .assert()
+                .failure()
+                .code(EXIT_NETWORK)
+                .stderr(predicate::str::contains("Network I/O failed"));
+        }
+
+        #[rstest]
+        #[case::送る側(&["send", "127.0.0.1", "-m", "Hi"])]
+        #[case::待ち受ける側(&["listen", "--port", "0"])]
+        fn 合言葉のファイルがなければ通信せずに終了する(
+            #[case] args: &[&str],
+        ) {
+            love()
+                .args(args)
+                .args(["--secret-file", "/nonexistent/love-secret"])
+                .timeout(Duration::from_secs(5))
+                .assert()
+                .failure()
+                .code(EXIT_CONFIG)
+                .stderr(predicate::str::contains("Failed to read secret file"));
+        }
+    }
+
+    mod 書き出せない場合 {
+        use super::*;
+
+        #[test]
+        fn 書き出し先を作れなければエラーを表示して終了する() {
+            love()
+                .args(["export", "/nonexistent/love.json"])
+                .assert()
+                .failure()
+                .code(EXIT_EXPORT)
+                .stderr(predicate::str::contains("/nonexistent/love.json"));
+        }
+    }
+
+    #[cfg(not(feature = "serde"))]
+    mod serde機能なしでビルドした場合 {
+        use super::*;
+
+        #[rstest]
+        #[case::設定の書き出し(&["--dump-config"])]
+        #[case::configサブコマンド(&["config"])]
+        #[case::jsonでの書き出し(&["--output", "json"])]
+        #[case::ブラウザ向けの配信(&["serve", "--port", "0"])]
+        fn serdeを使う機能はエラーを表示して終了する(#[case] args: &[&str]) {
+            love()
+                .args(args)
+                .timeout(Duration::from_secs(5))
+                .assert()
+                .failure()
+                .code(EXIT_CONFIG)
+                .stderr(predicate::str::contains("serde feature"));
+        }
+    }
+
+    #[cfg(unix)]
+    mod デーモンを操作できない場合 {
+        use super::*;
+
+        #[test]
+        fn デーモンが動いていなければエラーを表示して終了する() {
+            love()
+                .args(["ctl", "--socket", "/nonexistent/love.sock", "burst"])
+                .assert()
+                .failure()
+                .code(EXIT_NETWORK)
+                .stderr(predicate::str::contains("No love --daemon is listening"));
+        }
+
+        #[test]
+        fn 長すぎるメッセージは送らずに終了する() {
+            love()
+                .args(["ctl", "--socket", "/nonexistent/love.sock", "message"])
+                .arg("a".repeat(101))
+                .assert()
+                .failure()
+                .code(EXIT_VALIDATION)
+                .stderr(predicate::str::contains("Message too long"));
+        }
+
+        #[test]
+        fn ソケットの場所にファイルがあれば消さずに終了する() {
+            let path =
+                std::env::temp_dir().join(format!("love-daemon-{}.sock", std::process::id()));
+            std::fs::write(&path, "keep me").unwrap();
+
+            let assert = love()
+                .arg("--daemon")
+                .arg("--control-socket")
+                .arg(&path)
+                .timeout(Duration::from_secs(5))
+                .assert();
+            let kept = std::fs::read_to_string(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            assert
+                .failure()
+                .code(EXIT_NETWORK)
+                .stderr(predicate::str::contains("is not a socket"));
+            assert_eq!(kept, "keep me");
+        }
+    }
+
+    mod フォントファイルが不正な場合 {
+        use super::*;
+        use std::fs;
+
+        #[test]
+        fn 存在しないファイルはエラーを表示して終了する() {
+            love()
+                .args(["-m", "Hi", "--font", "/nonexistent/love.flf"])
+                .assert()
+                .failure()
+                .code(EXIT_CONFIG)
+                .stderr(predicate::str::contains("Failed to open"));
+        }
+
+        #[test]
+        fn figletフォントでないファイルはエラーを表示して終了する() {
+            let path = std::env::temp_dir().join(format!("love-font-{}.flf", std::process::id()));
+            fs::write(&path, "not a font\n").unwrap();
+
+            let assert = love().args(["-m", "Hi", "--font"]).arg(&path).assert();
+            fs::remove_file(&path).unwrap();
+
+            assert
+                .failure()
+                .code(EXIT_CONFIG)
+                .stderr(predicate::str::contains("Not a FIGlet font"));
+        }
+    }
 }